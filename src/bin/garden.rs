@@ -20,8 +20,11 @@ fn cmd_main() -> Result<()> {
     let mut options = cli::MainOptions::parse();
     options.update();
 
-    // Handle the "completion" and "init" commands before building the context.
+    // Handle the "check", "completion" and "init" commands before building the context.
     match options.command.clone() {
+        cli::Command::Check(check) => {
+            return cmds::check::main(&options, &check);
+        }
         cli::Command::Completion(completion) => {
             return cmds::completion::main(&options, &completion);
         }
@@ -33,18 +36,25 @@ fn cmd_main() -> Result<()> {
 
     let app = model::ApplicationContext::from_options(&options)?;
     match options.command {
+        cli::Command::Check(_) => Ok(()), // Handled above
         cli::Command::Cmd(mut cmd) => cmds::cmd::main_cmd(&app, &mut cmd),
         cli::Command::Completion(_) => Ok(()), // Handled above
+        cli::Command::Config(mut config) => cmds::config::main(&app, &mut config),
         cli::Command::Custom(args) => cmds::cmd::main_custom(&app, &args),
         cli::Command::Eval(eval) => cmds::eval::main(&app, &eval),
         cli::Command::Exec(mut exec) => cmds::exec::main(&app, &mut exec),
+        cli::Command::Export(mut export) => cmds::export::main(&app, &mut export),
         cli::Command::Git(mut git) => cmds::git::main(&app, &mut git),
         cli::Command::Grow(grow) => cmds::grow::main(&app, &grow),
         cli::Command::Gui(ref args) => cmds::gui::main(&options, args),
+        cli::Command::Import(import) => cmds::import::main(&app, &import),
         cli::Command::Init(_) => Ok(()), // Handled above
         cli::Command::List(mut list) => cmds::list::main(&app, &mut list),
+        cli::Command::Lock(mut lock) => cmds::lock::main(&app, &mut lock),
         cli::Command::Plant(plant) => cmds::plant::main(&app, &plant),
         cli::Command::Prune(mut prune) => cmds::prune::main(&app, &mut prune),
         cli::Command::Shell(shell) => cmds::shell::main(&app, &shell),
+        cli::Command::Status(mut status) => cmds::status::main(&app, &mut status),
+        cli::Command::Sync(mut sync) => cmds::sync::main(&app, &mut sync),
     }
 }
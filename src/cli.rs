@@ -1,6 +1,6 @@
 use clap::{Parser, Subcommand, ValueHint};
 
-use crate::{cmds, constants, model, path};
+use crate::{cmds, constants, model, path, syntax};
 
 #[derive(Clone, Debug, Default, Parser)]
 #[command(name = constants::GARDEN)]
@@ -137,12 +137,63 @@ pub struct Arguments {
     pub args: Vec<String>,
 }
 
+/// Filter trees by their Git state post-query
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct TreeFilterOptions {
+    /// Only use trees with uncommitted changes or untracked files
+    #[arg(long, conflicts_with = "clean")]
+    pub dirty: bool,
+    /// Only use trees without uncommitted changes or untracked files
+    #[arg(long)]
+    pub clean: bool,
+    /// Only use trees that do not exist on disk
+    #[arg(long, conflicts_with = "present")]
+    pub missing: bool,
+    /// Only use trees that exist on disk
+    #[arg(long)]
+    pub present: bool,
+    /// Only use trees whose current branch matches a glob pattern
+    #[arg(long = "branch", value_name = "PATTERN")]
+    pub branch_pattern: Option<glob::Pattern>,
+    /// Only use trees with commits that have not been pushed to their upstream branch
+    #[arg(long)]
+    pub ahead: bool,
+    /// Only use trees with upstream commits that have not been merged
+    #[arg(long)]
+    pub behind: bool,
+    /// Only use trees with a Git remote matching the specified name
+    #[arg(long, value_name = "NAME")]
+    pub has_remote: Option<String>,
+    /// Only use trees modified within the specified duration, e.g. "30m", "2h" or "1d"
+    #[arg(long, value_name = "DURATION", value_parser = syntax::parse_duration)]
+    pub modified_within: Option<std::time::Duration>,
+}
+
+impl TreeFilterOptions {
+    /// Return true when any of the filters have been specified.
+    pub fn is_active(&self) -> bool {
+        self.dirty
+            || self.clean
+            || self.missing
+            || self.present
+            || self.branch_pattern.is_some()
+            || self.ahead
+            || self.behind
+            || self.has_remote.is_some()
+            || self.modified_within.is_some()
+    }
+}
+
 #[derive(Clone, Debug, Subcommand)]
 pub enum Command {
+    /// Validate garden configuration files
+    Check(cmds::check::CheckOptions),
     /// Run custom commands over gardens
     Cmd(cmds::cmd::CmdOptions),
     /// Generate shell completions
     Completion(cmds::completion::CompletionOptions),
+    /// Inspect the garden configuration
+    Config(cmds::config::ConfigOptions),
     /// Custom commands
     #[command(external_subcommand)]
     Custom(Vec<String>),
@@ -150,17 +201,23 @@ pub enum Command {
     Eval(cmds::eval::EvalOptions),
     /// Run commands inside garden environments
     Exec(cmds::exec::ExecOptions),
+    /// Export trees as a repo manifest, vcstool file or submodules
+    Export(cmds::export::ExportOptions),
     /// Execute Git commands
     Git(cmds::git::GitOptions),
     /// Grow garden worktrees into existence
     Grow(cmds::grow::GrowOptions),
     /// Garden GUI (run "garden-gui --help" for more details)
     Gui(Arguments),
+    /// Import trees from submodules, repo manifests and vcstool files
+    Import(cmds::import::ImportOptions),
     /// Initialize a "garden.yaml" garden configuration file
     Init(cmds::init::InitOptions),
     /// List available gardens, groups, trees and commands
     #[command(name = "ls")]
     List(cmds::list::ListOptions),
+    /// Record the commit that is checked out in each tree
+    Lock(cmds::lock::LockOptions),
     /// Add pre-existing worktrees to a garden configuration file
     Plant(cmds::plant::PlantOptions),
     /// Remove unreferenced Git repositories
@@ -168,6 +225,10 @@ pub enum Command {
     /// Open a shell in a garden environment
     #[command(alias = "sh")]
     Shell(cmds::shell::ShellOptions),
+    /// Display the status of trees
+    Status(cmds::status::StatusOptions),
+    /// Fetch and fast-forward trees to their upstream branches
+    Sync(cmds::sync::SyncOptions),
 }
 
 impl std::default::Default for Command {
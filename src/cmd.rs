@@ -135,7 +135,7 @@ where
             path = tree.path_as_ref()?;
 
             // Sparse gardens/missing trees are okay -> skip these entries.
-            if !display::print_tree(tree, &display_options) {
+            if !display::print_tree(tree, None, &display_options) {
                 return Ok(());
             }
         } else {
@@ -145,7 +145,7 @@ where
         path = tree.path_as_ref()?;
 
         // Sparse gardens/missing trees are okay -> skip these entries.
-        if !display::print_tree(tree, &display_options) {
+        if !display::print_tree(tree, None, &display_options) {
             return Ok(());
         }
     } else {
@@ -0,0 +1,774 @@
+use anyhow::Result;
+use clap::Parser;
+use yaml_rust::Yaml;
+use yansi::Paint;
+
+use crate::{cli, config, constants, errors, eval, model, path, syntax};
+
+/// Validate garden configuration files
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct CheckOptions {}
+
+/// Keys that are allowed at the top-level of a garden file.
+const TOP_LEVEL_KEYS: &[&str] = &[
+    constants::COMMANDS,
+    constants::ENVIRONMENT,
+    constants::GARDEN,
+    constants::GARDENS,
+    constants::GRAFTS,
+    constants::GROUPS,
+    constants::TEMPLATES,
+    constants::TREES,
+    constants::VARIABLES,
+];
+
+/// Keys that are allowed in the "garden" block.
+const GARDEN_BLOCK_KEYS: &[&str] = &[
+    constants::INCLUDES,
+    constants::INTERACTIVE_SHELL,
+    constants::ROOT,
+    constants::SHELL,
+    constants::SHELL_ERREXIT,
+    constants::SHELL_WORDSPLIT,
+    constants::TREE_BRANCHES,
+];
+
+/// Keys that are allowed in "garden.includes" entries.
+const INCLUDE_KEYS: &[&str] = &[constants::IF, constants::OPTIONAL, constants::PATH];
+
+/// Keys that are allowed in tree definitions.
+const TREE_KEYS: &[&str] = &[
+    constants::BARE,
+    constants::BRANCH,
+    constants::BRANCHES,
+    constants::COMMANDS,
+    constants::COMMIT,
+    constants::DEFAULT_REMOTE,
+    constants::DEPTH,
+    constants::DESCRIPTION,
+    constants::ENVIRONMENT,
+    constants::EXTEND,
+    constants::FILTER,
+    constants::GITCONFIG,
+    constants::LINKS,
+    constants::PATH,
+    constants::REMOTES,
+    constants::REPLACE,
+    constants::SINGLE_BRANCH,
+    constants::SPARSE_CHECKOUT,
+    constants::SUBMODULES,
+    constants::SYMLINK,
+    constants::TAG,
+    constants::TAGS,
+    constants::TEMPLATES,
+    constants::URL,
+    constants::VARIABLES,
+    constants::WORKTREE,
+];
+
+/// Keys that are allowed in the "templates" entries of tree definitions.
+const TEMPLATE_ENTRY_KEYS: &[&str] = &[constants::ARGS, constants::NAME];
+
+/// Keys that are allowed in template definitions.
+const TEMPLATE_KEYS: &[&str] = &[
+    constants::BARE,
+    constants::BRANCH,
+    constants::BRANCHES,
+    constants::COMMANDS,
+    constants::COMMIT,
+    constants::DEFAULT_REMOTE,
+    constants::DEPTH,
+    constants::DESCRIPTION,
+    constants::ENVIRONMENT,
+    constants::EXTEND,
+    constants::FILTER,
+    constants::GITCONFIG,
+    constants::LINKS,
+    constants::PARAMETERS,
+    constants::REMOTES,
+    constants::SINGLE_BRANCH,
+    constants::SPARSE_CHECKOUT,
+    constants::SUBMODULES,
+    constants::SYMLINK,
+    constants::TAG,
+    constants::TAGS,
+    constants::URL,
+    constants::VARIABLES,
+    constants::WORKTREE,
+];
+
+/// Keys that are allowed in garden definitions.
+const GARDEN_KEYS: &[&str] = &[
+    constants::COMMANDS,
+    constants::ENVIRONMENT,
+    constants::GITCONFIG,
+    constants::GROUPS,
+    constants::TREES,
+    constants::VARIABLES,
+];
+
+/// Keys that are allowed in graft definitions.
+const GRAFT_KEYS: &[&str] = &[constants::CONFIG, constants::ROOT];
+
+/// Keys that are allowed in variables defined using the "value" and "required" keys.
+const VARIABLE_KEYS: &[&str] = &[constants::REQUIRED, constants::VALUE];
+
+/// Problems reported by "garden check". The codes are stable and can be used
+/// to identify a class of problems across releases.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Code {
+    /// E001: an unknown key was found.
+    UnknownKey,
+    /// E002: a tree that does not exist is referenced.
+    UndefinedTree,
+    /// E003: a group that does not exist is referenced.
+    UndefinedGroup,
+    /// E004: a template that does not exist is referenced.
+    UndefinedTemplate,
+    /// E005: the config file for a graft does not exist.
+    MissingGraftConfig,
+    /// E006: a pre or post-command refers to a command that does not exist.
+    UndefinedCommand,
+    /// E007: a garden file could not be read.
+    UnreadableFile,
+    /// E008: a template argument does not match any of the template's parameters.
+    UnknownTemplateParameter,
+    /// E009: a field has a value that is not valid for the field.
+    InvalidValue,
+    /// W001: a wildcard pattern does not match anything.
+    UnmatchedPattern,
+    /// W002: a "garden.includes" file does not exist.
+    MissingInclude,
+    /// W003: a pre or post-command is defined for a command that does not exist.
+    MissingBaseCommand,
+}
+
+impl Code {
+    /// Return the stable code for a diagnostic.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::UnknownKey => "E001",
+            Self::UndefinedTree => "E002",
+            Self::UndefinedGroup => "E003",
+            Self::UndefinedTemplate => "E004",
+            Self::MissingGraftConfig => "E005",
+            Self::UndefinedCommand => "E006",
+            Self::UnreadableFile => "E007",
+            Self::UnknownTemplateParameter => "E008",
+            Self::InvalidValue => "E009",
+            Self::UnmatchedPattern => "W001",
+            Self::MissingInclude => "W002",
+            Self::MissingBaseCommand => "W003",
+        }
+    }
+
+    /// Return true when the diagnostic is an error rather than a warning.
+    pub fn is_error(&self) -> bool {
+        !matches!(
+            self,
+            Self::UnmatchedPattern | Self::MissingInclude | Self::MissingBaseCommand
+        )
+    }
+}
+
+/// A problem found in a garden file.
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub code: Code,
+    pub path: std::path::PathBuf,
+    pub key: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = if self.code.is_error() {
+            format!("error[{}]", self.code.as_str())
+                .red()
+                .bold()
+                .to_string()
+        } else {
+            format!("warning[{}]", self.code.as_str())
+                .yellow()
+                .bold()
+                .to_string()
+        };
+        write!(
+            f,
+            "{}: {}: {}: {}",
+            label,
+            self.path.display(),
+            self.key.bold(),
+            self.message
+        )
+    }
+}
+
+/// Main entry point for the "garden check" command.
+pub fn main(options: &cli::MainOptions, _check_options: &CheckOptions) -> Result<()> {
+    // Grafts are read by the checker so that missing grafts can be reported
+    // alongside the other problems rather than aborting the load.
+    let app_context = model::ApplicationContext::from_options_without_grafts(options)?;
+    let diagnostics = check(&app_context)?;
+
+    let num_errors = diagnostics
+        .iter()
+        .filter(|diag| diag.code.is_error())
+        .count();
+    let num_warnings = diagnostics.len() - num_errors;
+    for diagnostic in &diagnostics {
+        println!("{diagnostic}");
+    }
+    if !options.quiet && (options.verbose > 0 || !diagnostics.is_empty()) {
+        eprintln!("{num_errors} error(s), {num_warnings} warning(s)");
+    }
+    if num_errors > 0 {
+        return Err(errors::GardenError::ExitStatus(errors::EX_CONFIG).into());
+    }
+
+    Ok(())
+}
+
+/// Read grafts and check the configuration files associated with an ApplicationContext.
+/// Grafts must not have been read into the ApplicationContext yet.
+pub(crate) fn check(
+    app_context: &model::ApplicationContext,
+) -> Result<Vec<Diagnostic>, errors::GardenError> {
+    let mut checker = Checker {
+        app_context,
+        diagnostics: Vec::new(),
+        visited: model::IndexSet::new(),
+    };
+    checker.read_grafts(app_context.get_root_id())?;
+    checker.check_config(app_context.get_root_id());
+
+    Ok(checker.diagnostics)
+}
+
+/// Walk over garden files and record diagnostics.
+struct Checker<'a> {
+    app_context: &'a model::ApplicationContext,
+    diagnostics: Vec<Diagnostic>,
+    visited: model::IndexSet<std::path::PathBuf>,
+}
+
+impl Checker<'_> {
+    /// Read grafts recursively. Grafts whose config file is missing are skipped.
+    /// They are reported when the file that defines them is checked.
+    fn read_grafts(&mut self, id: model::ConfigId) -> Result<(), errors::GardenError> {
+        let mut details = Vec::new();
+        {
+            let config = self.app_context.get_config(id);
+            for (graft_name, graft) in &config.grafts {
+                let path_str = config.eval_config_path(self.app_context, &graft.config);
+                let path = std::path::PathBuf::from(&path_str);
+                if !path.exists() {
+                    continue;
+                }
+                let root = if graft.root.is_empty() {
+                    None
+                } else {
+                    Some(std::path::PathBuf::from(graft.root.clone()))
+                };
+                details.push((graft_name.clone(), path, root));
+            }
+        }
+        for (graft_name, path, root) in details {
+            let graft_id =
+                self.app_context
+                    .add_graft_config(id, &graft_name, &path, root.as_deref())?;
+            self.read_grafts(graft_id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Record a diagnostic. Paths are reported relative to the current directory.
+    fn report(&mut self, code: Code, path: &std::path::Path, key: String, message: String) {
+        self.diagnostics.push(Diagnostic {
+            code,
+            path: path::relative_path(path),
+            key,
+            message,
+        });
+    }
+
+    /// Check the garden file for a configuration and the files that it includes.
+    fn check_config(&mut self, id: model::ConfigId) {
+        let config = self.app_context.get_config(id);
+        if let Some(path) = config.path.as_ref() {
+            self.check_file(config, path, None);
+        }
+    }
+
+    /// Check a single garden file and the files that it includes.
+    fn check_file(
+        &mut self,
+        config: &model::Configuration,
+        path: &std::path::Path,
+        current_include: Option<&std::path::Path>,
+    ) {
+        let canonical_path = path::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        if !self.visited.insert(canonical_path) {
+            return;
+        }
+        let doc = match config::reader::read_yaml(path) {
+            Ok(doc) => doc,
+            Err(err) => {
+                self.report(Code::UnreadableFile, path, String::new(), err.to_string());
+                return;
+            }
+        };
+        self.check_keys(path, &doc, "", TOP_LEVEL_KEYS);
+        self.check_keys(
+            path,
+            &doc[constants::GARDEN],
+            constants::GARDEN,
+            GARDEN_BLOCK_KEYS,
+        );
+        self.check_variables(path, &doc[constants::VARIABLES], constants::VARIABLES);
+        self.check_commands(config, path, &doc[constants::COMMANDS], constants::COMMANDS);
+        self.check_templates(config, path, &doc[constants::TEMPLATES]);
+        self.check_trees(config, path, &doc[constants::TREES]);
+        self.check_groups(config, path, &doc[constants::GROUPS]);
+        self.check_gardens(config, path, &doc[constants::GARDENS]);
+        self.check_grafts(config, path, &doc[constants::GRAFTS]);
+        self.check_includes(config, path, current_include, &doc);
+    }
+
+    /// Report keys in a hash that are not present in the list of known keys.
+    fn check_keys(&mut self, path: &std::path::Path, yaml: &Yaml, prefix: &str, keys: &[&str]) {
+        let Yaml::Hash(hash) = yaml else {
+            return;
+        };
+        for key in hash.keys() {
+            let Some(key_str) = key.as_str() else {
+                continue;
+            };
+            if !keys.contains(&key_str) {
+                self.report(
+                    Code::UnknownKey,
+                    path,
+                    join_key(prefix, key_str),
+                    format!("unknown key \"{key_str}\""),
+                );
+            }
+        }
+    }
+
+    /// Check variables defined using the "value" and "required" keys.
+    fn check_variables(&mut self, path: &std::path::Path, yaml: &Yaml, prefix: &str) {
+        for (name, value) in hash_entries(yaml) {
+            if let Yaml::Hash(_) = value {
+                self.check_keys(path, value, &join_key(prefix, name), VARIABLE_KEYS);
+            }
+        }
+    }
+
+    /// Check that pre and post-commands refer to existing commands.
+    fn check_commands(
+        &mut self,
+        config: &model::Configuration,
+        path: &std::path::Path,
+        yaml: &Yaml,
+        prefix: &str,
+    ) {
+        for (name, value) in hash_entries(yaml) {
+            if !syntax::is_pre_or_post_command(name) {
+                continue;
+            }
+            let key = join_key(prefix, name);
+            // "garden <name>" runs the pre and post-commands on their own when <name>
+            // is not defined so this is only reported as a warning.
+            let base_name = &name[..name.len() - 1];
+            if !command_exists(config, base_name) {
+                self.report(
+                    Code::MissingBaseCommand,
+                    path,
+                    key.clone(),
+                    format!("\"{name}\" has no base command: \"{base_name}\""),
+                );
+            }
+            for command_name in string_values(value) {
+                if !command_exists(config, command_name) {
+                    self.report(
+                        Code::UndefinedCommand,
+                        path,
+                        key.clone(),
+                        format!("\"{name}\" refers to an undefined command: \"{command_name}\""),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Check template definitions.
+    fn check_templates(
+        &mut self,
+        config: &model::Configuration,
+        path: &std::path::Path,
+        yaml: &Yaml,
+    ) {
+        for (name, value) in hash_entries(yaml) {
+            let prefix = join_key(constants::TEMPLATES, name);
+            self.check_keys(path, value, &prefix, TEMPLATE_KEYS);
+            self.check_tree_fields(config, path, value, &prefix);
+            for template_name in string_values(&value[constants::EXTEND]) {
+                if !config.templates.contains_key(template_name) {
+                    self.report(
+                        Code::UndefinedTemplate,
+                        path,
+                        join_key(&prefix, constants::EXTEND),
+                        format!("undefined template: \"{template_name}\""),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Check tree definitions.
+    fn check_trees(&mut self, config: &model::Configuration, path: &std::path::Path, yaml: &Yaml) {
+        for (name, value) in hash_entries(yaml) {
+            let prefix = join_key(constants::TREES, name);
+            self.check_keys(path, value, &prefix, TREE_KEYS);
+            self.check_tree_fields(config, path, value, &prefix);
+            self.check_tree_templates(config, path, &value[constants::TEMPLATES], &prefix);
+            if let Some(extend) = value[constants::EXTEND].as_str() {
+                if !config.trees.contains_key(extend) {
+                    self.report(
+                        Code::UndefinedTree,
+                        path,
+                        join_key(&prefix, constants::EXTEND),
+                        format!("undefined tree: \"{extend}\""),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Check the templates used by a tree and the arguments passed to them.
+    fn check_tree_templates(
+        &mut self,
+        config: &model::Configuration,
+        path: &std::path::Path,
+        yaml: &Yaml,
+        prefix: &str,
+    ) {
+        let key = join_key(prefix, constants::TEMPLATES);
+        let entries = match yaml {
+            Yaml::Array(entries) => entries.iter().collect(),
+            _ => vec![yaml],
+        };
+        for entry in entries {
+            self.check_keys(path, entry, &key, TEMPLATE_ENTRY_KEYS);
+            let Some(template_name) = entry.as_str().or_else(|| entry[constants::NAME].as_str())
+            else {
+                continue;
+            };
+            let Some(template) = config.templates.get(template_name) else {
+                self.report(
+                    Code::UndefinedTemplate,
+                    path,
+                    key.clone(),
+                    format!("undefined template: \"{template_name}\""),
+                );
+                continue;
+            };
+            for (arg, _) in hash_entries(&entry[constants::ARGS]) {
+                if !template.parameters.contains_key(arg) {
+                    self.report(
+                        Code::UnknownTemplateParameter,
+                        path,
+                        join_key(&key, constants::ARGS),
+                        format!("template \"{template_name}\" has no parameter \"{arg}\""),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Check fields that are common to trees and templates.
+    fn check_tree_fields(
+        &mut self,
+        config: &model::Configuration,
+        path: &std::path::Path,
+        value: &Yaml,
+        prefix: &str,
+    ) {
+        self.check_variables(
+            path,
+            &value[constants::VARIABLES],
+            &join_key(prefix, constants::VARIABLES),
+        );
+        self.check_commands(
+            config,
+            path,
+            &value[constants::COMMANDS],
+            &join_key(prefix, constants::COMMANDS),
+        );
+        if let Some(worktree) = value[constants::WORKTREE].as_str() {
+            let parent = eval::value(self.app_context, config, worktree);
+            if !config.trees.contains_key(&parent) {
+                self.report(
+                    Code::UndefinedTree,
+                    path,
+                    join_key(prefix, constants::WORKTREE),
+                    format!("undefined worktree parent: \"{parent}\""),
+                );
+            }
+        }
+        let submodules = &value[constants::SUBMODULES];
+        if !submodules.is_badvalue() && !config::reader::get_submodule_mode(submodules, &mut None) {
+            self.report(
+                Code::InvalidValue,
+                path,
+                join_key(prefix, constants::SUBMODULES),
+                "invalid value: expected true, false or \"recursive\"".to_string(),
+            );
+        }
+    }
+
+    /// Check group definitions.
+    fn check_groups(&mut self, config: &model::Configuration, path: &std::path::Path, yaml: &Yaml) {
+        for (name, value) in hash_entries(yaml) {
+            let key = join_key(constants::GROUPS, name);
+            for member in string_values(value) {
+                self.check_tree_reference(config, path, &key, member);
+            }
+        }
+    }
+
+    /// Check garden definitions.
+    fn check_gardens(
+        &mut self,
+        config: &model::Configuration,
+        path: &std::path::Path,
+        yaml: &Yaml,
+    ) {
+        for (name, value) in hash_entries(yaml) {
+            let prefix = join_key(constants::GARDENS, name);
+            self.check_keys(path, value, &prefix, GARDEN_KEYS);
+            self.check_variables(
+                path,
+                &value[constants::VARIABLES],
+                &join_key(&prefix, constants::VARIABLES),
+            );
+            self.check_commands(
+                config,
+                path,
+                &value[constants::COMMANDS],
+                &join_key(&prefix, constants::COMMANDS),
+            );
+            let key = join_key(&prefix, constants::GROUPS);
+            for group in string_values(&value[constants::GROUPS]) {
+                if is_pattern(group) {
+                    if !config
+                        .groups
+                        .keys()
+                        .any(|name| matches_pattern(group, name))
+                    {
+                        self.report(
+                            Code::UnmatchedPattern,
+                            path,
+                            key.clone(),
+                            format!("pattern does not match any groups: \"{group}\""),
+                        );
+                    }
+                } else if !config.groups.contains_key(group) {
+                    self.report(
+                        Code::UndefinedGroup,
+                        path,
+                        key.clone(),
+                        format!("undefined group: \"{group}\""),
+                    );
+                }
+            }
+            let key = join_key(&prefix, constants::TREES);
+            for tree in string_values(&value[constants::TREES]) {
+                self.check_tree_reference(config, path, &key, tree);
+            }
+        }
+    }
+
+    /// Check that a tree name or pattern refers to an existing tree.
+    fn check_tree_reference(
+        &mut self,
+        config: &model::Configuration,
+        path: &std::path::Path,
+        key: &str,
+        tree: &str,
+    ) {
+        let (config, tree_name) = if syntax::is_graft(tree) {
+            match config.get_graft_id(tree) {
+                Ok((graft_id, remainder)) => (self.app_context.get_config(graft_id), remainder),
+                Err(_) => {
+                    // Grafts with missing config files are reported separately.
+                    let graft_defined = syntax::split_graft(tree)
+                        .map(|(graft_name, _)| config.contains_graft(graft_name))
+                        .unwrap_or(false);
+                    if !graft_defined {
+                        self.report(
+                            Code::UndefinedTree,
+                            path,
+                            key.to_string(),
+                            format!("undefined graft: \"{tree}\""),
+                        );
+                    }
+                    return;
+                }
+            }
+        } else {
+            (config, tree)
+        };
+        if is_pattern(tree_name) {
+            if !config
+                .trees
+                .keys()
+                .any(|name| matches_pattern(tree_name, name))
+            {
+                self.report(
+                    Code::UnmatchedPattern,
+                    path,
+                    key.to_string(),
+                    format!("pattern does not match any trees: \"{tree}\""),
+                );
+            }
+        } else if !config.trees.contains_key(tree_name) {
+            self.report(
+                Code::UndefinedTree,
+                path,
+                key.to_string(),
+                format!("undefined tree: \"{tree}\""),
+            );
+        }
+    }
+
+    /// Check graft definitions and the garden files for each graft.
+    fn check_grafts(&mut self, config: &model::Configuration, path: &std::path::Path, yaml: &Yaml) {
+        for (name, value) in hash_entries(yaml) {
+            let key = join_key(constants::GRAFTS, name);
+            let graft_config_expr = match value {
+                Yaml::String(graft_config_expr) => graft_config_expr.as_str(),
+                Yaml::Hash(_) => {
+                    self.check_keys(path, value, &key, GRAFT_KEYS);
+                    value[constants::CONFIG].as_str().unwrap_or_default()
+                }
+                _ => "",
+            };
+            let graft_path_str = config.eval_config_path(self.app_context, graft_config_expr);
+            let graft_path = std::path::PathBuf::from(&graft_path_str);
+            if graft_config_expr.is_empty() || !graft_path.exists() {
+                self.report(
+                    Code::MissingGraftConfig,
+                    path,
+                    key,
+                    format!(
+                        "graft config file not found: {:?}",
+                        path::relative_path(&graft_path)
+                    ),
+                );
+                continue;
+            }
+            if let Some(graft_id) = config.grafts.get(name).and_then(|graft| graft.get_id()) {
+                self.check_config(graft_id);
+            }
+        }
+    }
+
+    /// Check the files specified in "garden.includes".
+    fn check_includes(
+        &mut self,
+        config: &model::Configuration,
+        path: &std::path::Path,
+        current_include: Option<&std::path::Path>,
+        doc: &Yaml,
+    ) {
+        let key = join_key(constants::GARDEN, constants::INCLUDES);
+        let yaml = &doc[constants::GARDEN][constants::INCLUDES];
+        match yaml {
+            Yaml::Array(entries) => {
+                for entry in entries {
+                    self.check_keys(path, entry, &key, INCLUDE_KEYS);
+                }
+            }
+            _ => self.check_keys(path, yaml, &key, INCLUDE_KEYS),
+        }
+        for include in config::reader::get_includes(yaml) {
+            if !config.eval_include_condition(self.app_context, &include) {
+                continue;
+            }
+            for include_path in
+                config.eval_include_pathbufs(self.app_context, current_include, &include)
+            {
+                if include_path.exists() {
+                    self.check_file(config, &include_path, Some(&include_path));
+                } else {
+                    self.report(
+                        Code::MissingInclude,
+                        path,
+                        key.clone(),
+                        format!(
+                            "include file not found: {:?}",
+                            path::relative_path(&include_path)
+                        ),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Join a dotted key prefix and a key.
+fn join_key(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{prefix}.{key}")
+    }
+}
+
+/// Return the string keys and values from a `Yaml::Hash`.
+fn hash_entries(yaml: &Yaml) -> Vec<(&str, &Yaml)> {
+    match yaml {
+        Yaml::Hash(hash) => hash
+            .iter()
+            .filter_map(|(key, value)| key.as_str().map(|key_str| (key_str, value)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Return the strings from a `Yaml::String` or `Yaml::Array<Yaml::String>`.
+fn string_values(yaml: &Yaml) -> Vec<&str> {
+    match yaml {
+        Yaml::String(value) => vec![value.as_str()],
+        Yaml::Array(values) => values.iter().filter_map(|value| value.as_str()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Return true if the string contains glob wildcards.
+fn is_pattern(string: &str) -> bool {
+    glob::Pattern::escape(string) != string
+}
+
+/// Return true if the name matches the specified glob pattern.
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    glob::Pattern::new(pattern)
+        .map(|pattern| pattern.matches(name))
+        .unwrap_or(false)
+}
+
+/// Return true if a command is defined in any scope of the configuration.
+fn command_exists(config: &model::Configuration, name: &str) -> bool {
+    config.commands.contains_key(name)
+        || config
+            .trees
+            .values()
+            .any(|tree| tree.commands.contains_key(name))
+        || config
+            .gardens
+            .values()
+            .any(|garden| garden.commands.contains_key(name))
+}
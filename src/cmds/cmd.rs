@@ -25,6 +25,8 @@ pub struct CmdOptions {
     /// Filter trees by name post-query using a glob pattern
     #[arg(long, short, default_value = "*")]
     trees: String,
+    #[command(flatten)]
+    filters: cli::TreeFilterOptions,
     /// Set variables using 'name=value' expressions
     #[arg(long, short = 'D')]
     define: Vec<String>,
@@ -93,6 +95,8 @@ pub struct CustomOptions {
     /// Filter trees by name post-query using a glob pattern
     #[arg(long, short, default_value = "*")]
     trees: String,
+    #[command(flatten)]
+    filters: cli::TreeFilterOptions,
     /// Do not pass "-e" to the shell.
     /// Prevent the "errexit" shell option from being set. By default, the "-e" option
     /// is passed to the configured shell so that multi-line and multi-statement
@@ -162,6 +166,7 @@ pub fn main_cmd(app_context: &model::ApplicationContext, options: &mut CmdOption
     }
     let mut params: CmdParams = options.clone().into();
     params.update(&app_context.options)?;
+    check_variable_cycles(app_context, std::slice::from_ref(&options.query), &params)?;
 
     let exit_status = if options.num_jobs.is_some() {
         cmd_parallel(app_context, &options.query, &params)?
@@ -181,6 +186,7 @@ pub struct CmdParams {
     arguments: Vec<String>,
     queries: Vec<String>,
     tree_pattern: glob::Pattern,
+    filters: cli::TreeFilterOptions,
     breadth_first: bool,
     dry_run: bool,
     force: bool,
@@ -205,6 +211,7 @@ impl From<CmdOptions> for CmdParams {
             dry_run: options.dry_run,
             echo: options.echo,
             exit_on_error: options.exit_on_error,
+            filters: options.filters.clone(),
             force: options.force,
             keep_going: options.keep_going,
             num_jobs: options.num_jobs,
@@ -234,6 +241,7 @@ impl From<CustomOptions> for CmdParams {
             dry_run: options.dry_run,
             echo: options.echo,
             exit_on_error: options.exit_on_error,
+            filters: options.filters.clone(),
             force: options.force,
             keep_going: options.keep_going,
             num_jobs: options.num_jobs,
@@ -324,6 +332,7 @@ pub fn main_custom(app_context: &model::ApplicationContext, arguments: &Vec<Stri
 fn cmd(app_context: &model::ApplicationContext, query: &str, params: &CmdParams) -> Result<u32> {
     let config = app_context.get_root_config_mut();
     let contexts = query::resolve_trees(app_context, config, None, query);
+    let contexts = query::filter_trees(app_context, contexts, &params.filters);
     if params.breadth_first {
         run_cmd_breadth_first(app_context, &contexts, params)
     } else {
@@ -339,6 +348,7 @@ fn cmd_parallel(
 ) -> Result<u32> {
     let config = app_context.get_root_config_mut();
     let contexts = query::resolve_trees(app_context, config, None, query);
+    let contexts = query::filter_trees(app_context, contexts, &params.filters);
     if params.breadth_first {
         run_cmd_breadth_first_parallel(app_context, &contexts, params)
     } else {
@@ -435,15 +445,25 @@ fn get_tree_from_context<'a>(
     if !params.tree_pattern.matches(&context.tree) {
         return None;
     }
+    let (config, tree) = get_config_and_tree(app_context, context)?;
     // Skip symlink trees.
+    if tree.is_symlink {
+        return None;
+    }
+
+    Some((config, tree))
+}
+
+/// Return the configuration and tree for a tree context.
+pub(crate) fn get_config_and_tree<'a>(
+    app_context: &'a model::ApplicationContext,
+    context: &model::TreeContext,
+) -> Option<(&'a model::Configuration, &'a model::Tree)> {
     let config = match context.config {
         Some(config_id) => app_context.get_config(config_id),
         None => app_context.get_root_config(),
     };
     let tree = config.trees.get(&context.tree)?;
-    if tree.is_symlink {
-        return None;
-    }
 
     Some((config, tree))
 }
@@ -469,7 +489,7 @@ fn get_command_environment<'a>(
         verbose: params.verbose,
         ..std::default::Default::default()
     };
-    if !display::print_tree(tree, &display_options) {
+    if !display::print_tree(tree, None, &display_options) {
         // The "--force" option runs commands in a fallback directory when the tree does not exist.
         if params.force {
             fallback_path = Some(config.fallback_execdir_string());
@@ -764,8 +784,44 @@ fn run_cmd_vec(
     Ok(())
 }
 
+/// Evaluate the environment and commands for every tree ahead of time and
+/// report variable cycles before any commands are run.
+fn check_variable_cycles(
+    app_context: &model::ApplicationContext,
+    queries: &[String],
+    params: &CmdParams,
+) -> Result<(), errors::GardenError> {
+    // Evaluate using a copy to avoid caching values in the original context.
+    let app_context = &app_context.clone();
+    app_context.set_checking_cycles(true);
+    for query in queries {
+        let contexts =
+            query::resolve_trees(app_context, app_context.get_root_config(), None, query);
+        for context in &contexts {
+            let Some((config, _tree)) = get_tree_from_context(app_context, context, params) else {
+                continue;
+            };
+            eval::environment(app_context, config, context);
+            for name in &params.commands {
+                for command_name in cmd::expand_command_names(app_context, context, name) {
+                    eval::command(app_context, context, &command_name);
+                    app_context.get_root_config_mut().reset();
+                }
+            }
+            if let Some(cycle) = app_context.get_variable_cycles().first() {
+                return Err(errors::GardenError::VariableCycle {
+                    cycle: cycle.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
 /// Run cmd() over a Vec of tree queries
 fn cmds(app: &model::ApplicationContext, params: &CmdParams) -> Result<()> {
+    check_variable_cycles(app, &params.queries, params)?;
     let exit_status = atomic::AtomicU32::new(errors::EX_OK);
     if params.num_jobs.is_some() {
         params.queries.par_iter().for_each(|query| {
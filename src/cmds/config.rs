@@ -0,0 +1,585 @@
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use yaml_rust::{yaml, Yaml};
+
+use crate::{config, constants, eval, model, query};
+
+/// Inspect the garden configuration
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct ConfigOptions {
+    #[command(subcommand)]
+    command: ConfigCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Print the fully resolved configuration
+    Dump(DumpOptions),
+}
+
+/// Print the fully resolved configuration
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct DumpOptions {
+    /// Evaluate variables, paths, remotes and commands for the trees matched by the query
+    #[arg(long, short)]
+    evaluate: bool,
+    /// Output format [yaml, json]
+    #[arg(
+        long,
+        short,
+        default_value_t = model::OutputFormat::Yaml,
+        value_name = "FORMAT",
+        value_parser = model::OutputFormat::parse_from_str,
+    )]
+    format: model::OutputFormat,
+    /// Tree query for the gardens, groups or trees to evaluate [default: "@*"]
+    #[arg(requires = "evaluate")]
+    queries: Vec<String>,
+}
+
+/// Main entry point for the "garden config" command
+pub fn main(app_context: &model::ApplicationContext, options: &mut ConfigOptions) -> Result<()> {
+    match &mut options.command {
+        ConfigCommand::Dump(dump_options) => dump(app_context, dump_options),
+    }
+}
+
+/// Print the configuration as YAML or JSON
+fn dump(app_context: &model::ApplicationContext, options: &mut DumpOptions) -> Result<()> {
+    let doc = if options.evaluate {
+        if options.queries.is_empty() {
+            options.queries.push(string!("@*"));
+        }
+        evaluated_config(app_context, &options.queries)
+    } else {
+        raw_config(app_context.get_root_config())
+    };
+    match options.format {
+        model::OutputFormat::Yaml => print!("{}", config::writer::to_yaml_string(&doc)),
+        model::OutputFormat::Json => println!("{}", config::writer::to_json_string(&doc)),
+    }
+
+    Ok(())
+}
+
+/// Return true for variables that are provided by garden rather than the configuration.
+fn is_builtin_variable(name: &str) -> bool {
+    matches!(
+        name,
+        constants::GARDEN_CMD_QUIET
+            | constants::GARDEN_CMD_VERBOSE
+            | constants::GARDEN_CONFIG_DIR
+            | constants::GARDEN_ROOT
+            | constants::TREE_NAME
+            | constants::TREE_PATH
+    )
+}
+
+/// Insert a string entry into a hash.
+fn insert_str(hash: &mut yaml::Hash, key: &str, value: &str) {
+    hash.insert(
+        Yaml::String(key.to_string()),
+        Yaml::String(value.to_string()),
+    );
+}
+
+/// Insert an entry into a hash. Empty hashes and arrays are omitted.
+fn insert_yaml(hash: &mut yaml::Hash, key: &str, value: Yaml) {
+    let is_empty = match &value {
+        Yaml::Hash(value) => value.is_empty(),
+        Yaml::Array(value) => value.is_empty(),
+        _ => false,
+    };
+    if !is_empty {
+        hash.insert(Yaml::String(key.to_string()), value);
+    }
+}
+
+/// Convert a list of strings into a YAML value. Single values are emitted as strings.
+fn strings_to_yaml(values: Vec<String>) -> Yaml {
+    if values.len() == 1 {
+        Yaml::String(values.into_iter().next().unwrap_or_default())
+    } else {
+        Yaml::Array(values.into_iter().map(Yaml::String).collect())
+    }
+}
+
+/// Convert a variable's expression into a YAML value.
+fn variable_to_yaml(config: &model::Configuration, name: &str, var: &model::Variable) -> Yaml {
+    // Variables defined using "garden -D name=value" override all other variables.
+    let var = config.override_variables.get(name).unwrap_or(var);
+    if var.is_required() {
+        let mut hash = yaml::Hash::new();
+        insert_str(&mut hash, constants::VALUE, var.get_expr());
+        hash.insert(
+            Yaml::String(constants::REQUIRED.to_string()),
+            Yaml::Boolean(true),
+        );
+        Yaml::Hash(hash)
+    } else {
+        Yaml::String(var.get_expr().to_string())
+    }
+}
+
+/// Convert a map of variables into a YAML hash.
+fn variables_to_yaml(config: &model::Configuration, variables: &model::VariableMap) -> Yaml {
+    let mut hash = yaml::Hash::new();
+    for (name, var) in variables {
+        if is_builtin_variable(name) {
+            continue;
+        }
+        hash.insert(
+            Yaml::String(name.to_string()),
+            variable_to_yaml(config, name, var),
+        );
+    }
+
+    Yaml::Hash(hash)
+}
+
+/// Convert a list of variables into a YAML value.
+fn variable_list_to_yaml(variables: &[model::Variable]) -> Yaml {
+    strings_to_yaml(
+        variables
+            .iter()
+            .map(|var| var.get_expr().to_string())
+            .collect(),
+    )
+}
+
+/// Convert multi-variables into a YAML hash.
+fn multi_variables_to_yaml(variables: &[model::MultiVariable]) -> Yaml {
+    let mut hash = yaml::Hash::new();
+    for multi_var in variables {
+        let values = multi_var.iter().map(|var| var.get_expr().to_string());
+        hash.insert(
+            Yaml::String(multi_var.get_name().to_string()),
+            strings_to_yaml(values.collect()),
+        );
+    }
+
+    Yaml::Hash(hash)
+}
+
+/// Convert a map of multi-variables into a YAML hash.
+fn multi_variable_map_to_yaml(variables: &model::MultiVariableMap) -> Yaml {
+    let mut hash = yaml::Hash::new();
+    for (name, values) in variables {
+        hash.insert(
+            Yaml::String(name.to_string()),
+            variable_list_to_yaml(values),
+        );
+    }
+
+    Yaml::Hash(hash)
+}
+
+/// Convert a set of strings into a YAML array.
+fn string_set_to_yaml(values: &model::StringSet) -> Yaml {
+    Yaml::Array(values.iter().cloned().map(Yaml::String).collect())
+}
+
+/// Build a YAML document from a configuration with includes and templates flattened.
+fn raw_config(config: &model::Configuration) -> Yaml {
+    let mut doc = yaml::Hash::new();
+
+    // garden block
+    let mut garden = yaml::Hash::new();
+    insert_str(&mut garden, constants::ROOT, config.root.get_expr());
+    insert_str(&mut garden, constants::SHELL, &config.shell);
+    if !config.interactive_shell.is_empty() {
+        insert_str(
+            &mut garden,
+            constants::INTERACTIVE_SHELL,
+            &config.interactive_shell,
+        );
+    }
+    for (key, value) in [
+        (constants::SHELL_ERREXIT, config.shell_exit_on_error),
+        (constants::SHELL_WORDSPLIT, config.shell_word_split),
+        (constants::TREE_BRANCHES, config.tree_branches),
+    ] {
+        garden.insert(Yaml::String(key.to_string()), Yaml::Boolean(value));
+    }
+    insert_yaml(&mut doc, constants::GARDEN, Yaml::Hash(garden));
+
+    // Global variables. Variables defined using "garden -D" are included.
+    let mut variables = config.variables.clone();
+    for (name, var) in &config.override_variables {
+        if !variables.contains_key(name) {
+            variables.insert(name.to_string(), var.clone());
+        }
+    }
+    insert_yaml(
+        &mut doc,
+        constants::VARIABLES,
+        variables_to_yaml(config, &variables),
+    );
+    insert_yaml(
+        &mut doc,
+        constants::ENVIRONMENT,
+        multi_variables_to_yaml(&config.environment),
+    );
+    insert_yaml(
+        &mut doc,
+        constants::COMMANDS,
+        multi_variable_map_to_yaml(&config.commands),
+    );
+
+    // Trees
+    let mut trees = yaml::Hash::new();
+    for (name, tree) in &config.trees {
+        trees.insert(Yaml::String(name.to_string()), raw_tree(config, tree));
+    }
+    insert_yaml(&mut doc, constants::TREES, Yaml::Hash(trees));
+
+    // Groups
+    let mut groups = yaml::Hash::new();
+    for (name, group) in &config.groups {
+        groups.insert(
+            Yaml::String(name.to_string()),
+            string_set_to_yaml(&group.members),
+        );
+    }
+    insert_yaml(&mut doc, constants::GROUPS, Yaml::Hash(groups));
+
+    // Gardens
+    let mut gardens = yaml::Hash::new();
+    for (name, garden) in &config.gardens {
+        let mut hash = yaml::Hash::new();
+        insert_yaml(
+            &mut hash,
+            constants::GROUPS,
+            string_set_to_yaml(&garden.groups),
+        );
+        insert_yaml(
+            &mut hash,
+            constants::TREES,
+            string_set_to_yaml(&garden.trees),
+        );
+        insert_yaml(
+            &mut hash,
+            constants::VARIABLES,
+            variables_to_yaml(config, &garden.variables),
+        );
+        insert_yaml(
+            &mut hash,
+            constants::ENVIRONMENT,
+            multi_variables_to_yaml(&garden.environment),
+        );
+        insert_yaml(
+            &mut hash,
+            constants::GITCONFIG,
+            multi_variable_map_to_yaml(&garden.gitconfig),
+        );
+        insert_yaml(
+            &mut hash,
+            constants::COMMANDS,
+            multi_variable_map_to_yaml(&garden.commands),
+        );
+        gardens.insert(Yaml::String(name.to_string()), Yaml::Hash(hash));
+    }
+    insert_yaml(&mut doc, constants::GARDENS, Yaml::Hash(gardens));
+
+    // Grafts
+    let mut grafts = yaml::Hash::new();
+    for (name, graft) in &config.grafts {
+        let mut hash = yaml::Hash::new();
+        insert_str(&mut hash, constants::CONFIG, &graft.config);
+        if !graft.root.is_empty() {
+            insert_str(&mut hash, constants::ROOT, &graft.root);
+        }
+        grafts.insert(Yaml::String(name.to_string()), Yaml::Hash(hash));
+    }
+    insert_yaml(&mut doc, constants::GRAFTS, Yaml::Hash(grafts));
+
+    Yaml::Hash(doc)
+}
+
+/// Build a YAML hash for a tree using its unevaluated expressions.
+fn raw_tree(config: &model::Configuration, tree: &model::Tree) -> Yaml {
+    let mut hash = yaml::Hash::new();
+    if !tree.description.is_empty() {
+        insert_str(&mut hash, constants::DESCRIPTION, &tree.description);
+    }
+    insert_yaml(&mut hash, constants::TAGS, string_set_to_yaml(&tree.tags));
+    insert_str(&mut hash, constants::PATH, tree.get_path().get_expr());
+    if let Some(url) = tree.remotes.get(&tree.default_remote) {
+        hash.insert(
+            Yaml::String(constants::URL.to_string()),
+            variable_to_yaml(config, constants::URL, url),
+        );
+    }
+    if tree.default_remote != constants::ORIGIN {
+        insert_str(&mut hash, constants::DEFAULT_REMOTE, &tree.default_remote);
+    }
+    let mut remotes = yaml::Hash::new();
+    for (name, remote) in &tree.remotes {
+        if name != &tree.default_remote {
+            insert_str(&mut remotes, name, remote.get_expr());
+        }
+    }
+    insert_yaml(&mut hash, constants::REMOTES, Yaml::Hash(remotes));
+    for (key, var) in [
+        (constants::BRANCH, &tree.branch),
+        (constants::COMMIT, &tree.commit),
+        (constants::TAG, &tree.tag),
+        (constants::WORKTREE, &tree.worktree),
+        (constants::SYMLINK, &tree.symlink),
+    ] {
+        if !var.is_empty() {
+            insert_str(&mut hash, key, var.get_expr());
+        }
+    }
+    insert_yaml(
+        &mut hash,
+        constants::BRANCHES,
+        variables_to_yaml(config, &tree.branches),
+    );
+    if tree.is_bare_repository {
+        hash.insert(
+            Yaml::String(constants::BARE.to_string()),
+            Yaml::Boolean(true),
+        );
+    }
+    if tree.is_single_branch {
+        hash.insert(
+            Yaml::String(constants::SINGLE_BRANCH.to_string()),
+            Yaml::Boolean(true),
+        );
+    }
+    if tree.clone_depth > 0 {
+        hash.insert(
+            Yaml::String(constants::DEPTH.to_string()),
+            Yaml::Integer(tree.clone_depth),
+        );
+    }
+    if !tree.clone_filter.is_empty() {
+        insert_str(&mut hash, constants::FILTER, &tree.clone_filter);
+    }
+    insert_yaml(
+        &mut hash,
+        constants::SPARSE_CHECKOUT,
+        string_set_to_yaml(&tree.sparse_checkout),
+    );
+    match tree.submodules {
+        None | Some(model::SubmoduleMode::None) => (),
+        Some(model::SubmoduleMode::Init) => {
+            hash.insert(
+                Yaml::String(constants::SUBMODULES.to_string()),
+                Yaml::Boolean(true),
+            );
+        }
+        Some(mode @ model::SubmoduleMode::Recursive) => {
+            insert_str(&mut hash, constants::SUBMODULES, &mode.to_string());
+        }
+    }
+    insert_yaml(
+        &mut hash,
+        constants::LINKS,
+        Yaml::Array(
+            tree.links
+                .iter()
+                .map(|link| Yaml::String(link.get_expr().to_string()))
+                .collect(),
+        ),
+    );
+    insert_yaml(
+        &mut hash,
+        constants::VARIABLES,
+        variables_to_yaml(config, &tree.variables),
+    );
+    insert_yaml(
+        &mut hash,
+        constants::ENVIRONMENT,
+        multi_variables_to_yaml(&tree.environment),
+    );
+    insert_yaml(
+        &mut hash,
+        constants::GITCONFIG,
+        multi_variable_map_to_yaml(&tree.gitconfig),
+    );
+    insert_yaml(
+        &mut hash,
+        constants::COMMANDS,
+        multi_variable_map_to_yaml(&tree.commands),
+    );
+
+    Yaml::Hash(hash)
+}
+
+/// Build a YAML document containing evaluated values for the trees matched by the queries.
+fn evaluated_config(app_context: &model::ApplicationContext, queries: &[String]) -> Yaml {
+    let config = app_context.get_root_config();
+    let mut doc = yaml::Hash::new();
+
+    let mut garden = yaml::Hash::new();
+    insert_str(
+        &mut garden,
+        constants::ROOT,
+        &config.root_path.to_string_lossy(),
+    );
+    insert_yaml(&mut doc, constants::GARDEN, Yaml::Hash(garden));
+
+    let mut variables = yaml::Hash::new();
+    for (name, var) in &config.variables {
+        if is_builtin_variable(name) {
+            continue;
+        }
+        let var = config.override_variables.get(name).unwrap_or(var);
+        let value = eval::variable(app_context, config, var);
+        insert_str(&mut variables, name, &value);
+    }
+    insert_yaml(&mut doc, constants::VARIABLES, Yaml::Hash(variables));
+
+    let mut trees = yaml::Hash::new();
+    for query in queries {
+        for context in query::resolve_and_filter_trees(app_context, config, query, "*") {
+            let name = Yaml::String(query::qualified_tree_name(app_context, &context));
+            if trees.contains_key(&name) {
+                continue;
+            }
+            if let Some(tree) = evaluated_tree(app_context, &context) {
+                trees.insert(name, tree);
+            }
+        }
+    }
+    insert_yaml(&mut doc, constants::TREES, Yaml::Hash(trees));
+
+    Yaml::Hash(doc)
+}
+
+/// Build a YAML hash containing the evaluated values for a tree.
+fn evaluated_tree(
+    app_context: &model::ApplicationContext,
+    context: &model::TreeContext,
+) -> Option<Yaml> {
+    let config = match context.config {
+        Some(config_id) => app_context.get_config(config_id),
+        None => app_context.get_root_config(),
+    };
+    let tree = config.trees.get(&context.tree)?;
+    let tree_variable = |var: &model::Variable| {
+        eval::tree_variable(
+            app_context,
+            config,
+            None,
+            &context.tree,
+            context.garden.as_ref(),
+            var,
+        )
+    };
+
+    let mut hash = yaml::Hash::new();
+    if !tree.description.is_empty() {
+        insert_str(&mut hash, constants::DESCRIPTION, &tree.description);
+    }
+    insert_yaml(&mut hash, constants::TAGS, string_set_to_yaml(&tree.tags));
+    if let Ok(path) = tree.path_as_ref() {
+        insert_str(&mut hash, constants::PATH, path);
+    }
+    if let Some(url) = tree.remotes.get(&tree.default_remote) {
+        insert_str(&mut hash, constants::URL, &tree_variable(url));
+    }
+    if tree.default_remote != constants::ORIGIN {
+        insert_str(&mut hash, constants::DEFAULT_REMOTE, &tree.default_remote);
+    }
+    let mut remotes = yaml::Hash::new();
+    for (name, remote) in &tree.remotes {
+        if name != &tree.default_remote {
+            insert_str(&mut remotes, name, &tree_variable(remote));
+        }
+    }
+    insert_yaml(&mut hash, constants::REMOTES, Yaml::Hash(remotes));
+    for (key, var) in [
+        (constants::BRANCH, &tree.branch),
+        (constants::COMMIT, &tree.commit),
+        (constants::TAG, &tree.tag),
+        (constants::WORKTREE, &tree.worktree),
+    ] {
+        if !var.is_empty() {
+            insert_str(&mut hash, key, &tree_variable(var));
+        }
+    }
+    if let Ok(symlink) = tree.symlink_as_ref() {
+        insert_str(&mut hash, constants::SYMLINK, symlink);
+    }
+    let mut branches = yaml::Hash::new();
+    for (name, var) in &tree.branches {
+        insert_str(&mut branches, name, &tree_variable(var));
+    }
+    insert_yaml(&mut hash, constants::BRANCHES, Yaml::Hash(branches));
+    insert_yaml(
+        &mut hash,
+        constants::LINKS,
+        Yaml::Array(
+            tree.links
+                .iter()
+                .map(|link| Yaml::String(tree_variable(link)))
+                .collect(),
+        ),
+    );
+
+    // Variables are reported using the value that they resolve to in the tree's context.
+    let garden = context
+        .garden
+        .as_ref()
+        .and_then(|garden_name| config.gardens.get(garden_name));
+    let mut variable_names = model::StringSet::new();
+    variable_names.extend(tree.variables.keys().cloned());
+    if let Some(garden) = garden {
+        variable_names.extend(garden.variables.keys().cloned());
+    }
+    let mut variables = yaml::Hash::new();
+    for name in &variable_names {
+        if is_builtin_variable(name) {
+            continue;
+        }
+        let value = eval::tree_value(
+            app_context,
+            config,
+            None,
+            &format!("${{{name}}}"),
+            &context.tree,
+            context.garden.as_ref(),
+        );
+        insert_str(&mut variables, name, &value);
+    }
+    insert_yaml(&mut hash, constants::VARIABLES, Yaml::Hash(variables));
+
+    let mut environment = yaml::Hash::new();
+    for (name, value) in eval::environment(app_context, config, context) {
+        insert_str(&mut environment, &name, &value);
+    }
+    insert_yaml(&mut hash, constants::ENVIRONMENT, Yaml::Hash(environment));
+
+    let mut gitconfig = yaml::Hash::new();
+    for (name, values) in &tree.gitconfig {
+        let values = values.iter().map(tree_variable).collect();
+        gitconfig.insert(Yaml::String(name.to_string()), strings_to_yaml(values));
+    }
+    insert_yaml(&mut hash, constants::GITCONFIG, Yaml::Hash(gitconfig));
+
+    // Commands are gathered from the global, tree and garden scopes.
+    let mut command_names = model::StringSet::new();
+    command_names.extend(config.commands.keys().cloned());
+    command_names.extend(tree.commands.keys().cloned());
+    if let Some(garden) = garden {
+        command_names.extend(garden.commands.keys().cloned());
+    }
+    let mut commands = yaml::Hash::new();
+    for name in &command_names {
+        let pattern = glob::Pattern::escape(name);
+        let values = eval::command(app_context, context, &pattern)
+            .into_iter()
+            .flatten()
+            .map(Yaml::String)
+            .collect();
+        commands.insert(Yaml::String(name.to_string()), Yaml::Array(values));
+    }
+    insert_yaml(&mut hash, constants::COMMANDS, Yaml::Hash(commands));
+
+    Some(Yaml::Hash(hash))
+}
@@ -10,6 +10,9 @@ pub struct EvalOptions {
     /// Set variables using 'name=value' expressions
     #[arg(long, short = 'D')]
     define: Vec<String>,
+    /// Print the location where each evaluated variable was defined
+    #[arg(long)]
+    show_origin: bool,
     /// Expression to evaluate
     expr: String,
     /// Tree within which to evaluate
@@ -23,6 +26,9 @@ pub fn main(app_context: &model::ApplicationContext, eval: &EvalOptions) -> Resu
     app_context
         .get_root_config_mut()
         .apply_defines(&eval.define);
+    if eval.show_origin {
+        app_context.record_variable_origins();
+    }
     match eval.tree.as_ref() {
         None => {
             // Evaluate and print the expression in global scope. No trees or gardens
@@ -47,6 +53,14 @@ pub fn main(app_context: &model::ApplicationContext, eval: &EvalOptions) -> Resu
             println!("{value}");
         }
     }
+    // Report the variables that contributed to the value and where they were defined.
+    for origin in app_context.get_variable_origins() {
+        println!("{origin}");
+    }
+    // Variable cycles evaluate to empty strings. Report them as warnings.
+    for cycle in app_context.get_variable_cycles() {
+        eprintln!("warning: variable cycle detected: {cycle}");
+    }
 
     Ok(())
 }
@@ -5,7 +5,7 @@ use clap::{Parser, ValueHint};
 use rayon::prelude::*;
 
 use crate::cli::GardenOptions;
-use crate::{cmd, constants, errors, model, query};
+use crate::{cli, cmd, constants, errors, model, query};
 
 /// Evaluate garden expressions
 #[derive(Parser, Clone, Debug)]
@@ -14,6 +14,8 @@ pub struct ExecOptions {
     /// Filter trees by name post-query using a glob pattern
     #[arg(long, short, default_value = "*")]
     pub(crate) trees: String,
+    #[command(flatten)]
+    pub(crate) filters: cli::TreeFilterOptions,
     /// Perform a trial run without executing any commands
     #[arg(long, short = 'N', short_alias = 'n')]
     pub(crate) dry_run: bool,
@@ -67,6 +69,7 @@ fn exec(app_context: &model::ApplicationContext, exec_options: &ExecOptions) ->
     // Resolve the tree query into a vector of tree contexts.
     let config = app_context.get_root_config_mut();
     let contexts = query::resolve_trees(app_context, config, None, query);
+    let contexts = query::filter_trees(app_context, contexts, &exec_options.filters);
     let pattern = glob::Pattern::new(tree_pattern).unwrap_or_default();
     let exit_status = atomic::AtomicU32::new(errors::EX_OK);
 
@@ -0,0 +1,306 @@
+use anyhow::Result;
+use clap::{Parser, ValueHint};
+use yaml_rust::{yaml, Yaml};
+
+use crate::{cli, config, constants, git, model, path, query};
+
+/// Export trees as a repo manifest, vcstool file or submodules
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct ExportOptions {
+    /// Output format [gitmodules, json, repo-manifest, vcstool]
+    #[arg(
+        long,
+        short,
+        value_name = "FORMAT",
+        value_parser = model::ExportFormat::parse_from_str,
+    )]
+    format: model::ExportFormat,
+    /// Pin each tree to the commit that is currently checked out
+    #[arg(long)]
+    pin: bool,
+    /// Filter trees by name post-query using a glob pattern
+    #[arg(long, short, default_value = "*")]
+    trees: String,
+    #[command(flatten)]
+    filters: cli::TreeFilterOptions,
+    /// Tree queries for the gardens, groups or trees to export [default: "@*"]
+    #[arg(value_hint = ValueHint::Other)]
+    queries: Vec<String>,
+}
+
+/// An evaluated tree that is written by "garden export".
+struct ExportedTree {
+    name: String,
+    /// The tree path relative to the garden root.
+    path: String,
+    url: String,
+    default_remote: String,
+    /// Remotes other than the default remote.
+    remotes: Vec<(String, String)>,
+    branch: String,
+    commit: String,
+    tag: String,
+}
+
+impl ExportedTree {
+    /// Return the revision for formats that accept a branch, tag or commit.
+    fn revision(&self) -> &str {
+        if !self.commit.is_empty() {
+            &self.commit
+        } else if !self.tag.is_empty() {
+            &self.tag
+        } else {
+            &self.branch
+        }
+    }
+
+    /// Warn when the non-default remotes cannot be represented by the output format.
+    fn warn_unexported_remotes(&self, format: &str) {
+        if self.remotes.is_empty() {
+            return;
+        }
+        let names: Vec<&str> = self.remotes.iter().map(|(name, _)| name.as_str()).collect();
+        eprintln!(
+            "warning: {}: remotes cannot be exported to {}: {}",
+            self.name,
+            format,
+            names.join(", ")
+        );
+    }
+}
+
+/// Main entry point for the "garden export" command
+pub fn main(app_context: &model::ApplicationContext, options: &mut ExportOptions) -> Result<()> {
+    if options.queries.is_empty() {
+        options.queries.push(string!("@*"));
+    }
+    let trees = exported_trees(app_context, options);
+    match options.format {
+        model::ExportFormat::Gitmodules => print!("{}", to_gitmodules(&trees)),
+        model::ExportFormat::Json => {
+            println!("{}", config::writer::to_json_string(&to_yaml(&trees)));
+        }
+        model::ExportFormat::RepoManifest => print!("{}", to_repo_manifest(&trees)),
+        model::ExportFormat::Vcstool => {
+            print!("{}", config::writer::to_yaml_string(&to_vcstool(&trees)));
+        }
+    }
+
+    Ok(())
+}
+
+/// Evaluate the trees matched by the tree queries.
+/// Symlink trees and trees without a URL are skipped with a warning.
+fn exported_trees(
+    app_context: &model::ApplicationContext,
+    options: &ExportOptions,
+) -> Vec<ExportedTree> {
+    let config = app_context.get_root_config();
+    let root = path::canonicalize(&config.root_path).unwrap_or(config.root_path.clone());
+    let mut trees = Vec::new();
+    for (name, context, tree) in query::resolve_unique_trees(
+        app_context,
+        &options.queries,
+        &options.trees,
+        &options.filters,
+    ) {
+        if tree.is_symlink {
+            eprintln!("warning: {name}: symlink trees cannot be exported");
+            continue;
+        }
+        let eval_context = model::EvalContext::from_app_context(app_context, &context);
+        let Some(url) = tree.eval_url(&eval_context).filter(|url| !url.is_empty()) else {
+            eprintln!("warning: {name}: tree has no url");
+            continue;
+        };
+        let pathbuf = tree.pathbuf().unwrap_or_default();
+        let mut exported_tree = ExportedTree {
+            name: name.clone(),
+            path: path::strip_prefix_into_string(&root, &pathbuf)
+                .unwrap_or_else(|_| pathbuf.to_string_lossy().to_string()),
+            url,
+            default_remote: tree.default_remote.to_string(),
+            remotes: tree
+                .remotes
+                .iter()
+                .filter(|(remote, _)| **remote != tree.default_remote)
+                .map(|(remote, var)| (remote.to_string(), eval_context.tree_variable(var)))
+                .collect(),
+            branch: tree.eval_branch(&eval_context),
+            commit: String::new(),
+            tag: String::new(),
+        };
+        match tree.eval_pin(&eval_context) {
+            Some(model::TreePin::Commit(commit)) => exported_tree.commit = commit,
+            Some(model::TreePin::Tag(tag)) => exported_tree.tag = tag,
+            None => (),
+        }
+        if options.pin {
+            match git::commit_id(&pathbuf, "HEAD") {
+                Some(commit) => exported_tree.commit = commit,
+                None => eprintln!("warning: {name}: tree does not exist and was not pinned"),
+            }
+        }
+        trees.push(exported_tree);
+    }
+
+    trees
+}
+
+/// Convert the trees into a JSON-compatible YAML document.
+fn to_yaml(trees: &[ExportedTree]) -> Yaml {
+    let string = |value: &str| Yaml::String(value.to_string());
+    let trees = trees
+        .iter()
+        .map(|tree| {
+            let mut remotes = yaml::Hash::new();
+            remotes.insert(string(&tree.default_remote), string(&tree.url));
+            for (remote, url) in &tree.remotes {
+                remotes.insert(string(remote), string(url));
+            }
+            let mut hash = yaml::Hash::new();
+            hash.insert(string("name"), string(&tree.name));
+            hash.insert(string(constants::PATH), string(&tree.path));
+            hash.insert(string(constants::URL), string(&tree.url));
+            hash.insert(
+                string(constants::DEFAULT_REMOTE),
+                string(&tree.default_remote),
+            );
+            hash.insert(string(constants::REMOTES), Yaml::Hash(remotes));
+            hash.insert(string(constants::BRANCH), string(&tree.branch));
+            hash.insert(string(constants::COMMIT), string(&tree.commit));
+            hash.insert(string(constants::TAG), string(&tree.tag));
+            Yaml::Hash(hash)
+        })
+        .collect();
+    let mut doc = yaml::Hash::new();
+    doc.insert(string(constants::TREES), Yaml::Array(trees));
+
+    Yaml::Hash(doc)
+}
+
+/// Convert the trees into a vcstool ".repos" document.
+fn to_vcstool(trees: &[ExportedTree]) -> Yaml {
+    let string = |value: &str| Yaml::String(value.to_string());
+    let mut repositories = yaml::Hash::new();
+    for tree in trees {
+        tree.warn_unexported_remotes("vcstool");
+        let mut hash = yaml::Hash::new();
+        hash.insert(string("type"), string("git"));
+        hash.insert(string(constants::URL), string(&tree.url));
+        if !tree.revision().is_empty() {
+            hash.insert(string("version"), string(tree.revision()));
+        }
+        repositories.insert(string(&tree.path), Yaml::Hash(hash));
+    }
+    let mut doc = yaml::Hash::new();
+    doc.insert(string("repositories"), Yaml::Hash(repositories));
+
+    Yaml::Hash(doc)
+}
+
+/// Convert the trees into a ".gitmodules" file. Submodules record their commits in
+/// the superproject so commit and tag pins are reported as warnings.
+fn to_gitmodules(trees: &[ExportedTree]) -> String {
+    let mut output = String::new();
+    for tree in trees {
+        if !tree.commit.is_empty() || !tree.tag.is_empty() {
+            eprintln!(
+                "warning: {}: .gitmodules files cannot pin commits or tags",
+                tree.name
+            );
+        }
+        tree.warn_unexported_remotes(".gitmodules");
+        output.push_str(&format!("[submodule {}]\n", gitconfig_quote(&tree.path)));
+        output.push_str(&format!("\tpath = {}\n", gitconfig_value(&tree.path)));
+        output.push_str(&format!("\turl = {}\n", gitconfig_value(&tree.url)));
+        if !tree.branch.is_empty() {
+            output.push_str(&format!("\tbranch = {}\n", gitconfig_value(&tree.branch)));
+        }
+    }
+
+    output
+}
+
+/// Convert the trees into a Google "repo" XML manifest. Projects are named relative
+/// to a remote that is created for each distinct fetch URL.
+fn to_repo_manifest(trees: &[ExportedTree]) -> String {
+    // Remotes are keyed by their fetch URL and named after the tree's default remote.
+    let mut remotes: model::IndexMap<&str, String> = model::IndexMap::new();
+    let mut projects = Vec::new();
+    for tree in trees {
+        let Some((fetch, name)) = tree.url.rsplit_once('/') else {
+            eprintln!("warning: {}: unable to export url: {}", tree.name, tree.url);
+            continue;
+        };
+        tree.warn_unexported_remotes("repo manifests");
+        let remote_count = remotes.len();
+        let remote = remotes.entry(fetch).or_insert_with(|| {
+            if remote_count == 0 {
+                tree.default_remote.to_string()
+            } else {
+                format!("{}-{}", tree.default_remote, remote_count)
+            }
+        });
+        let mut project = format!(
+            "  <project name=\"{}\" path=\"{}\" remote=\"{}\"",
+            xml_escape(name),
+            xml_escape(&tree.path),
+            xml_escape(remote)
+        );
+        let revision = if tree.commit.is_empty() && !tree.tag.is_empty() {
+            format!("refs/tags/{}", tree.tag)
+        } else {
+            tree.revision().to_string()
+        };
+        if !revision.is_empty() {
+            project.push_str(&format!(" revision=\"{}\"", xml_escape(&revision)));
+        }
+        project.push_str(" />\n");
+        projects.push(project);
+    }
+
+    let mut output = string!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<manifest>\n");
+    for (fetch, remote) in &remotes {
+        output.push_str(&format!(
+            "  <remote name=\"{}\" fetch=\"{}\" />\n",
+            xml_escape(remote),
+            xml_escape(fetch)
+        ));
+    }
+    if !remotes.is_empty() && !projects.is_empty() {
+        output.push('\n');
+    }
+    for project in projects {
+        output.push_str(&project);
+    }
+    output.push_str("</manifest>\n");
+
+    output
+}
+
+/// Escape a value for use in an XML attribute.
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Quote a value for use in a Git config file.
+fn gitconfig_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Quote a Git config value when it contains characters that Git would otherwise interpret.
+fn gitconfig_value(value: &str) -> String {
+    let needs_quotes = value.trim() != value || value.contains(['#', ';', '"', '\\']);
+    if needs_quotes {
+        gitconfig_quote(value)
+    } else {
+        value.to_string()
+    }
+}
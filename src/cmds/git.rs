@@ -1,6 +1,7 @@
 use anyhow::Result;
 use clap::{Parser, ValueHint};
 
+use crate::cli;
 use crate::cmds::exec;
 use crate::model;
 
@@ -11,6 +12,8 @@ pub struct GitOptions {
     /// Filter trees by name post-query using a glob pattern
     #[arg(long, short, default_value = "*")]
     trees: String,
+    #[command(flatten)]
+    filters: cli::TreeFilterOptions,
     /// Perform a trial run without executing any commands
     #[arg(long, short = 'N', short_alias = 'n')]
     dry_run: bool,
@@ -50,6 +53,7 @@ impl From<GitOptions> for exec::ExecOptions {
                 cmd
             },
             trees: git_options.trees,
+            filters: git_options.filters,
         }
     }
 }
@@ -1,10 +1,14 @@
 /// Grow garden worktrees
+use std::io::Write;
+
 use anyhow::Result;
 use clap::Parser;
+use rayon::prelude::*;
+use yaml_rust::{yaml, Yaml};
 use yansi::Paint;
 
 use crate::{
-    cmd, constants, display, errors, git, model,
+    cmd, config, constants, display, errors, git, model,
     model::{IndexMap, IndexSet, StringSet},
     query,
 };
@@ -18,6 +22,31 @@ pub struct GrowOptions {
     /// Increase verbosity level (default: 0)
     #[arg(short, long, action = clap::ArgAction::Count)]
     verbose: u8,
+    /// Grow trees in parallel using the specified number of jobs.
+    #[arg(
+        long = "jobs",
+        short = 'j',
+        require_equals = false,
+        num_args = 0..=1,
+        default_missing_value = "0",
+        value_name = "JOBS",
+    )]
+    num_jobs: Option<usize>,
+    /// Print the commands that would be run without running them
+    #[arg(long, short = 'N')]
+    dry_run: bool,
+    /// Output format for the dry run plan [json, yaml]
+    #[arg(
+        long,
+        short,
+        requires = "dry_run",
+        value_name = "FORMAT",
+        value_parser = model::OutputFormat::parse_from_str,
+    )]
+    format: Option<model::OutputFormat>,
+    /// Check out the commits recorded in "garden.lock"
+    #[arg(long)]
+    locked: bool,
     /// Filter trees by name post-query using a glob pattern
     #[arg(long, short, default_value = "*")]
     trees: String,
@@ -33,12 +62,29 @@ pub struct GrowOptions {
 pub fn main(app_context: &model::ApplicationContext, options: &GrowOptions) -> Result<()> {
     let quiet = app_context.options.quiet;
     let verbose = app_context.options.verbose + options.verbose;
+    let locked_trees = if options.locked {
+        let lock_path = config::lock::lock_path(app_context)?;
+        Some(config::lock::read(&lock_path)?)
+    } else {
+        None
+    };
+    let locked_trees = locked_trees.as_ref();
+    if options.dry_run {
+        return grow_dry_run(app_context, options, locked_trees, verbose);
+    }
+    if options.num_jobs.is_some() {
+        cmd::initialize_threads_option(options.num_jobs)?;
+        return grow_parallel(app_context, options, locked_trees, quiet, verbose);
+    }
     let mut exit_status = errors::EX_OK;
     let mut configured_worktrees: StringSet = IndexSet::new();
+    let mut output = GrowOutput::default();
     for query in &options.queries {
         let status = grow(
             app_context,
             &mut configured_worktrees,
+            &mut output,
+            locked_trees,
             quiet,
             verbose,
             query,
@@ -54,10 +100,331 @@ pub fn main(app_context: &model::ApplicationContext, options: &GrowOptions) -> R
     errors::exit_status_into_result(exit_status)
 }
 
+/// Print the commands that would be run to grow the trees without running them.
+/// Commands that only read from existing repositories are run to compute the plan.
+fn grow_dry_run(
+    app_context: &model::ApplicationContext,
+    options: &GrowOptions,
+    locked_trees: Option<&config::lock::LockedTrees>,
+    verbose: u8,
+) -> Result<()> {
+    let mut configured_worktrees: StringSet = IndexSet::new();
+    let mut output = GrowOutput::dry_run();
+    for query in &options.queries {
+        grow(
+            app_context,
+            &mut configured_worktrees,
+            &mut output,
+            locked_trees,
+            true,
+            verbose,
+            query,
+            &options.trees,
+            &options.remote,
+        )?;
+    }
+
+    let plan = output.plan.unwrap_or_default();
+    match options.format {
+        Some(model::OutputFormat::Json) => {
+            println!("{}", config::writer::to_json_string(&plan_to_yaml(&plan)));
+        }
+        Some(model::OutputFormat::Yaml) => {
+            print!("{}", config::writer::to_yaml_string(&plan_to_yaml(&plan)));
+        }
+        None => {
+            let mut current_tree = None;
+            for planned in &plan {
+                if current_tree != Some(&planned.tree) {
+                    current_tree = Some(&planned.tree);
+                    println!("{} {}", "#".cyan(), planned.tree.blue().bold());
+                }
+                let quoted_args = planned
+                    .command
+                    .iter()
+                    .map(|arg| cmd::shell_quote(arg))
+                    .collect::<Vec<String>>();
+                println!("{} {}", ":".cyan(), quoted_args.join(" ").green());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Convert a dry run plan into a YAML document.
+fn plan_to_yaml(plan: &[PlannedCommand]) -> Yaml {
+    let string = |value: &str| Yaml::String(value.to_string());
+    let commands = plan
+        .iter()
+        .map(|planned| {
+            let mut hash = yaml::Hash::new();
+            hash.insert(string("tree"), string(&planned.tree));
+            hash.insert(string("directory"), string(&planned.directory));
+            let command = planned.command.iter().map(|arg| string(arg)).collect();
+            hash.insert(string("command"), Yaml::Array(command));
+            Yaml::Hash(hash)
+        })
+        .collect();
+    let mut doc = yaml::Hash::new();
+    doc.insert(string("plan"), Yaml::Array(commands));
+
+    Yaml::Hash(doc)
+}
+
+/// Grow trees in parallel. Worktrees share a repository with their parent worktree
+/// so trees are grouped by their shared repository and each group is grown by a single job.
+fn grow_parallel(
+    app_context: &model::ApplicationContext,
+    options: &GrowOptions,
+    locked_trees: Option<&config::lock::LockedTrees>,
+    quiet: bool,
+    verbose: u8,
+) -> Result<()> {
+    let config = app_context.get_root_config();
+    let remote_pattern = glob::Pattern::new(&options.remote).unwrap_or_default();
+    let mut repositories: IndexMap<String, Vec<model::TreeContext>> = IndexMap::new();
+    for query in &options.queries {
+        let contexts = query::resolve_and_filter_trees(app_context, config, query, &options.trees);
+        for context in contexts {
+            let shared_worktree_path = query::shared_worktree_path(app_context, config, &context);
+            repositories
+                .entry(shared_worktree_path)
+                .or_default()
+                .push(context);
+        }
+    }
+
+    let results: Vec<Result<u32>> = repositories
+        .into_values()
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|contexts| {
+            // Create a thread-specific ApplicationContext.
+            let app_context_clone = app_context.clone();
+            let app_context = &app_context_clone;
+            let mut configured_worktrees: StringSet = IndexSet::new();
+            let mut output = GrowOutput::buffered();
+            let mut exit_status = errors::EX_OK;
+            for tree_context in &contexts {
+                let eval_context = model::EvalContext::from_app_context(app_context, tree_context);
+                let status = grow_tree_from_context(
+                    &eval_context,
+                    &mut configured_worktrees,
+                    &mut output,
+                    locked_trees,
+                    &remote_pattern,
+                    quiet,
+                    verbose,
+                );
+                match status {
+                    Ok(errors::EX_OK) => (),
+                    Ok(status) => exit_status = status,
+                    Err(err) => {
+                        output.flush();
+                        return Err(err);
+                    }
+                }
+            }
+            output.flush();
+
+            Ok(exit_status)
+        })
+        .collect();
+
+    // Return the first error or the last non-zero exit status.
+    let mut exit_status = errors::EX_OK;
+    for result in results {
+        let status = result?;
+        if status != errors::EX_OK {
+            exit_status = status;
+        }
+    }
+
+    errors::exit_status_into_result(exit_status)
+}
+
+/// A command recorded by "garden grow --dry-run".
+struct PlannedCommand {
+    tree: String,
+    directory: String,
+    command: Vec<String>,
+}
+
+/// Output from growing trees. Output is buffered when growing trees in parallel
+/// so that the output from concurrent clones is not interleaved.
+#[derive(Default)]
+struct GrowOutput {
+    /// Buffered (is_stderr, text) entries. Output is displayed immediately when unset.
+    buffer: Option<Vec<(bool, String)>>,
+    /// Commands are recorded into the plan instead of being run during a dry run.
+    plan: Option<Vec<PlannedCommand>>,
+    /// Paths that would have been created by the commands in the dry run plan.
+    planned_paths: std::collections::HashSet<std::path::PathBuf>,
+    /// The name of the tree that is currently being grown.
+    tree: String,
+}
+
+impl GrowOutput {
+    /// Create a GrowOutput that buffers output until flush() is called.
+    fn buffered() -> Self {
+        Self {
+            buffer: Some(Vec::new()),
+            ..Default::default()
+        }
+    }
+
+    /// Create a GrowOutput that records commands instead of running them.
+    fn dry_run() -> Self {
+        Self {
+            plan: Some(Vec::new()),
+            ..Default::default()
+        }
+    }
+
+    /// Return true when commands are recorded instead of being run.
+    fn is_dry_run(&self) -> bool {
+        self.plan.is_some()
+    }
+
+    /// Return true when the dry run plan would have created the path.
+    fn is_planned(&self, path: &std::path::Path) -> bool {
+        self.planned_paths.contains(path)
+    }
+
+    /// Remember that the dry run plan creates the path.
+    fn plan_path(&mut self, path: &std::path::Path) {
+        if self.is_dry_run() {
+            self.planned_paths.insert(path.to_path_buf());
+        }
+    }
+
+    /// Record a command into the dry run plan. Returns false when not performing a dry run.
+    fn record(&mut self, command: &[&str], directory: Option<&std::path::Path>) -> bool {
+        let Some(plan) = &mut self.plan else {
+            return false;
+        };
+        let directory = match directory {
+            Some(directory) => directory.to_string_lossy().to_string(),
+            None => std::env::current_dir()
+                .map(|directory| directory.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        };
+        plan.push(PlannedCommand {
+            tree: self.tree.clone(),
+            directory,
+            command: command.iter().map(|arg| arg.to_string()).collect(),
+        });
+
+        true
+    }
+
+    /// Print a line to stdout.
+    fn println(&mut self, text: String) {
+        match &mut self.buffer {
+            Some(buffer) => buffer.push((false, text)),
+            None => println!("{text}"),
+        }
+    }
+
+    /// Print a line to stderr.
+    fn eprintln(&mut self, text: String) {
+        match &mut self.buffer {
+            Some(buffer) => buffer.push((true, text)),
+            None => eprintln!("{text}"),
+        }
+    }
+
+    /// Print a command from a list of arguments.
+    fn print_command(&mut self, command: &[&str]) {
+        let quoted_args = command
+            .iter()
+            .map(|arg| cmd::shell_quote(arg))
+            .collect::<Vec<String>>();
+        self.print_command_str(&quoted_args.join(" "));
+    }
+
+    /// Print a single command from a string. Commands are not printed during a dry run
+    /// because the plan is printed once it has been computed.
+    fn print_command_str(&mut self, cmd: &str) {
+        if !self.is_dry_run() {
+            self.println(format!("{} {}", ":".cyan(), cmd.green()));
+        }
+    }
+
+    /// Run a command in the specified directory and return its exit status.
+    fn run_command(&mut self, command: &[&str], path: &dyn AsRef<std::path::Path>) -> u32 {
+        if self.record(command, Some(path.as_ref())) {
+            return errors::EX_OK;
+        }
+        if self.buffer.is_none() {
+            return cmd::run_command(command, path.as_ref());
+        }
+        self.status(cmd::exec_in_dir(command, path.as_ref()))
+    }
+
+    /// Run a command in the current directory and return its exit status.
+    fn run_command_in_current_dir(&mut self, command: &[&str]) -> u32 {
+        if self.record(command, None) {
+            return errors::EX_OK;
+        }
+        self.status(cmd::exec_cmd(command))
+    }
+
+    /// Run a command and return its exit status. The command's output is captured
+    /// into the buffer when output is buffered.
+    fn status(&mut self, exec: subprocess::Exec) -> u32 {
+        let Some(buffer) = &mut self.buffer else {
+            return cmd::status(exec);
+        };
+        let capture = exec
+            .stdin(subprocess::Redirection::Null)
+            .stdout(subprocess::Redirection::Pipe)
+            .stderr(subprocess::Redirection::Pipe)
+            .capture();
+        let capture = match capture {
+            Ok(capture) => capture,
+            Err(err) => {
+                return cmd::subprocess_result(Err(err))
+                    .err()
+                    .unwrap_or(errors::EX_ERROR)
+            }
+        };
+        for (is_stderr, text) in [(false, capture.stdout_str()), (true, capture.stderr_str())] {
+            let text = text.trim_end();
+            if !text.is_empty() {
+                buffer.push((is_stderr, text.to_string()));
+            }
+        }
+
+        cmd::exit_status(capture.exit_status)
+    }
+
+    /// Display the buffered output. Stdout and stderr are locked so that the output
+    /// is not interleaved with output from other threads.
+    fn flush(&mut self) {
+        let Some(buffer) = &mut self.buffer else {
+            return;
+        };
+        let mut stdout = std::io::stdout().lock();
+        let mut stderr = std::io::stderr().lock();
+        for (is_stderr, text) in buffer.drain(..) {
+            if is_stderr {
+                writeln!(stderr, "{text}").unwrap_or(());
+            } else {
+                writeln!(stdout, "{text}").unwrap_or(());
+            }
+        }
+    }
+}
+
 /// Create/update trees in the evaluated tree query.
+#[allow(clippy::too_many_arguments)]
 fn grow(
     app_context: &model::ApplicationContext,
     configured_worktrees: &mut StringSet,
+    output: &mut GrowOutput,
+    locked_trees: Option<&config::lock::LockedTrees>,
     quiet: bool,
     verbose: u8,
     query: &str,
@@ -74,6 +441,8 @@ fn grow(
         let status = grow_tree_from_context(
             &eval_context,
             configured_worktrees,
+            output,
+            locked_trees,
             &remote_pattern,
             quiet,
             verbose,
@@ -92,6 +461,8 @@ fn grow(
 fn grow_tree_from_context(
     eval_context: &model::EvalContext,
     configured_worktrees: &mut StringSet,
+    output: &mut GrowOutput,
+    locked_trees: Option<&config::lock::LockedTrees>,
     remote_pattern: &glob::Pattern,
     quiet: bool,
     verbose: u8,
@@ -114,13 +485,27 @@ fn grow_tree_from_context(
         }
     };
 
-    display::print_tree_details(tree, eval_context.config.tree_branches, verbose, quiet);
+    output.tree = tree.get_name().to_string();
+    if !quiet {
+        if let Ok(path) = tree.path_as_ref() {
+            let tree_branches = eval_context.config.tree_branches;
+            output.eprintln(display::display_tree(
+                tree,
+                path,
+                tree_branches,
+                None,
+                verbose,
+            ));
+        }
+    }
     let Some(pathbuf) = tree.pathbuf() else {
-        return Err(errors::GardenError::ConfigurationError(format!(
-            "invalid path for tree: {tree}",
-            tree = tree.get_name()
-        ))
-        .into());
+        return Err(
+            errors::GardenError::ConfigurationError(tree.format_with_location(&format!(
+                "invalid path for tree: {tree}",
+                tree = tree.get_name()
+            )))
+            .into(),
+        );
     };
     let Some(parent) = pathbuf.parent() else {
         return Err(errors::GardenError::OSError(format!(
@@ -128,15 +513,18 @@ fn grow_tree_from_context(
         ))
         .into());
     };
-    std::fs::create_dir_all(parent).map_err(|err| {
-        errors::GardenError::OSError(format!("unable to create {parent:?}: {err}"))
-    })?;
+    if !output.is_dry_run() {
+        std::fs::create_dir_all(parent).map_err(|err| {
+            errors::GardenError::OSError(format!("unable to create {parent:?}: {err}"))
+        })?;
+    }
 
     let branch = tree.eval_branch(eval_context);
-    if !is_empty_tree(&pathbuf) {
+    if output.is_planned(&pathbuf) {
         return update_tree_from_context(
             eval_context,
             configured_worktrees,
+            output,
             &pathbuf,
             &branch,
             remote_pattern,
@@ -145,9 +533,48 @@ fn grow_tree_from_context(
             verbose,
         );
     }
+    if !is_empty_tree(&pathbuf) {
+        exit_status = update_tree_from_context(
+            eval_context,
+            configured_worktrees,
+            output,
+            &pathbuf,
+            &branch,
+            remote_pattern,
+            false,
+            quiet,
+            verbose,
+        )?;
+        // Existing trees are moved to their locked commit but are not moved to their pin.
+        let locked_pin = if tree.is_symlink {
+            None
+        } else {
+            locked_tree_pin(eval_context, locked_trees, output)
+        };
+        if let Some(pin) = &locked_pin {
+            if git::commit_id(&pathbuf, "HEAD") != git::commit_id(&pathbuf, &pin.revision()) {
+                let status = checkout_pin(tree, pin, &pathbuf, output, verbose);
+                if status != errors::EX_OK {
+                    exit_status = status;
+                }
+            }
+        }
+        let status = update_checkout(tree, &pathbuf, output, verbose);
+        if status != errors::EX_OK {
+            exit_status = status;
+        }
+        if locked_pin.is_none() {
+            if let Some(pin) = tree.eval_pin(eval_context) {
+                report_pin_drift(tree, &pin, &pathbuf, output);
+            }
+        }
+        return Ok(exit_status);
+    }
+
+    output.plan_path(&pathbuf);
 
     if tree.is_symlink {
-        let status = grow_symlink(eval_context.app_context, eval_context.tree_context)
+        let status = grow_symlink(eval_context.app_context, eval_context.tree_context, output)
             .unwrap_or(errors::EX_IOERR);
         if status != errors::EX_OK {
             exit_status = status;
@@ -159,6 +586,8 @@ fn grow_tree_from_context(
         return grow_tree_from_context_as_worktree(
             eval_context,
             configured_worktrees,
+            output,
+            locked_trees,
             remote_pattern,
             quiet,
             verbose,
@@ -205,6 +634,17 @@ fn grow_tree_from_context(
         clone_depth_opt = format!("--depth={clone_depth}");
         cmd.push(&clone_depth_opt);
     }
+    // "git clone --filter=blob:none" creates partial clones.
+    let clone_filter_opt;
+    if !tree.clone_filter.is_empty() {
+        clone_filter_opt = format!("--filter={}", tree.clone_filter);
+        cmd.push(&clone_filter_opt);
+    }
+    // "git clone --sparse" checks out only the top-level files. The configured
+    // "sparse-checkout" paths are added after the clone completes.
+    if !tree.sparse_checkout.is_empty() && !tree.is_bare_repository {
+        cmd.push("--sparse");
+    }
     // "git clone --depth=N" clones a single branch by default.
     // We generally want all branches available in our clones so we default to
     // "single-branch: false" so that "--no-single-branch" is used. This makes
@@ -221,11 +661,10 @@ fn grow_tree_from_context(
     cmd.push(&url);
     cmd.push(path);
     if verbose > 1 {
-        print_quoted_command(&cmd);
+        output.print_command(&cmd);
     }
 
-    let exec = cmd::exec_cmd(&cmd);
-    let status = cmd::status(exec);
+    let status = output.run_command_in_current_dir(&cmd);
     if status != 0 {
         exit_status = status;
     }
@@ -233,6 +672,7 @@ fn grow_tree_from_context(
     let status = update_tree_from_context(
         eval_context,
         configured_worktrees,
+        output,
         &pathbuf,
         &branch,
         remote_pattern,
@@ -243,22 +683,168 @@ fn grow_tree_from_context(
     if status != errors::EX_OK {
         exit_status = status;
     }
+    let pin =
+        locked_tree_pin(eval_context, locked_trees, output).or_else(|| tree.eval_pin(eval_context));
+    if let Some(pin) = pin {
+        let status = checkout_pin(tree, &pin, &pathbuf, output, verbose);
+        if status != errors::EX_OK {
+            exit_status = status;
+        }
+    }
+    let status = update_checkout(tree, &pathbuf, output, verbose);
+    if status != errors::EX_OK {
+        exit_status = status;
+    }
 
     Ok(exit_status)
 }
 
-/// Print a command from a list of arguments.
-fn print_quoted_command(command: &[&str]) {
-    let quoted_args = command
-        .iter()
-        .map(|arg| cmd::shell_quote(arg))
-        .collect::<Vec<String>>();
-    print_command_str(&quoted_args.join(" "));
+/// Return the commit recorded for a tree in "garden.lock" when growing with "--locked".
+fn locked_tree_pin(
+    eval_context: &model::EvalContext,
+    locked_trees: Option<&config::lock::LockedTrees>,
+    output: &mut GrowOutput,
+) -> Option<model::TreePin> {
+    let locked_trees = locked_trees?;
+    let name = query::qualified_tree_name(eval_context.app_context, eval_context.tree_context);
+    match locked_trees.get(&name) {
+        Some(locked_tree) if !locked_tree.commit.is_empty() => {
+            Some(model::TreePin::Commit(locked_tree.commit.to_string()))
+        }
+        _ => {
+            output.eprintln(format!(
+                "warning: {name}: not found in {}",
+                constants::GARDEN_LOCK
+            ));
+            None
+        }
+    }
 }
 
-/// Print a single command from a string.
-fn print_command_str(cmd: &str) {
-    println!("{} {}", ":".cyan(), cmd.green())
+/// Check out the pinned commit or tag as a detached HEAD.
+/// The pinned revision is fetched from the default remote when it is not available locally.
+fn checkout_pin(
+    tree: &model::Tree,
+    pin: &model::TreePin,
+    path: &std::path::Path,
+    output: &mut GrowOutput,
+    verbose: u8,
+) -> u32 {
+    if tree.is_bare_repository || tree.is_symlink {
+        return errors::EX_OK;
+    }
+    let revision = pin.revision();
+    if git::commit_id(path, &revision).is_none() {
+        let remote = tree.default_remote.as_str();
+        let command = match pin {
+            model::TreePin::Commit(commit) => vec!["git", "fetch", remote, commit.as_str()],
+            model::TreePin::Tag(tag) => vec!["git", "fetch", remote, "tag", tag.as_str()],
+        };
+        if verbose > 1 {
+            output.print_command(&command);
+        }
+        let status = output.run_command(&command, &path);
+        if status != errors::EX_OK {
+            return status;
+        }
+    }
+
+    let command = ["git", "checkout", "--quiet", "--detach", revision.as_str()];
+    if verbose > 1 {
+        output.print_command(&command);
+    }
+
+    output.run_command(&command, &path)
+}
+
+/// Warn when the checked-out commit differs from the pinned commit or tag.
+fn report_pin_drift(
+    tree: &model::Tree,
+    pin: &model::TreePin,
+    path: &std::path::Path,
+    output: &mut GrowOutput,
+) {
+    if tree.is_bare_repository || tree.is_symlink || output.is_planned(path) {
+        return;
+    }
+    let name = tree.get_name();
+    let Some(pinned) = git::commit_id(path, &pin.revision()) else {
+        output.eprintln(format!(
+            "warning: {name}: {pin} does not exist, run \"git fetch\" to update the tree"
+        ));
+        return;
+    };
+    let head = git::commit_id(path, "HEAD").unwrap_or_default();
+    if head != pinned {
+        output.eprintln(format!(
+            "warning: {name}: HEAD {} has drifted from {pin}",
+            git::short_commit_id(&head)
+        ));
+    }
+}
+
+/// Apply the "sparse-checkout" and "submodules" settings to a worktree.
+fn update_checkout(
+    tree: &model::Tree,
+    path: &std::path::Path,
+    output: &mut GrowOutput,
+    verbose: u8,
+) -> u32 {
+    let mut exit_status = errors::EX_OK;
+    if tree.is_bare_repository || tree.is_symlink {
+        return exit_status;
+    }
+
+    // "git sparse-checkout set" is only run when the configured paths have changed.
+    if !tree.sparse_checkout.is_empty() && !is_sparse_checkout_current(tree, path) {
+        let mut command = vec!["git", "sparse-checkout", "set", "--cone"];
+        command.extend(tree.sparse_checkout.iter().map(String::as_str));
+        if verbose > 1 {
+            output.print_command(&command);
+        }
+        let status = output.run_command(&command, &path);
+        if status != errors::EX_OK {
+            exit_status = status;
+        }
+    }
+
+    let command: &[&str] = match tree.submodules.unwrap_or_default() {
+        model::SubmoduleMode::None => &[],
+        model::SubmoduleMode::Init => &["git", "submodule", "update", "--init"],
+        model::SubmoduleMode::Recursive => &["git", "submodule", "update", "--init", "--recursive"],
+    };
+    if !command.is_empty() {
+        if verbose > 1 {
+            output.print_command(command);
+        }
+        let status = output.run_command(command, &path);
+        if status != errors::EX_OK {
+            exit_status = status;
+        }
+    }
+
+    exit_status
+}
+
+/// Return true when the worktree's sparse checkout matches the configured paths.
+fn is_sparse_checkout_current(tree: &model::Tree, path: &std::path::Path) -> bool {
+    let command = ["git", "sparse-checkout", "list"];
+    let exec = cmd::exec_in_dir(&command, path);
+    let Ok(output) = cmd::stdout_to_string(exec) else {
+        return false;
+    };
+    let current: StringSet = output
+        .lines()
+        .map(|line| line.trim().trim_end_matches('/').to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+    let configured: StringSet = tree
+        .sparse_checkout
+        .iter()
+        .map(|path| path.trim().trim_end_matches('/').to_string())
+        .collect();
+
+    current == configured
 }
 
 /// Add remotes that do not already exist and synchronize .git/config values.
@@ -266,6 +852,7 @@ fn print_command_str(cmd: &str) {
 fn update_tree_from_context(
     eval_context: &model::EvalContext,
     configured_worktrees: &mut StringSet,
+    output: &mut GrowOutput,
     path: &dyn AsRef<std::path::Path>,
     branch: &str,
     remote_pattern: &glob::Pattern,
@@ -319,10 +906,15 @@ fn update_tree_from_context(
             }
         }
     }
+    // Repositories are not cloned during a dry run. "git clone" creates the default remote.
+    if checkout && output.is_dry_run() {
+        existing_remotes.insert(tree.default_remote.to_string());
+    }
 
     // The "default-remote" field is used to change the name of the default "origin" remote.
     if tree.default_remote != constants::ORIGIN {
         set_gitconfig_value(
+            output,
             "checkout.defaultRemoteName",
             &tree.default_remote,
             path,
@@ -341,23 +933,23 @@ fn update_tree_from_context(
         let url = eval_context.tree_variable(var);
         if existing_remotes.contains(remote) {
             let remote_key = format!("remote.{remote}.url");
-            let status = set_gitconfig_value(&remote_key, &url, path, verbose);
+            let status = set_gitconfig_value(output, &remote_key, &url, path, verbose);
             if status != errors::EX_OK {
                 exit_status = status;
             }
         } else {
             let command = ["git", "remote", "add", remote.as_ref(), url.as_ref()];
             if verbose > 1 {
-                print_command_str(&command.join(" "));
+                output.print_command_str(&command.join(" "));
             }
-            let status = cmd::run_command(&command, path.as_ref());
+            let status = output.run_command(&command, path);
             if status != errors::EX_OK {
                 exit_status = status;
             }
 
             // git config remote.<name>.tagopt --no-tags
             let key = format!("remote.{remote}.tagopt");
-            let status = set_gitconfig_value(&key, "--no-tags", path, verbose);
+            let status = set_gitconfig_value(output, &key, "--no-tags", path, verbose);
             if status != errors::EX_OK {
                 exit_status = status;
             }
@@ -366,10 +958,10 @@ fn update_tree_from_context(
             if Some(remote) == remote_for_branch.as_ref() {
                 let command = ["git", "fetch", remote];
                 if verbose > 1 {
-                    print_command_str(&command.join(" "));
+                    output.print_command_str(&command.join(" "));
                 }
                 fetched_remotes.insert(remote.to_string());
-                let status = cmd::run_command(&command, path.as_ref());
+                let status = output.run_command(&command, path);
                 if status != errors::EX_OK {
                     exit_status = status;
                 }
@@ -377,6 +969,21 @@ fn update_tree_from_context(
         }
     }
 
+    // Partial clones lazily fetch missing objects from the default remote.
+    // "git clone --filter" configures new clones. Existing clones are configured here.
+    if !tree.clone_filter.is_empty() && tree.remotes.contains_key(&tree.default_remote) {
+        let key = format!("remote.{}.promisor", tree.default_remote);
+        let status = set_gitconfig_value(output, &key, "true", path, verbose);
+        if status != errors::EX_OK {
+            exit_status = status;
+        }
+        let key = format!("remote.{}.partialclonefilter", tree.default_remote);
+        let status = set_gitconfig_value(output, &key, &tree.clone_filter, path, verbose);
+        if status != errors::EX_OK {
+            exit_status = status;
+        }
+    }
+
     // Set gitconfig settings.
     let mut gitconfig_cache: GitConfigMap = GitConfigMap::new();
     for (var_name, variables) in &tree.gitconfig {
@@ -388,10 +995,10 @@ fn update_tree_from_context(
             };
             let status = if variables.len() > 1 {
                 // Multiple values are set using "git config --add <name> <value>"
-                append_gitconfig_value(&name, &value, path, &mut gitconfig_cache)
+                append_gitconfig_value(output, &name, &value, path, &mut gitconfig_cache)
             } else {
                 // Single values are set directly using "git config <name> <value>".
-                set_gitconfig_value(&name, &value, path, verbose)
+                set_gitconfig_value(output, &name, &value, path, verbose)
             };
             if status != errors::EX_OK {
                 exit_status = status;
@@ -415,9 +1022,9 @@ fn update_tree_from_context(
                         fetched_remotes.insert(remote_for_branch.to_string());
                         let command = ["git", "fetch", remote_for_branch.as_str()];
                         if verbose > 1 {
-                            print_command_str(&command.join(" "));
+                            output.print_command_str(&command.join(" "));
                         }
-                        let status = cmd::run_command(&command, path.as_ref());
+                        let status = output.run_command(&command, path);
                         if status != errors::EX_OK {
                             exit_status = status;
                         }
@@ -425,9 +1032,9 @@ fn update_tree_from_context(
                 }
                 let command = ["git", "branch", "--track", branch, remote_branch.as_str()];
                 if verbose > 1 {
-                    print_command_str(&command.join(" "));
+                    output.print_command_str(&command.join(" "));
                 }
-                let status = cmd::run_command(&command, path.as_ref());
+                let status = output.run_command(&command, path);
                 if status != errors::EX_OK {
                     exit_status = status;
                 }
@@ -438,7 +1045,7 @@ fn update_tree_from_context(
     // Checkout the configured branch if we are creating the repository initially.
     if checkout && !branch.is_empty() && tree.branches.contains_key(branch) {
         let command = ["git", "checkout", branch, "--"];
-        let status = cmd::run_command(&command, path.as_ref());
+        let status = output.run_command(&command, path);
         if status != errors::EX_OK {
             exit_status = status;
         }
@@ -449,6 +1056,7 @@ fn update_tree_from_context(
 
 /// Apply a "gitconfig" value in the specified directory.
 fn append_gitconfig_value(
+    output: &mut GrowOutput,
     name: &str,
     value: &str,
     path: &dyn AsRef<std::path::Path>,
@@ -479,7 +1087,7 @@ fn append_gitconfig_value(
         if !values.contains(value) {
             values.insert(value.to_string());
             let command = ["git", "config", "--add", name, value];
-            status = cmd::run_command(&command, path.as_ref());
+            status = output.run_command(&command, path);
         }
     }
 
@@ -488,6 +1096,7 @@ fn append_gitconfig_value(
 
 /// Set a simple gitconfig value.
 fn set_gitconfig_value(
+    output: &mut GrowOutput,
     name: &str,
     value: &str,
     path: &dyn AsRef<std::path::Path>,
@@ -495,10 +1104,10 @@ fn set_gitconfig_value(
 ) -> u32 {
     let command = ["git", "config", name, value];
     if verbose > 1 {
-        print_command_str(&command.join(" "));
+        output.print_command_str(&command.join(" "));
     }
 
-    cmd::run_command(&command, path.as_ref())
+    output.run_command(&command, path)
 }
 
 /// Use "git worktree" to create a worktree.
@@ -506,6 +1115,8 @@ fn set_gitconfig_value(
 fn grow_tree_from_context_as_worktree(
     eval_context: &model::EvalContext,
     configured_worktrees: &mut StringSet,
+    output: &mut GrowOutput,
+    locked_trees: Option<&config::lock::LockedTrees>,
     remote_pattern: &glob::Pattern,
     quiet: bool,
     verbose: u8,
@@ -545,10 +1156,13 @@ fn grow_tree_from_context_as_worktree(
     exit_status = grow_tree_from_context(
         &parent_eval_context,
         configured_worktrees,
+        output,
+        locked_trees,
         remote_pattern,
         quiet,
         verbose,
     )?;
+    output.tree = tree.get_name().to_string();
     if exit_status != 0 {
         return Err(errors::GardenError::WorktreeParentCreationError {
             tree: tree.get_name().into(),
@@ -570,11 +1184,16 @@ fn grow_tree_from_context_as_worktree(
     };
     let parent_path = parent_tree.path_as_ref()?;
 
+    let pin =
+        locked_tree_pin(eval_context, locked_trees, output).or_else(|| tree.eval_pin(eval_context));
     let mut cmd: Vec<&str> = ["git", "worktree", "add"].to_vec();
     if !branch.is_empty() {
         cmd.push("--track");
         cmd.push("-b");
         cmd.push(&branch);
+    } else if pin.is_some() {
+        // Pinned worktrees without a branch are created with a detached HEAD.
+        cmd.push("--detach");
     }
 
     // The parent_path is the base path from which we'll execute "git worktree add".
@@ -604,9 +1223,9 @@ fn grow_tree_from_context_as_worktree(
     }
 
     if verbose > 1 {
-        print_quoted_command(&cmd);
+        output.print_command(&cmd);
     }
-    exit_status = cmd::run_command(&cmd, &parent_path);
+    exit_status = output.run_command(&cmd, &parent_path);
     if exit_status != 0 {
         return Err(errors::GardenError::WorktreeGitCheckoutError {
             tree: tree.get_name().clone(),
@@ -614,6 +1233,14 @@ fn grow_tree_from_context_as_worktree(
         }
         .into());
     }
+    let worktree_path = std::path::Path::new(tree_path);
+    if let Some(pin) = pin {
+        exit_status = checkout_pin(tree, &pin, worktree_path, output, verbose);
+        if exit_status != errors::EX_OK {
+            return Ok(exit_status);
+        }
+    }
+    exit_status = update_checkout(tree, worktree_path, output, verbose);
 
     Ok(exit_status)
 }
@@ -622,6 +1249,7 @@ fn grow_tree_from_context_as_worktree(
 fn grow_symlink(
     app_context: &model::ApplicationContext,
     tree_context: &model::TreeContext,
+    output: &mut GrowOutput,
 ) -> Result<u32> {
     let config = match tree_context.config {
         Some(config_id) => app_context.get_config(config_id),
@@ -633,10 +1261,9 @@ fn grow_symlink(
     };
     // Invalid usage: non-symlink.
     if !tree.is_symlink || tree.path_as_ref()?.is_empty() || tree.symlink_as_ref()?.is_empty() {
-        return Err(errors::GardenError::ConfigurationError(format!(
-            "invalid symlink: {}",
-            tree.get_name()
-        ))
+        return Err(errors::GardenError::ConfigurationError(
+            tree.format_with_location(&format!("invalid symlink: {}", tree.get_name())),
+        )
         .into());
     }
     let path_str = tree.path_as_ref()?;
@@ -663,6 +1290,11 @@ fn grow_symlink(
     }
     .to_string();
 
+    // A dry run records the equivalent "ln -s" command.
+    if output.record(&["ln", "-s", &target, path_str], Some(&parent)) {
+        return Ok(errors::EX_OK);
+    }
+
     let target_path = std::path::PathBuf::from(&target);
     #[cfg(unix)]
     {
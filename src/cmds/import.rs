@@ -0,0 +1,364 @@
+use anyhow::Result;
+use clap::{Parser, ValueHint};
+use yaml_rust::{yaml, Yaml};
+
+use crate::{cmd, config, constants, errors, model, syntax};
+
+/// Import trees from submodules, repo manifests and vcstool files
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct ImportOptions {
+    /// Format of the file to import [gitmodules, repo-manifest, vcstool]
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        value_parser = model::ImportFormat::parse_from_str,
+    )]
+    from: model::ImportFormat,
+    /// URL of the manifest repository or superproject used to resolve relative URLs
+    #[arg(long, value_name = "URL", value_hint = ValueHint::Url)]
+    base_url: Option<String>,
+    /// Garden configuration file to write [default: "garden.yaml"]
+    #[arg(long, short)]
+    output: Option<String>,
+    /// Increase verbosity level (default: 0)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+    /// File to import
+    #[arg(value_hint = ValueHint::FilePath)]
+    path: String,
+}
+
+/// A repository read from an imported file.
+#[derive(Debug, Default)]
+struct ImportedTree {
+    /// The repository path is used as the tree name.
+    path: String,
+    url: String,
+    /// The name of the remote that the url belongs to.
+    remote: String,
+    branch: String,
+    commit: String,
+    tag: String,
+    groups: Vec<String>,
+}
+
+impl ImportedTree {
+    /// Record a revision as a commit, tag or branch.
+    fn set_revision(&mut self, revision: &str) {
+        if let Some(tag) = revision.strip_prefix("refs/tags/") {
+            self.tag = tag.to_string();
+        } else if is_commit_id(revision) {
+            self.commit = revision.to_string();
+        } else {
+            let branch = revision.strip_prefix("refs/heads/").unwrap_or(revision);
+            self.branch = branch.to_string();
+        }
+    }
+}
+
+/// Main entry point for the "garden import" command
+pub fn main(app_context: &model::ApplicationContext, options: &ImportOptions) -> Result<()> {
+    let verbose = app_context.options.verbose + options.verbose;
+    let path = std::path::Path::new(&options.path);
+    std::fs::metadata(path).map_err(|err| errors::GardenError::ReadFile {
+        path: path.into(),
+        err,
+    })?;
+    let imported_trees = match options.from {
+        model::ImportFormat::Gitmodules => read_gitmodules(path, options.base_url.as_deref())?,
+        model::ImportFormat::RepoManifest => read_repo_manifest(path, options.base_url.as_deref())?,
+        model::ImportFormat::Vcstool => read_vcstool(path)?,
+    };
+
+    // Read existing configuration
+    let config = app_context.get_root_config();
+    let (mut doc, original) = config::reader::read_yaml_and_string(config.get_path()?)?;
+
+    // Output filename defaults to the input filename.
+    let output = match &options.output {
+        Some(output) => output.to_string(),
+        None => config.get_path()?.to_string_lossy().to_string(),
+    };
+    let has_groups = imported_trees.iter().any(|tree| !tree.groups.is_empty());
+    config::reader::add_section(constants::TREES, &mut doc)?;
+    if has_groups {
+        config::reader::add_section(constants::GROUPS, &mut doc)?;
+    }
+
+    // Get a mutable reference to top-level document hash.
+    let doc_hash: &mut yaml::Hash = match doc {
+        Yaml::Hash(ref mut hash) => hash,
+        _ => {
+            error!("invalid config: not a hash");
+        }
+    };
+    let trees_key = Yaml::String(constants::TREES.to_string());
+    let trees: &mut yaml::Hash = match doc_hash.get_mut(&trees_key) {
+        Some(Yaml::Hash(ref mut hash)) => hash,
+        _ => {
+            error!("invalid trees: not a hash");
+        }
+    };
+    for imported_tree in &imported_trees {
+        if verbose > 0 {
+            eprintln!("{}: {}", imported_tree.path, imported_tree.url);
+        }
+        import_tree(trees, imported_tree);
+    }
+    if has_groups {
+        let groups_key = Yaml::String(constants::GROUPS.to_string());
+        let groups: &mut yaml::Hash = match doc_hash.get_mut(&groups_key) {
+            Some(Yaml::Hash(ref mut hash)) => hash,
+            _ => {
+                error!("invalid groups: not a hash");
+            }
+        };
+        for imported_tree in &imported_trees {
+            for group in &imported_tree.groups {
+                super::plant::add_group_member(groups, group, &imported_tree.path);
+            }
+        }
+    }
+
+    // Update the YAML configuration while preserving comments and formatting.
+    Ok(config::writer::update_yaml(&original, &doc, output)?)
+}
+
+/// Add or update a tree entry. Existing entries are updated in-place so that
+/// keys that are not provided by the imported file are retained.
+fn import_tree(trees: &mut yaml::Hash, imported_tree: &ImportedTree) {
+    let string = |value: &str| Yaml::String(value.to_string());
+    let key = string(&imported_tree.path);
+    let mut entry = trees
+        .get(&key)
+        .and_then(Yaml::as_hash)
+        .cloned()
+        .unwrap_or_default();
+    if !imported_tree.remote.is_empty() && imported_tree.remote != constants::ORIGIN {
+        entry.replace(
+            string(constants::DEFAULT_REMOTE),
+            string(&imported_tree.remote),
+        );
+    }
+    if !imported_tree.url.is_empty() {
+        entry.replace(string(constants::URL), string(&imported_tree.url));
+    }
+    // Setting one of "branch", "commit" or "tag" removes the others so that
+    // stale pins from an earlier import do not take precedence.
+    let revisions = [
+        (constants::BRANCH, &imported_tree.branch),
+        (constants::COMMIT, &imported_tree.commit),
+        (constants::TAG, &imported_tree.tag),
+    ];
+    if revisions.iter().any(|(_, value)| !value.is_empty()) {
+        for (field, value) in revisions {
+            if value.is_empty() {
+                entry.remove(&string(field));
+            } else {
+                entry.replace(string(field), string(value));
+            }
+        }
+    }
+
+    if let Some(tree_entry) = trees.get_mut(&key) {
+        *tree_entry = Yaml::Hash(entry);
+    } else {
+        trees.insert(key, Yaml::Hash(entry));
+    }
+}
+
+/// Read submodules from a ".gitmodules" file.
+/// Relative URLs are resolved against the superproject's URL.
+fn read_gitmodules(
+    path: &std::path::Path,
+    base_url: Option<&str>,
+) -> Result<Vec<ImportedTree>, errors::GardenError> {
+    let path_str = path.to_string_lossy();
+    let command = ["git", "config", "--file", path_str.as_ref(), "--list"];
+    let output = cmd::stdout_to_string(cmd::exec_cmd(&command)).map_err(|err| {
+        errors::GardenError::InvalidConfiguration {
+            msg: format!("{path:?}: {err}"),
+        }
+    })?;
+    // Submodules are keyed by name using "submodule.<name>.<field>=<value>" entries.
+    let mut submodules: model::IndexMap<String, ImportedTree> = model::IndexMap::new();
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let Some((name, field)) = key
+            .strip_prefix("submodule.")
+            .and_then(|key| key.rsplit_once('.'))
+        else {
+            continue;
+        };
+        let submodule = submodules.entry(name.to_string()).or_default();
+        match field {
+            constants::PATH => submodule.path = value.to_string(),
+            constants::URL => submodule.url = value.to_string(),
+            // "branch = ." tracks the superproject's branch.
+            constants::BRANCH if value != "." => submodule.branch = value.to_string(),
+            _ => (),
+        }
+    }
+
+    let mut imported_trees = Vec::new();
+    for (name, mut submodule) in submodules {
+        if submodule.path.is_empty() {
+            submodule.path = name;
+        }
+        if submodule.url.is_empty() {
+            eprintln!("warning: {}: submodule has no url", submodule.path);
+            continue;
+        }
+        if syntax::is_relative_url(&submodule.url) {
+            let Some(base_url) = base_url else {
+                eprintln!(
+                    "warning: {}: relative url \"{}\" requires --base-url",
+                    submodule.path, submodule.url
+                );
+                continue;
+            };
+            submodule.url = syntax::resolve_relative_url(base_url, &submodule.url);
+        }
+        imported_trees.push(submodule);
+    }
+
+    Ok(imported_trees)
+}
+
+/// Read projects from a Google "repo" XML manifest.
+/// Relative "fetch" URLs are resolved against the manifest repository's URL.
+fn read_repo_manifest(
+    path: &std::path::Path,
+    base_url: Option<&str>,
+) -> Result<Vec<ImportedTree>, errors::GardenError> {
+    let content = std::fs::read_to_string(path).map_err(|err| errors::GardenError::ReadFile {
+        path: path.into(),
+        err,
+    })?;
+    let invalid_manifest = |msg: String| errors::GardenError::InvalidConfiguration {
+        msg: format!("{path:?}: {msg}"),
+    };
+    let document =
+        roxmltree::Document::parse(&content).map_err(|err| invalid_manifest(err.to_string()))?;
+    let manifest = document.root_element();
+    if manifest.tag_name().name() != "manifest" {
+        return Err(invalid_manifest(string!("<manifest> element not found")));
+    }
+    let elements = || manifest.children().filter(roxmltree::Node::is_element);
+
+    // Remotes map to their (fetch, revision) values.
+    let mut remotes: model::IndexMap<&str, (&str, &str)> = model::IndexMap::new();
+    let mut default_remote = "";
+    let mut default_revision = "";
+    for element in elements() {
+        match element.tag_name().name() {
+            "remote" => {
+                if let (Some(name), Some(fetch)) =
+                    (element.attribute("name"), element.attribute("fetch"))
+                {
+                    let revision = element.attribute("revision").unwrap_or_default();
+                    remotes.insert(name, (fetch, revision));
+                }
+            }
+            "default" => {
+                default_remote = element.attribute("remote").unwrap_or(default_remote);
+                default_revision = element.attribute("revision").unwrap_or(default_revision);
+            }
+            _ => (),
+        }
+    }
+
+    let mut imported_trees = Vec::new();
+    for project in elements().filter(|element| element.tag_name().name() == "project") {
+        let Some(name) = project.attribute("name") else {
+            continue;
+        };
+        let remote = project.attribute("remote").unwrap_or(default_remote);
+        let Some((fetch, remote_revision)) = remotes.get(remote) else {
+            eprintln!("warning: {name}: unknown remote \"{remote}\"");
+            continue;
+        };
+        // "repo" resolves relative fetch URLs from the directory containing the manifest
+        // repository, e.g. fetch=".." for "https://host/platform/manifest" is "https://host".
+        let fetch = if syntax::is_relative_url(fetch) {
+            let Some(base_url) = base_url else {
+                eprintln!("warning: {name}: relative fetch url \"{fetch}\" requires --base-url");
+                continue;
+            };
+            syntax::resolve_relative_url(base_url, &format!("../{fetch}"))
+        } else {
+            fetch.to_string()
+        };
+        let mut imported_tree = ImportedTree {
+            path: project
+                .attribute(constants::PATH)
+                .unwrap_or(name)
+                .to_string(),
+            url: format!("{}/{}", fetch.trim_end_matches('/'), name),
+            remote: remote.to_string(),
+            ..Default::default()
+        };
+        let revision = project
+            .attribute("revision")
+            .or(Some(*remote_revision).filter(|revision| !revision.is_empty()))
+            .unwrap_or(default_revision);
+        if !revision.is_empty() {
+            imported_tree.set_revision(revision);
+        }
+        if let Some(groups) = project.attribute(constants::GROUPS) {
+            imported_tree.groups = groups
+                .split([',', ' '])
+                .filter(|group| !group.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        imported_trees.push(imported_tree);
+    }
+
+    Ok(imported_trees)
+}
+
+/// Read repositories from a vcstool ".repos" file.
+fn read_vcstool(path: &std::path::Path) -> Result<Vec<ImportedTree>, errors::GardenError> {
+    let doc = config::reader::read_yaml(path)?;
+    let Some(repositories) = doc["repositories"].as_hash() else {
+        return Err(errors::GardenError::InvalidConfiguration {
+            msg: format!("{path:?}: \"repositories\" not found"),
+        });
+    };
+
+    let mut imported_trees = Vec::new();
+    for (repo_path, repository) in repositories {
+        let Some(repo_path) = repo_path.as_str() else {
+            continue;
+        };
+        let vcs_type = repository["type"].as_str().unwrap_or("git");
+        if vcs_type != "git" {
+            eprintln!("warning: {repo_path}: unsupported repository type \"{vcs_type}\"");
+            continue;
+        }
+        let Some(url) = repository[constants::URL].as_str() else {
+            eprintln!("warning: {repo_path}: repository has no url");
+            continue;
+        };
+        let mut imported_tree = ImportedTree {
+            path: repo_path.to_string(),
+            url: url.to_string(),
+            ..Default::default()
+        };
+        if let Some(version) = repository["version"].as_str() {
+            imported_tree.set_revision(version);
+        }
+        imported_trees.push(imported_tree);
+    }
+
+    Ok(imported_trees)
+}
+
+/// Return true when a revision is a full SHA-1 or SHA-256 commit ID.
+fn is_commit_id(revision: &str) -> bool {
+    matches!(revision.len(), 40 | 64) && revision.chars().all(|c| c.is_ascii_hexdigit())
+}
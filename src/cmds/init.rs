@@ -85,10 +85,11 @@ pub fn main(options: &cli::MainOptions, init_options: &mut InitOptions) -> Resul
     let exists = config_path.exists();
 
     // Read or create a new document
-    let mut doc = if exists {
-        config::reader::read_yaml(&config_path)?
+    let (mut doc, original) = if exists {
+        let (doc, original) = config::reader::read_yaml_and_string(&config_path)?;
+        (doc, Some(original))
     } else {
-        config::reader::empty_doc()
+        (config::reader::empty_doc(), None)
     };
 
     let mut config = model::Configuration::new();
@@ -145,7 +146,10 @@ pub fn main(options: &cli::MainOptions, init_options: &mut InitOptions) -> Resul
         }
     }
 
-    config::writer::write_yaml(&doc, &config_path)?;
+    match original {
+        Some(original) => config::writer::update_yaml(&original, &doc, &config_path)?,
+        None => config::writer::write_yaml(&doc, &config_path)?,
+    }
 
     if !options.quiet {
         if exists {
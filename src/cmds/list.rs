@@ -1,8 +1,9 @@
 use anyhow::Result;
 use clap::Parser;
+use yaml_rust::{yaml, Yaml};
 
 use crate::cli::GardenOptions;
-use crate::{constants, display, model, query};
+use crate::{cli, config, constants, display, eval, git, model, query};
 
 /// Query tree status
 #[derive(Parser, Clone, Debug)]
@@ -44,16 +45,262 @@ pub struct ListOptions {
     /// Increase verbosity level (default: 0)
     #[arg(short, long, action = clap::ArgAction::Count)]
     verbose: u8,
+    /// Output format [json, yaml] or a line template, e.g. '%(name) %(path) %(branch)'
+    #[arg(
+        long,
+        short,
+        default_value = "text",
+        value_name = "FORMAT",
+        value_parser = ListFormat::parse_from_str,
+    )]
+    format: ListFormat,
     /// Display worktrees
     #[arg(short, long, default_value_t = false)]
     worktrees: bool,
     /// Filter trees by name post-query using a glob pattern
     #[arg(long, short, default_value = "*")]
     trees: String,
+    #[command(flatten)]
+    filters: cli::TreeFilterOptions,
     /// Tree query for the gardens, groups or trees to display
     queries: Vec<String>,
 }
 
+/// Output formats for "garden ls".
+#[derive(Clone, Debug)]
+enum ListFormat {
+    /// Colored text for display in a terminal.
+    Text,
+    /// Structured YAML or JSON output.
+    Structured(model::OutputFormat),
+    /// One line per tree using a "%(field)" template.
+    Template(Vec<TemplatePart>),
+}
+
+/// Parsed "%(field)" template fragments.
+#[derive(Clone, Debug)]
+enum TemplatePart {
+    Text(String),
+    Field(String),
+}
+
+/// Fields reported for each tree by "garden ls --format".
+const TREE_FIELDS: &[&str] = &[
+    "name",
+    "path",
+    "type",
+    "branch",
+    "commit",
+    "tag",
+    "parent",
+    "description",
+    "tags",
+    "url",
+    "remotes",
+    "links",
+    "commands",
+    "garden",
+    "group",
+];
+
+impl ListFormat {
+    /// Parse "text", "json", "yaml" or a "%(field)" line template.
+    fn parse_from_str(string: &str) -> Result<ListFormat, String> {
+        if string.contains('%') {
+            return parse_template(string).map(ListFormat::Template);
+        }
+        if string.eq_ignore_ascii_case("text") {
+            return Ok(ListFormat::Text);
+        }
+        model::OutputFormat::parse_from_str(string)
+            .map(ListFormat::Structured)
+            .map_err(|_| {
+                "choices are [\"text\", \"json\", \"yaml\"] or a '%(field)' template".to_string()
+            })
+    }
+}
+
+/// Parse a line template into text and "%(field)" fragments. "%%" is a literal "%".
+fn parse_template(string: &str) -> Result<Vec<TemplatePart>, String> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut remainder = string;
+    while let Some(idx) = remainder.find('%') {
+        text.push_str(&remainder[..idx]);
+        remainder = &remainder[idx + 1..];
+        if let Some(rest) = remainder.strip_prefix('%') {
+            text.push('%');
+            remainder = rest;
+            continue;
+        }
+        let Some((field, rest)) = remainder
+            .strip_prefix('(')
+            .and_then(|rest| rest.split_once(')'))
+        else {
+            return Err(format!("invalid template: '{string}'"));
+        };
+        if !TREE_FIELDS.contains(&field) {
+            return Err(format!(
+                "unknown field \"{field}\": fields are {TREE_FIELDS:?}"
+            ));
+        }
+        if !text.is_empty() {
+            parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+        }
+        parts.push(TemplatePart::Field(field.to_string()));
+        remainder = rest;
+    }
+    text.push_str(remainder);
+    if !text.is_empty() {
+        parts.push(TemplatePart::Text(text));
+    }
+
+    Ok(parts)
+}
+
+/// Details reported for each tree by "garden ls --format".
+#[derive(Default)]
+struct TreeDetails {
+    name: String,
+    path: String,
+    tree_type: String,
+    branch: String,
+    commit: String,
+    tag: String,
+    parent: String,
+    description: String,
+    tags: Vec<String>,
+    url: String,
+    remotes: Vec<(String, String)>,
+    links: Vec<String>,
+    commands: Vec<String>,
+    garden: String,
+    group: String,
+}
+
+impl TreeDetails {
+    /// Gather the details for a tree, including its live Git state.
+    fn new(
+        app_context: &model::ApplicationContext,
+        config: &model::Configuration,
+        context: &model::TreeContext,
+        tree: &model::Tree,
+    ) -> Self {
+        let tree_variable = |var: &model::Variable| {
+            eval::tree_variable(
+                app_context,
+                config,
+                None,
+                &context.tree,
+                context.garden.as_ref(),
+                var,
+            )
+        };
+        let path = tree.path_as_ref().cloned().unwrap_or_default();
+        let mut details = TreeDetails {
+            name: tree.get_name().to_string(),
+            commit: tree_variable(&tree.commit),
+            tag: tree_variable(&tree.tag),
+            description: tree.description.to_string(),
+            tags: tree.tags.iter().cloned().collect(),
+            url: tree
+                .remotes
+                .get(&tree.default_remote)
+                .map(tree_variable)
+                .unwrap_or_default(),
+            remotes: tree
+                .remotes
+                .iter()
+                .map(|(name, remote)| (name.to_string(), tree_variable(remote)))
+                .collect(),
+            links: tree.links.iter().map(tree_variable).collect(),
+            commands: tree.commands.keys().cloned().collect(),
+            garden: context.garden.clone().unwrap_or_default(),
+            group: context.group.clone().unwrap_or_default(),
+            ..Default::default()
+        };
+        let pathbuf = std::path::PathBuf::from(&path);
+        details.tree_type = if tree.is_symlink {
+            string!("symlink")
+        } else if !pathbuf.exists() {
+            string!("missing")
+        } else {
+            match git::worktree_details(&pathbuf) {
+                Ok(worktree_details) => {
+                    details.branch = worktree_details.branch;
+                    match worktree_details.tree_type {
+                        model::GitTreeType::Parent => string!("parent"),
+                        model::GitTreeType::Worktree(parent) => {
+                            details.parent = parent.to_string_lossy().to_string();
+                            string!("worktree")
+                        }
+                        model::GitTreeType::Tree => string!("tree"),
+                        model::GitTreeType::Bare => string!("bare"),
+                    }
+                }
+                Err(_) => string!("unknown"),
+            }
+        };
+        // Detached heads are reported using their abbreviated commit ID.
+        if details.branch.is_empty() && details.tree_type != "missing" {
+            details.branch = git::branch(&pathbuf).unwrap_or_default();
+        }
+        details.path = path;
+
+        details
+    }
+
+    /// Return the value of a field for use in line templates.
+    fn field(&self, name: &str) -> String {
+        match name {
+            "name" => self.name.clone(),
+            "path" => self.path.clone(),
+            "type" => self.tree_type.clone(),
+            "branch" => self.branch.clone(),
+            "commit" => self.commit.clone(),
+            "tag" => self.tag.clone(),
+            "parent" => self.parent.clone(),
+            "description" => self.description.clone(),
+            "tags" => self.tags.join(" "),
+            "url" => self.url.clone(),
+            "remotes" => self
+                .remotes
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>()
+                .join(" "),
+            "links" => self.links.join(" "),
+            "commands" => self.commands.join(" "),
+            "garden" => self.garden.clone(),
+            "group" => self.group.clone(),
+            _ => String::new(),
+        }
+    }
+
+    /// Return the details as a YAML hash. All fields are always present.
+    fn to_yaml(&self) -> Yaml {
+        let string = |value: &str| Yaml::String(value.to_string());
+        let strings = |values: &[String]| Yaml::Array(values.iter().map(|x| string(x)).collect());
+        let mut remotes = yaml::Hash::new();
+        for (name, url) in &self.remotes {
+            remotes.insert(string(name), string(url));
+        }
+        let mut hash = yaml::Hash::new();
+        for field in TREE_FIELDS {
+            let value = match *field {
+                "tags" => strings(&self.tags),
+                "remotes" => Yaml::Hash(remotes.clone()),
+                "links" => strings(&self.links),
+                "commands" => strings(&self.commands),
+                _ => string(&self.field(field)),
+            };
+            hash.insert(string(field), value);
+        }
+
+        Yaml::Hash(hash)
+    }
+}
+
 /// Main entry point for the "garden ls" command
 pub fn main(app_context: &model::ApplicationContext, options: &mut ListOptions) -> Result<()> {
     if options.queries.is_empty() {
@@ -85,10 +332,12 @@ fn list(app_context: &model::ApplicationContext, options: &ListOptions) -> Resul
         debug!("queries: {:?}", options.queries);
     }
 
+    // Trees selected for structured and templated output.
+    let mut selected = Vec::new();
     for query in &options.queries {
         // Resolve the tree query into a vector of tree contexts.
-        let mut contexts =
-            query::resolve_and_filter_trees(app_context, config, query, &options.trees);
+        let contexts = query::resolve_and_filter_trees(app_context, config, query, &options.trees);
+        let mut contexts = query::filter_trees(app_context, contexts, &options.filters);
         match options.sort {
             model::TreeSortMode::None => (),
             model::TreeSortMode::Name => {
@@ -108,15 +357,11 @@ fn list(app_context: &model::ApplicationContext, options: &ListOptions) -> Resul
                         config_a
                             .trees
                             .get(&context_a.tree)
-                            .and_then(|tree| tree.pathbuf())
-                            .and_then(|pathbuf| pathbuf.metadata().ok())
-                            .and_then(|metadata| metadata.modified().ok()),
+                            .and_then(|tree| tree.modified()),
                         config_b
                             .trees
                             .get(&context_b.tree)
-                            .and_then(|tree| tree.pathbuf())
-                            .and_then(|pathbuf| pathbuf.metadata().ok())
-                            .and_then(|metadata| metadata.modified().ok()),
+                            .and_then(|tree| tree.modified()),
                     ) {
                         (Some(a), Some(b)) => a.cmp(&b),
                         (None, Some(_)) => std::cmp::Ordering::Less,
@@ -129,6 +374,10 @@ fn list(app_context: &model::ApplicationContext, options: &ListOptions) -> Resul
         if options.reverse {
             contexts.reverse();
         }
+        if !matches!(options.format, ListFormat::Text) {
+            selected.append(&mut contexts);
+            continue;
+        }
         // Loop over each context and display the tree.
         for (idx, context) in contexts.iter().enumerate() {
             let config = match context.config {
@@ -184,7 +433,8 @@ fn list(app_context: &model::ApplicationContext, options: &ListOptions) -> Resul
                 println!();
             }
             display_options.branches = config.tree_branches;
-            display::print_tree(tree, &display_options);
+            let pin = tree.get_pin(app_context, config, None, context);
+            display::print_tree(tree, pin.as_ref(), &display_options);
             if !only_commands {
                 display::print_tree_extended_details(app_context, context, tree, &display_options);
             }
@@ -195,14 +445,51 @@ fn list(app_context: &model::ApplicationContext, options: &ListOptions) -> Resul
         }
     }
 
+    match &options.format {
+        ListFormat::Text => (),
+        ListFormat::Structured(format) => {
+            let doc = list_document(
+                app_context,
+                &selected,
+                show_groups,
+                show_gardens,
+                show_commands,
+            );
+            match format {
+                model::OutputFormat::Yaml => print!("{}", config::writer::to_yaml_string(&doc)),
+                model::OutputFormat::Json => println!("{}", config::writer::to_json_string(&doc)),
+            }
+            return Ok(());
+        }
+        ListFormat::Template(parts) => {
+            for context in &selected {
+                let Some((tree_config, tree)) =
+                    super::cmd::get_config_and_tree(app_context, context)
+                else {
+                    continue;
+                };
+                let details = TreeDetails::new(app_context, tree_config, context, tree);
+                let line: String = parts
+                    .iter()
+                    .map(|part| match part {
+                        TemplatePart::Text(text) => text.clone(),
+                        TemplatePart::Field(field) => details.field(field),
+                    })
+                    .collect();
+                println!("{line}");
+            }
+            return Ok(());
+        }
+    }
+
     if show_groups && !config.groups.is_empty() {
         println!();
-        display::print_groups(&config.groups);
+        display::print_groups(&config.groups, verbose);
     }
 
     if show_gardens && !config.gardens.is_empty() {
         println!();
-        display::print_gardens(&config.gardens);
+        display::print_gardens(&config.gardens, verbose);
     }
 
     if show_commands && !config.commands.is_empty() {
@@ -223,3 +510,51 @@ fn list(app_context: &model::ApplicationContext, options: &ListOptions) -> Resul
 
     Ok(())
 }
+
+/// Build a YAML document with details for the selected trees, groups, gardens and commands.
+fn list_document(
+    app_context: &model::ApplicationContext,
+    contexts: &[model::TreeContext],
+    show_groups: bool,
+    show_gardens: bool,
+    show_commands: bool,
+) -> Yaml {
+    let config = app_context.get_root_config();
+    let string = |value: &str| Yaml::String(value.to_string());
+    let strings =
+        |values: &model::StringSet| Yaml::Array(values.iter().map(|x| string(x)).collect());
+    let mut doc = yaml::Hash::new();
+
+    let trees = contexts
+        .iter()
+        .filter_map(|context| {
+            let (tree_config, tree) = super::cmd::get_config_and_tree(app_context, context)?;
+            Some(TreeDetails::new(app_context, tree_config, context, tree).to_yaml())
+        })
+        .collect();
+    doc.insert(string(constants::TREES), Yaml::Array(trees));
+
+    if show_groups {
+        let mut groups = yaml::Hash::new();
+        for (name, group) in &config.groups {
+            groups.insert(string(name), strings(&group.members));
+        }
+        doc.insert(string(constants::GROUPS), Yaml::Hash(groups));
+    }
+    if show_gardens {
+        let mut gardens = yaml::Hash::new();
+        for (name, garden) in &config.gardens {
+            let mut hash = yaml::Hash::new();
+            hash.insert(string(constants::GROUPS), strings(&garden.groups));
+            hash.insert(string(constants::TREES), strings(&garden.trees));
+            gardens.insert(string(name), Yaml::Hash(hash));
+        }
+        doc.insert(string(constants::GARDENS), Yaml::Hash(gardens));
+    }
+    if show_commands {
+        let commands = config.commands.keys().map(|name| string(name)).collect();
+        doc.insert(string(constants::COMMANDS), Yaml::Array(commands));
+    }
+
+    Yaml::Hash(doc)
+}
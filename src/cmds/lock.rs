@@ -0,0 +1,137 @@
+use anyhow::Result;
+use clap::{Parser, ValueHint};
+use yansi::Paint;
+
+use crate::{cli, config, constants, errors, git, model, query};
+
+/// Record the commit that is checked out in each tree
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct LockOptions {
+    /// Check that the trees match "garden.lock" without updating it
+    #[arg(long)]
+    check: bool,
+    /// Filter trees by name post-query using a glob pattern
+    #[arg(long, short, default_value = "*")]
+    trees: String,
+    #[command(flatten)]
+    filters: cli::TreeFilterOptions,
+    /// Tree queries for the gardens, groups or trees to lock [default: "@*"]
+    #[arg(value_hint = ValueHint::Other)]
+    queries: Vec<String>,
+}
+
+/// Main entry point for the "garden lock" command
+pub fn main(app_context: &model::ApplicationContext, options: &mut LockOptions) -> Result<()> {
+    // Locking every tree rewrites the lock file. Locking a subset of the trees
+    // updates their entries and preserves the entries for the remaining trees.
+    let lock_all = options.queries.is_empty();
+    if lock_all {
+        options.queries.push(string!("@*"));
+    }
+    let lock_path = config::lock::lock_path(app_context)?;
+    let current_trees = current_trees(app_context, options);
+    if options.check {
+        let locked_trees = config::lock::read(&lock_path)?;
+        return check(&current_trees, &locked_trees);
+    }
+
+    let mut locked_trees = if !lock_all && lock_path.exists() {
+        config::lock::read(&lock_path)?
+    } else {
+        config::lock::LockedTrees::new()
+    };
+    for (name, current_tree) in current_trees {
+        match current_tree {
+            Some(locked_tree) => {
+                locked_trees.insert(name, locked_tree);
+            }
+            None => {
+                if !app_context.options.quiet {
+                    eprintln!("warning: {name}: tree does not exist and was not locked");
+                }
+            }
+        }
+    }
+    config::lock::write(&lock_path, &locked_trees)?;
+
+    Ok(())
+}
+
+/// Read the remote URL, branch and HEAD commit for each tree.
+/// Trees that do not exist on disk are reported as None. Symlinks are not locked.
+fn current_trees(
+    app_context: &model::ApplicationContext,
+    options: &LockOptions,
+) -> Vec<(String, Option<config::lock::LockedTree>)> {
+    let mut trees = Vec::new();
+    for (name, context, tree) in query::resolve_unique_trees(
+        app_context,
+        &options.queries,
+        &options.trees,
+        &options.filters,
+    ) {
+        if tree.is_symlink {
+            continue;
+        }
+        let Some(path) = tree.pathbuf() else {
+            continue;
+        };
+        let Some(commit) = git::commit_id(&path, "HEAD") else {
+            trees.push((name, None));
+            continue;
+        };
+        let eval_context = model::EvalContext::from_app_context(app_context, &context);
+        let locked_tree = config::lock::LockedTree {
+            url: tree.eval_url(&eval_context).unwrap_or_default(),
+            branch: git::checked_out_branch(&path).unwrap_or_default(),
+            commit,
+        };
+        trees.push((name, Some(locked_tree)));
+    }
+
+    trees
+}
+
+/// Report trees whose commit or URL differs from the lock file.
+/// Branches are not compared because "garden grow --locked" checks out detached heads.
+/// Returns an error when any tree differs.
+fn check(
+    current_trees: &[(String, Option<config::lock::LockedTree>)],
+    locked_trees: &config::lock::LockedTrees,
+) -> Result<()> {
+    let mut exit_status = errors::EX_OK;
+    for (name, current_tree) in current_trees {
+        let differences = match (current_tree, locked_trees.get(name)) {
+            (Some(_), None) => vec![format!("not found in {}", constants::GARDEN_LOCK)],
+            (None, Some(_)) => vec![string!("missing")],
+            (None, None) => continue,
+            (Some(current), Some(locked)) => {
+                let mut differences = Vec::new();
+                if current.commit != locked.commit {
+                    differences.push(format!(
+                        "HEAD {} differs from {}",
+                        git::short_commit_id(&current.commit),
+                        git::short_commit_id(&locked.commit)
+                    ));
+                }
+                if current.url != locked.url {
+                    differences.push(format!("url {} differs from {}", current.url, locked.url));
+                }
+                differences
+            }
+        };
+        if differences.is_empty() {
+            continue;
+        }
+        exit_status = errors::EX_ERROR;
+        println!(
+            "{} {} ({})",
+            "changed".yellow(),
+            name.bold(),
+            differences.join(", ")
+        );
+    }
+
+    errors::exit_status_into_result(exit_status)
+}
@@ -1,15 +1,24 @@
 /// Configuration-defined commands
 pub mod cmd;
 
+/// Check command
+pub mod check;
+
 /// Completion command
 pub mod completion;
 
+/// Config command
+pub mod config;
+
 /// Exec command
 pub mod exec;
 
 /// Eval command
 pub mod eval;
 
+/// Export command
+pub mod export;
+
 /// Git command
 pub mod git;
 
@@ -19,12 +28,18 @@ pub mod grow;
 /// Gui command
 pub mod gui;
 
+/// Import command
+pub mod import;
+
 /// Init command
 pub mod init;
 
 /// List command
 pub mod list;
 
+/// Lock command
+pub mod lock;
+
 /// Plant command
 pub mod plant;
 
@@ -33,3 +48,9 @@ pub mod prune;
 
 /// Shell command
 pub mod shell;
+
+/// Status command
+pub mod status;
+
+/// Sync command
+pub mod sync;
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use clap::{Parser, ValueHint};
 use yaml_rust::{yaml, Yaml};
+use yansi::Paint;
 
 use crate::{cmd, config, constants, errors, eval, git, model, path, query};
 
@@ -14,19 +15,49 @@ pub struct PlantOptions {
     /// Sort all trees after planting new trees
     #[arg(long, short)]
     sort: bool,
+    /// Add the planted trees to the specified group
+    #[arg(long, short)]
+    group: Option<String>,
+    /// Find and plant all of the repositories below each path
+    #[arg(long, short)]
+    recursive: bool,
+    /// Set the maximum traversal depth when planting recursively
+    #[arg(long, short = 'd', default_value_t = -1, requires = "recursive")]
+    max_depth: isize,
+    /// Only plant repositories starting at the given depth
+    #[arg(long, default_value_t = -1, requires = "recursive")]
+    min_depth: isize,
+    /// Only plant repositories at the exact depth. Alias for '--min-depth=# --max-depth=#'
+    #[arg(long, default_value_t = -1, requires = "recursive")]
+    exact_depth: isize,
+    /// Refresh the entries for planted trees from their repositories
+    #[arg(long, short, conflicts_with_all = ["group", "recursive"])]
+    update: bool,
     /// Increase verbosity level (default: 0)
     #[arg(short, long, action = clap::ArgAction::Count)]
     verbose: u8,
-    /// Trees to plant
-    #[arg(required = true, value_hint=ValueHint::DirPath)]
+    /// Trees to plant, or tree queries to update with "--update" [default: "@*"]
+    #[arg(required_unless_present = "update", value_hint=ValueHint::DirPath)]
     paths: Vec<String>,
 }
 
+/// The outcome of updating a planted tree.
+enum UpdateResult {
+    /// The tree's entry was updated with the specified changes.
+    Updated(Vec<String>),
+    /// The tree's entry already matches its repository.
+    UpToDate,
+    /// The tree was not updated for the specified reason.
+    Skipped(String),
+    /// Reading the tree's repository failed.
+    Failed(String),
+}
+
 pub fn main(app_context: &model::ApplicationContext, options: &PlantOptions) -> Result<()> {
     // Read existing configuration
     let verbose = app_context.options.verbose + options.verbose;
     let config = app_context.get_root_config();
-    let mut doc = config::reader::read_yaml(config.get_path()?)?;
+    let (mut doc, original) = config::reader::read_yaml_and_string(config.get_path()?)?;
 
     // Output filename defaults to the input filename.
     let output = match &options.output {
@@ -35,6 +66,13 @@ pub fn main(app_context: &model::ApplicationContext, options: &PlantOptions) ->
     };
     let trees_key = Yaml::String(constants::TREES.to_string());
     config::reader::add_section(constants::TREES, &mut doc)?;
+    if options.group.is_some() {
+        config::reader::add_section(constants::GROUPS, &mut doc)?;
+    }
+
+    let (mut min_depth, mut max_depth) = (options.min_depth, options.max_depth);
+    super::prune::apply_depth_options(&mut min_depth, &mut max_depth, options.exact_depth);
+    let mut exit_status = errors::EX_OK;
 
     // Mutable YAML scope.
     {
@@ -52,9 +90,48 @@ pub fn main(app_context: &model::ApplicationContext, options: &PlantOptions) ->
                 error!("invalid trees: not a hash");
             }
         };
-        for path in &options.paths {
-            if let Err(msg) = plant_path(Some(app_context), config, verbose, path, trees) {
-                error!("{}", msg);
+        let mut planted = Vec::new();
+        if options.update {
+            exit_status = update_trees(app_context, config, options, verbose, trees);
+        } else {
+            for path in &options.paths {
+                if options.recursive {
+                    let paths = find_repositories(config, path, min_depth, max_depth)?;
+                    for path in paths {
+                        let path = path.to_string_lossy();
+                        match plant_path(Some(app_context), config, verbose, &path, trees) {
+                            Ok(key) => planted.push(key),
+                            Err(msg) => eprintln!("warning: {path}: {msg}"),
+                        }
+                    }
+                    continue;
+                }
+                match plant_path(Some(app_context), config, verbose, path, trees) {
+                    Ok(key) => planted.push(key),
+                    Err(msg) => {
+                        error!("{}", msg);
+                    }
+                }
+            }
+        }
+        if verbose > 0 {
+            for key in &planted {
+                eprintln!("{}: planted", key.as_str().unwrap_or_default());
+            }
+        }
+
+        if let Some(group) = &options.group {
+            let groups_key = Yaml::String(constants::GROUPS.to_string());
+            let groups: &mut yaml::Hash = match doc_hash.get_mut(&groups_key) {
+                Some(Yaml::Hash(ref mut hash)) => hash,
+                _ => {
+                    error!("invalid groups: not a hash");
+                }
+            };
+            for key in &planted {
+                if let Some(tree_name) = key.as_str() {
+                    add_group_member(groups, group, tree_name);
+                }
             }
         }
     }
@@ -83,7 +160,7 @@ pub fn main(app_context: &model::ApplicationContext, options: &PlantOptions) ->
             }
         }
         // Sort trees case-insensitively.
-        names_and_trees.sort_by(|a, b| a.0.to_lowercase().cmp(&b.0.to_lowercase()));
+        names_and_trees.sort_by_key(|a| a.0.to_lowercase());
         // Build a new trees table and replace the existing entry with it.
         let mut sorted_trees = yaml::Hash::new();
         for (name, tree) in names_and_trees {
@@ -94,8 +171,256 @@ pub fn main(app_context: &model::ApplicationContext, options: &PlantOptions) ->
         }
     }
 
-    // Emit the YAML configuration into a string
-    Ok(config::writer::write_yaml(&doc, output)?)
+    // Update the YAML configuration while preserving comments and formatting.
+    config::writer::update_yaml(&original, &doc, output)?;
+
+    errors::exit_status_into_result(exit_status)
+}
+
+/// Re-read the remotes, default remote, branch and worktree details for the trees
+/// matched by the tree queries and merge them into the existing entries.
+/// A summary of the changes is printed for each tree.
+fn update_trees(
+    app_context: &model::ApplicationContext,
+    config: &model::Configuration,
+    options: &PlantOptions,
+    verbose: u8,
+    trees: &mut yaml::Hash,
+) -> u32 {
+    let quiet = app_context.options.quiet;
+    let mut queries = options.paths.clone();
+    if queries.is_empty() {
+        queries.push(string!("@*"));
+    }
+    let (mut updated, mut up_to_date, mut skipped, mut failed) = (0, 0, 0, 0);
+    let mut seen = model::StringSet::new();
+    for query in &queries {
+        for context in query::resolve_and_filter_trees(app_context, config, query, "*") {
+            // Trees from grafts are defined in other garden files.
+            if context.config.is_some() || !seen.insert(context.tree.to_string()) {
+                continue;
+            }
+            let name = context.tree.to_string();
+            match update_tree(app_context, config, verbose, &name, trees) {
+                UpdateResult::Updated(changes) => {
+                    updated += 1;
+                    println!(
+                        "{} {} ({})",
+                        "updated".green(),
+                        name.bold(),
+                        changes.join(", ")
+                    );
+                }
+                UpdateResult::UpToDate => {
+                    up_to_date += 1;
+                    if !quiet {
+                        println!("{} {}", "up-to-date".dim(), name.bold());
+                    }
+                }
+                UpdateResult::Skipped(reason) => {
+                    skipped += 1;
+                    println!("{} {} ({})", "skipped".yellow(), name.bold(), reason);
+                }
+                UpdateResult::Failed(reason) => {
+                    failed += 1;
+                    println!("{} {} ({})", "failed".red(), name.bold(), reason);
+                }
+            }
+        }
+    }
+    println!(
+        "{} updated, {} up-to-date, {} skipped, {} failed",
+        updated, up_to_date, skipped, failed
+    );
+
+    if failed > 0 {
+        errors::EX_ERROR
+    } else {
+        errors::EX_OK
+    }
+}
+
+/// Refresh a single planted tree and report the fields that changed.
+fn update_tree(
+    app_context: &model::ApplicationContext,
+    config: &model::Configuration,
+    verbose: u8,
+    tree_name: &str,
+    trees: &mut yaml::Hash,
+) -> UpdateResult {
+    let Some(tree) = config.trees.get(tree_name) else {
+        return UpdateResult::Skipped(string!("not found"));
+    };
+    // Trees from garden.includes files are not written to the root garden file.
+    if let Some(location) = tree.get_location() {
+        if !tree
+            .get_locations()
+            .iter()
+            .any(|location| location.path == config.path)
+        {
+            let defined_in = location
+                .path
+                .as_deref()
+                .map(|path| path::relative_path(path).display().to_string())
+                .unwrap_or_default();
+            return UpdateResult::Skipped(format!("defined in {defined_in}"));
+        }
+    }
+    if tree.is_symlink {
+        return UpdateResult::Skipped(string!("symlink"));
+    }
+    let Some(path) = tree.pathbuf().filter(|path| path.exists()) else {
+        return UpdateResult::Skipped(string!("missing"));
+    };
+    let before = planted_fields(trees.get(&Yaml::String(tree_name.to_string())));
+    let path_str = path.to_string_lossy();
+    let key = match plant_path(Some(app_context), config, verbose, &path_str, trees) {
+        Ok(key) => key,
+        Err(err) => return UpdateResult::Failed(err.to_string()),
+    };
+
+    // Record the branch that is checked out. Worktree entries record their branch
+    // when planted. Detached heads, e.g. for pinned trees, are left as-is.
+    // Branches that are configured using variables are reported instead of being replaced.
+    let is_worktree = planted_fields(trees.get(&key)).contains_key(constants::WORKTREE);
+    if !is_worktree && !tree.is_bare_repository {
+        if let Some(branch) = git::checked_out_branch(&path) {
+            let current_branch =
+                eval::tree_variable(app_context, config, None, tree_name, None, &tree.branch);
+            if branch != current_branch && tree.branch.get_expr().contains("${") {
+                eprintln!(
+                    "warning: {tree_name}: branch \"{}\" is \"{current_branch}\" but \"{branch}\" is checked out",
+                    tree.branch.get_expr()
+                );
+            } else if branch != current_branch {
+                set_tree_field(trees, &key, constants::BRANCH, branch);
+            }
+        }
+    }
+
+    let after = planted_fields(trees.get(&key));
+    let mut changes = Vec::new();
+    for (field, value) in &after {
+        match before.get(field) {
+            Some(previous) if previous == value => (),
+            Some(previous) => changes.push(format!("{field}: {previous} -> {value}")),
+            None => changes.push(format!("{field}: {value}")),
+        }
+    }
+    if changes.is_empty() {
+        UpdateResult::UpToDate
+    } else {
+        UpdateResult::Updated(changes)
+    }
+}
+
+/// Return the fields that are managed by "garden plant" for a tree entry.
+/// Remotes are reported as "remotes.<name>" fields.
+fn planted_fields(entry: Option<&Yaml>) -> model::IndexMap<String, String> {
+    let value_string = |value: &Yaml| match value {
+        Yaml::String(value) => value.to_string(),
+        Yaml::Boolean(value) => value.to_string(),
+        Yaml::Integer(value) => value.to_string(),
+        _ => String::new(),
+    };
+    let mut fields = model::IndexMap::new();
+    match entry {
+        Some(Yaml::String(url)) => {
+            fields.insert(constants::URL.to_string(), url.to_string());
+        }
+        Some(Yaml::Hash(hash)) => {
+            for field in [
+                constants::URL,
+                constants::DEFAULT_REMOTE,
+                constants::BRANCH,
+                constants::WORKTREE,
+                constants::BARE,
+            ] {
+                if let Some(value) = hash.get(&Yaml::String(field.to_string())) {
+                    fields.insert(field.to_string(), value_string(value));
+                }
+            }
+            if let Some(Yaml::Hash(remotes)) = hash.get(&Yaml::String(constants::REMOTES.into())) {
+                for (remote, url) in remotes {
+                    fields.insert(
+                        format!("{}.{}", constants::REMOTES, value_string(remote)),
+                        value_string(url),
+                    );
+                }
+            }
+        }
+        _ => (),
+    }
+
+    fields
+}
+
+/// Set a field on a tree entry. Single-line "tree: <url>" entries are expanded.
+fn set_tree_field(trees: &mut yaml::Hash, key: &Yaml, field: &str, value: String) {
+    let Some(entry) = trees.get_mut(key) else {
+        return;
+    };
+    if let Yaml::String(url) = entry {
+        let mut hash = yaml::Hash::new();
+        hash.insert(
+            Yaml::String(constants::URL.to_string()),
+            Yaml::String(url.to_string()),
+        );
+        *entry = Yaml::Hash(hash);
+    }
+    if let Yaml::Hash(hash) = entry {
+        hash.replace(Yaml::String(field.to_string()), Yaml::String(value));
+    }
+}
+
+/// Find the repositories below a directory for "garden plant --recursive".
+/// Linked worktrees are returned after all other repositories so that their
+/// parent trees are planted first.
+fn find_repositories(
+    config: &model::Configuration,
+    raw_path: &str,
+    min_depth: isize,
+    max_depth: isize,
+) -> Result<Vec<std::path::PathBuf>, errors::GardenError> {
+    let pathbuf = std::path::PathBuf::from(raw_path);
+    if !pathbuf.is_dir() {
+        return Err(errors::GardenError::ConfigurationError(format!(
+            "invalid directory: {raw_path}"
+        )));
+    }
+    let path = path::canonicalize(&pathbuf).map_err(|err| {
+        errors::GardenError::ConfigurationError(format!(
+            "unable to canonicalize {raw_path:?}: {err:?}"
+        ))
+    })?;
+    let paths = super::prune::find_repositories(config, path, min_depth, max_depth);
+    // Linked worktrees use a ".git" file that points to their parent repository.
+    let (worktrees, mut repositories): (Vec<_>, Vec<_>) = paths
+        .into_iter()
+        .partition(|path| path.join(".git").is_file());
+    repositories.extend(worktrees);
+
+    Ok(repositories)
+}
+
+/// Add a tree to a group. Groups are created when they do not already exist.
+pub(crate) fn add_group_member(groups: &mut yaml::Hash, group: &str, tree: &str) {
+    let key = Yaml::String(group.to_string());
+    let member = Yaml::String(tree.to_string());
+    let mut members = match groups.get(&key) {
+        Some(Yaml::Array(members)) => members.clone(),
+        Some(Yaml::String(value)) => vec![Yaml::String(value.clone())],
+        _ => Vec::new(),
+    };
+    if members.contains(&member) {
+        return;
+    }
+    members.push(member);
+    if let Some(group_entry) = groups.get_mut(&key) {
+        *group_entry = Yaml::Array(members);
+    } else {
+        groups.insert(key, Yaml::Array(members));
+    }
 }
 
 pub(crate) fn plant_path(
@@ -132,11 +457,16 @@ pub(crate) fn plant_path(
             Some(tree_name) => tree_name,
             None => {
                 let relative_path = path::strip_prefix(&root, &parent_path)?;
-                return Err(errors::GardenError::WorktreeParentNotPlantedError {
-                    parent: relative_path,
-                    tree: raw_path.into(),
+                // Parent trees that were planted earlier by the same command are accepted.
+                let parent_name = relative_path.to_string_lossy().to_string();
+                if !trees.contains_key(&Yaml::String(parent_name.clone())) {
+                    return Err(errors::GardenError::WorktreeParentNotPlantedError {
+                        parent: relative_path,
+                        tree: raw_path.into(),
+                    }
+                    .into());
                 }
-                .into());
+                parent_name
             }
         };
     }
@@ -173,12 +503,13 @@ pub(crate) fn plant_path(
     }
 
     // If this is a child worktree then record a "worktree" entry only.
+    // Existing entries are replaced in-place so that their ordering is retained.
     if is_worktree {
-        entry.insert(
+        entry.replace(
             Yaml::String(constants::WORKTREE.to_string()),
             Yaml::String(parent_tree_name),
         );
-        entry.insert(
+        entry.replace(
             Yaml::String(constants::BRANCH.to_string()),
             Yaml::String(worktree_details.branch.to_string()),
         );
@@ -311,14 +642,14 @@ pub(crate) fn plant_path(
                 update_url = current_url != remote_url;
             }
             if update_url {
-                entry.insert(url_key.clone(), Yaml::String(remote_url));
+                entry.replace(url_key.clone(), Yaml::String(remote_url));
             }
         }
     }
 
     // Update the "default-remote" field.
     if default_remote != constants::ORIGIN {
-        entry.insert(
+        entry.replace(
             Yaml::String(constants::DEFAULT_REMOTE.into()),
             Yaml::String(default_remote),
         );
@@ -331,7 +662,7 @@ pub(crate) fn plant_path(
         let exec = cmd::exec_in_dir(&command, &path);
         if let Ok(is_bare) = cmd::stdout_to_string(exec) {
             if is_bare == "true" {
-                entry.insert(bare_key, Yaml::Boolean(true));
+                entry.replace(bare_key, Yaml::Boolean(true));
             }
         }
     }
@@ -51,26 +51,39 @@ pub fn main(app_context: &model::ApplicationContext, options: &mut PruneOptions)
         options.num_jobs = 3;
     }
 
+    apply_depth_options(
+        &mut options.min_depth,
+        &mut options.max_depth,
+        options.exact_depth,
+    );
+
+    let exit_status = prune(config, options, &options.paths)?;
+
+    // Return the last non-zero exit status.
+    errors::exit_status_into_result(exit_status)
+}
+
+/// Validate the depth options and apply --exact-depth. Exits on invalid combinations.
+pub(crate) fn apply_depth_options(
+    min_depth: &mut isize,
+    max_depth: &mut isize,
+    exact_depth: isize,
+) {
     // Do not allow min_depth to be greater than max_depth.
-    if options.max_depth >= 0 && options.max_depth < options.min_depth {
+    if *max_depth >= 0 && *max_depth < *min_depth {
         println!("error: --max-depth cannot be less than --min-depth");
         std::process::exit(errors::EX_USAGE as i32);
     }
 
     // --exact-depth <depth> is an alias for --min-depth <depth> --max-depth <depth>.
-    if options.exact_depth >= 0 {
-        if options.min_depth >= 0 || options.max_depth >= 0 {
+    if exact_depth >= 0 {
+        if *min_depth >= 0 || *max_depth >= 0 {
             println!("error: --exact-depth cannot be used with --min-depth and --max-depth");
             std::process::exit(errors::EX_USAGE as i32);
         }
-        options.min_depth = options.exact_depth;
-        options.max_depth = options.exact_depth;
+        *min_depth = exact_depth;
+        *max_depth = exact_depth;
     }
-
-    let exit_status = prune(config, options, &options.paths)?;
-
-    // Return the last non-zero exit status.
-    errors::exit_status_into_result(exit_status)
 }
 
 /// PathBufMessage is sent across channels between the TraverseFilesystem,
@@ -496,16 +509,8 @@ pub fn prune(
     let (send_remove_path, recv_remove_path) = crossbeam::channel::unbounded();
     let (send_finished_path, recv_finished_path) = crossbeam::channel::unbounded();
 
-    // Existing trees are never removed. Create an IndexSet containing all of the current
-    // tree paths so that we can skip them while traversing.
-    let mut configured_tree_paths = IndexSet::new();
-    {
-        for tree in config.trees.values() {
-            if let Some(pathbuf) = tree.canonical_pathbuf() {
-                configured_tree_paths.insert(pathbuf);
-            }
-        }
-    }
+    // Existing trees are never removed so we skip them while traversing.
+    let configured_tree_paths = configured_tree_paths(config);
 
     let root_path = config.root_path.to_path_buf();
     let path_filters: Vec<std::path::PathBuf> = paths
@@ -553,3 +558,48 @@ pub fn prune(
 
     Ok(exit_status)
 }
+
+/// Return the canonical paths for all of the configured trees.
+fn configured_tree_paths(config: &model::Configuration) -> IndexSet<std::path::PathBuf> {
+    let mut configured_tree_paths = IndexSet::new();
+    for tree in config.trees.values() {
+        if let Some(pathbuf) = tree.canonical_pathbuf() {
+            configured_tree_paths.insert(pathbuf);
+        }
+    }
+
+    configured_tree_paths
+}
+
+/// Find the Git repositories, bare repositories and worktrees below a directory.
+/// Configured trees are not traversed. Paths are returned in sorted order.
+pub(crate) fn find_repositories(
+    config: &model::Configuration,
+    root_path: std::path::PathBuf,
+    min_depth: isize,
+    max_depth: isize,
+) -> Vec<std::path::PathBuf> {
+    let (send_repo_path, recv_repo_path) = crossbeam::channel::unbounded();
+    let configured_tree_paths = configured_tree_paths(config);
+    let path_filters = Vec::new();
+    let traverse_filesystem = TraverseFilesystem {
+        min_depth,
+        max_depth,
+        send_repo_path,
+        root_path,
+        path_filters: &path_filters,
+        configured_tree_paths: &configured_tree_paths,
+    };
+    traverse_filesystem.traverse();
+
+    let mut paths: Vec<std::path::PathBuf> = recv_repo_path
+        .try_iter()
+        .filter_map(|message| match message {
+            PathBufMessage::Path(pathbuf) => Some(pathbuf),
+            PathBufMessage::Finished => None,
+        })
+        .collect();
+    paths.sort();
+
+    paths
+}
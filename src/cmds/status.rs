@@ -0,0 +1,248 @@
+use anyhow::Result;
+use clap::{Parser, ValueHint};
+use rayon::prelude::*;
+use yaml_rust::{yaml, Yaml};
+use yansi::Paint;
+
+use crate::{cli, cmd, config, constants, errors, git, model, query};
+
+/// Display the status of trees
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct StatusOptions {
+    /// Output format [json, yaml]. A table is displayed by default
+    #[arg(
+        long,
+        short,
+        value_name = "FORMAT",
+        value_parser = model::OutputFormat::parse_from_str,
+    )]
+    format: Option<model::OutputFormat>,
+    /// Query trees in parallel using the specified number of jobs.
+    #[arg(
+        long = "jobs",
+        short = 'j',
+        require_equals = false,
+        num_args = 0..=1,
+        default_missing_value = "0",
+        value_name = "JOBS",
+    )]
+    num_jobs: Option<usize>,
+    /// Filter trees by name post-query using a glob pattern
+    #[arg(long, short, default_value = "*")]
+    trees: String,
+    #[command(flatten)]
+    filters: cli::TreeFilterOptions,
+    /// Tree queries for the gardens, groups or trees to display [default: "@*"]
+    #[arg(value_hint = ValueHint::Other)]
+    queries: Vec<String>,
+}
+
+/// The state of a tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum_macros::Display)]
+#[strum(serialize_all = "kebab-case")]
+enum TreeState {
+    /// The tree has no local changes and matches its upstream branch.
+    Clean,
+    /// The tree has local changes or differs from its upstream branch.
+    Dirty,
+    /// The tree does not exist on disk.
+    Missing,
+    /// The tree is a bare repository.
+    Bare,
+    /// The status could not be determined.
+    Unknown,
+}
+
+/// The status of a single tree.
+struct TreeStatus {
+    name: String,
+    path: String,
+    state: TreeState,
+    status: model::GitStatus,
+}
+
+/// Main entry point for the "garden status" command
+pub fn main(app_context: &model::ApplicationContext, options: &mut StatusOptions) -> Result<()> {
+    if options.queries.is_empty() {
+        options.queries.push(string!("@*"));
+    }
+    cmd::initialize_threads_option(options.num_jobs)?;
+
+    let statuses = tree_statuses(app_context, options);
+    match options.format {
+        Some(model::OutputFormat::Json) => {
+            println!("{}", config::writer::to_json_string(&to_yaml(&statuses)));
+        }
+        Some(model::OutputFormat::Yaml) => {
+            print!("{}", config::writer::to_yaml_string(&to_yaml(&statuses)));
+        }
+        None => print_table(&statuses),
+    }
+
+    // Trees with local changes or unsynchronized branches fail the status check.
+    // Missing trees and trees whose status could not be read also fail the check.
+    let exit_status = if statuses
+        .iter()
+        .any(|tree_status| !matches!(tree_status.state, TreeState::Clean | TreeState::Bare))
+    {
+        errors::EX_ERROR
+    } else {
+        errors::EX_OK
+    };
+
+    errors::exit_status_into_result(exit_status)
+}
+
+/// Query the status for each tree in parallel.
+fn tree_statuses(
+    app_context: &model::ApplicationContext,
+    options: &StatusOptions,
+) -> Vec<TreeStatus> {
+    // Trees are represented using (name, path, is_bare) tuples.
+    let mut trees = Vec::new();
+    for (name, _, tree) in query::resolve_unique_trees(
+        app_context,
+        &options.queries,
+        &options.trees,
+        &options.filters,
+    ) {
+        let path = tree.path_as_ref().cloned().unwrap_or_default();
+        trees.push((name, path, tree.is_bare_repository));
+    }
+
+    trees
+        .into_par_iter()
+        .map(|(name, path, is_bare)| tree_status(name, path, is_bare))
+        .collect()
+}
+
+/// Query the status of a tree.
+fn tree_status(name: String, path: String, is_bare: bool) -> TreeStatus {
+    let pathbuf = std::path::PathBuf::from(&path);
+    let mut status = model::GitStatus::default();
+    let state = if !pathbuf.exists() {
+        TreeState::Missing
+    } else if is_bare {
+        status.branch = git::branch(&pathbuf).unwrap_or_default();
+        TreeState::Bare
+    } else {
+        match git::status(&pathbuf) {
+            Some(git_status) => {
+                status = git_status;
+                if status.is_clean() {
+                    TreeState::Clean
+                } else {
+                    TreeState::Dirty
+                }
+            }
+            None => TreeState::Unknown,
+        }
+    };
+
+    TreeStatus {
+        name,
+        path,
+        state,
+        status,
+    }
+}
+
+/// Print the statuses as a table.
+fn print_table(statuses: &[TreeStatus]) {
+    let headers = [
+        "tree",
+        "branch",
+        "upstream",
+        "ahead",
+        "behind",
+        "staged",
+        "unstaged",
+        "untracked",
+        "stash",
+    ];
+    let rows: Vec<[String; 9]> = statuses
+        .iter()
+        .map(|tree_status| {
+            let status = &tree_status.status;
+            let count = |value: usize| match tree_status.state {
+                TreeState::Clean | TreeState::Dirty => value.to_string(),
+                _ => String::new(),
+            };
+            let branch = match tree_status.state {
+                TreeState::Missing | TreeState::Unknown => format!("({})", tree_status.state),
+                TreeState::Bare => format!("{} (bare)", status.branch),
+                _ => status.branch.clone(),
+            };
+            [
+                tree_status.name.clone(),
+                branch,
+                status.upstream.clone(),
+                count(status.ahead),
+                count(status.behind),
+                count(status.staged),
+                count(status.unstaged),
+                count(status.untracked),
+                count(status.stashes),
+            ]
+        })
+        .collect();
+
+    let mut widths = headers.map(str::len);
+    for row in &rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+    let format_row = |values: &[String]| {
+        values
+            .iter()
+            .zip(widths)
+            .map(|(value, width)| format!("{value:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let headers = headers.map(str::to_string);
+    println!("{}", format_row(&headers).bold());
+    for (row, tree_status) in rows.iter().zip(statuses) {
+        let line = format_row(row);
+        match tree_status.state {
+            TreeState::Clean => println!("{}", line.green()),
+            TreeState::Dirty => println!("{}", line.yellow()),
+            TreeState::Missing | TreeState::Unknown => println!("{}", line.red().dim()),
+            TreeState::Bare => println!("{}", line.blue()),
+        }
+    }
+}
+
+/// Convert the statuses into a YAML document.
+fn to_yaml(statuses: &[TreeStatus]) -> Yaml {
+    let string = |value: &str| Yaml::String(value.to_string());
+    let integer = |value: usize| Yaml::Integer(value as i64);
+    let trees = statuses
+        .iter()
+        .map(|tree_status| {
+            let status = &tree_status.status;
+            let mut hash = yaml::Hash::new();
+            hash.insert(string("name"), string(&tree_status.name));
+            hash.insert(string(constants::PATH), string(&tree_status.path));
+            hash.insert(string("state"), string(&tree_status.state.to_string()));
+            hash.insert(string(constants::BRANCH), string(&status.branch));
+            hash.insert(string("upstream"), string(&status.upstream));
+            hash.insert(string("ahead"), integer(status.ahead));
+            hash.insert(string("behind"), integer(status.behind));
+            hash.insert(string("staged"), integer(status.staged));
+            hash.insert(string("unstaged"), integer(status.unstaged));
+            hash.insert(string("untracked"), integer(status.untracked));
+            hash.insert(string("stashes"), integer(status.stashes));
+            Yaml::Hash(hash)
+        })
+        .collect();
+    let mut doc = yaml::Hash::new();
+    doc.insert(string(constants::TREES), Yaml::Array(trees));
+
+    Yaml::Hash(doc)
+}
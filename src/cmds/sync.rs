@@ -0,0 +1,269 @@
+use anyhow::Result;
+use clap::{Parser, ValueHint};
+use rayon::prelude::*;
+use yansi::Paint;
+
+use crate::{cli, cmd, errors, git, model, query};
+
+/// Fetch and fast-forward trees to their upstream branches
+#[derive(Parser, Clone, Debug)]
+#[command(author, about, long_about)]
+pub struct SyncOptions {
+    /// Synchronize trees in parallel using the specified number of jobs.
+    #[arg(
+        long = "jobs",
+        short = 'j',
+        require_equals = false,
+        num_args = 0..=1,
+        default_missing_value = "0",
+        value_name = "JOBS",
+    )]
+    num_jobs: Option<usize>,
+    /// Filter trees by name post-query using a glob pattern
+    #[arg(long, short, default_value = "*")]
+    trees: String,
+    #[command(flatten)]
+    filters: cli::TreeFilterOptions,
+    /// Tree queries for the gardens, groups or trees to sync [default: "@*"]
+    #[arg(value_hint = ValueHint::Other)]
+    queries: Vec<String>,
+}
+
+/// The outcome of synchronizing a tree.
+enum SyncResult {
+    /// The branch was fast-forwarded to its upstream branch.
+    Updated(String),
+    /// The branch already contains its upstream branch.
+    UpToDate,
+    /// The tree was not fast-forwarded for the specified reason.
+    Skipped(String),
+    /// Git commands failed while synchronizing the tree.
+    Failed(String),
+}
+
+/// A tree to synchronize. Trees are evaluated up front because the configuration
+/// cannot be shared across threads.
+struct SyncTree {
+    /// The position of the tree in the output.
+    index: usize,
+    name: String,
+    path: std::path::PathBuf,
+    is_bare: bool,
+    is_symlink: bool,
+    /// Remotes to fetch with the default remote listed first.
+    remotes: Vec<String>,
+    /// Upstream branches configured using "branches: {<branch>: <remote>/<branch>}".
+    branches: model::IndexMap<String, String>,
+}
+
+/// Main entry point for the "garden sync" command
+pub fn main(app_context: &model::ApplicationContext, options: &mut SyncOptions) -> Result<()> {
+    if options.queries.is_empty() {
+        options.queries.push(string!("@*"));
+    }
+    cmd::initialize_threads_option(options.num_jobs)?;
+
+    let quiet = app_context.options.quiet;
+    let repositories = sync_trees(app_context, options);
+    let mut results: Vec<(usize, String, SyncResult)> = repositories
+        .into_par_iter()
+        .flat_map(sync_repository)
+        .collect();
+    results.sort_by_key(|(index, _, _)| *index);
+
+    let (mut updated, mut up_to_date, mut skipped, mut failed) = (0, 0, 0, 0);
+    for (_, name, result) in &results {
+        match result {
+            SyncResult::Updated(details) => {
+                updated += 1;
+                println!("{} {} ({})", "updated".green(), name.bold(), details);
+            }
+            SyncResult::UpToDate => {
+                up_to_date += 1;
+                if !quiet {
+                    println!("{} {}", "up-to-date".dim(), name.bold());
+                }
+            }
+            SyncResult::Skipped(reason) => {
+                skipped += 1;
+                println!("{} {} ({})", "skipped".yellow(), name.bold(), reason);
+            }
+            SyncResult::Failed(reason) => {
+                failed += 1;
+                println!("{} {} ({})", "failed".red(), name.bold(), reason);
+            }
+        }
+    }
+    println!(
+        "{} updated, {} up-to-date, {} skipped, {} failed",
+        updated, up_to_date, skipped, failed
+    );
+
+    let exit_status = if failed > 0 {
+        errors::EX_ERROR
+    } else {
+        errors::EX_OK
+    };
+
+    errors::exit_status_into_result(exit_status)
+}
+
+/// Resolve the tree queries into trees grouped by their shared Git repository.
+/// Worktrees share their parent's repository so each group is fetched once.
+fn sync_trees(
+    app_context: &model::ApplicationContext,
+    options: &SyncOptions,
+) -> Vec<Vec<SyncTree>> {
+    let config = app_context.get_root_config();
+    let mut repositories: model::IndexMap<String, Vec<SyncTree>> = model::IndexMap::new();
+    for (index, (name, context, tree)) in query::resolve_unique_trees(
+        app_context,
+        &options.queries,
+        &options.trees,
+        &options.filters,
+    )
+    .into_iter()
+    .enumerate()
+    {
+        let Some(path) = tree.pathbuf() else {
+            continue;
+        };
+        let eval_context = model::EvalContext::from_app_context(app_context, &context);
+        let mut remotes = vec![tree.default_remote.to_string()];
+        for remote in tree.remotes.keys() {
+            if *remote != tree.default_remote {
+                remotes.push(remote.to_string());
+            }
+        }
+        let branches = tree
+            .branches
+            .iter()
+            .map(|(branch, expr)| (branch.to_string(), eval_context.tree_variable(expr)))
+            .filter(|(_, remote_branch)| !remote_branch.is_empty())
+            .collect();
+        let shared_path = query::shared_worktree_path(app_context, config, &context);
+        repositories.entry(shared_path).or_default().push(SyncTree {
+            index,
+            name,
+            path,
+            is_bare: tree.is_bare_repository,
+            is_symlink: tree.is_symlink,
+            remotes,
+            branches,
+        });
+    }
+
+    repositories.into_values().collect()
+}
+
+/// Fetch the remotes for a repository and fast-forward each of its worktrees.
+fn sync_repository(trees: Vec<SyncTree>) -> Vec<(usize, String, SyncResult)> {
+    let mut fetched_remotes = model::StringSet::new();
+    let mut fetch_error: Option<String> = None;
+    trees
+        .into_iter()
+        .map(|tree| {
+            let result = sync_tree(&tree, &mut fetched_remotes, &mut fetch_error);
+            (tree.index, tree.name, result)
+        })
+        .collect()
+}
+
+/// Fetch the tree's remotes and fast-forward its current branch.
+fn sync_tree(
+    tree: &SyncTree,
+    fetched_remotes: &mut model::StringSet,
+    fetch_error: &mut Option<String>,
+) -> SyncResult {
+    if tree.is_symlink {
+        return SyncResult::Skipped(string!("symlink"));
+    }
+    if !tree.path.exists() {
+        return SyncResult::Skipped(string!("missing"));
+    }
+
+    // Fetch remotes that have not already been fetched through another worktree.
+    // Remotes that have not been added by "garden grow" are not fetched.
+    let existing_remotes = git::remotes(&tree.path);
+    for remote in &tree.remotes {
+        if !existing_remotes.contains(remote) || !fetched_remotes.insert(remote.to_string()) {
+            continue;
+        }
+        let command = ["git", "fetch", "--quiet", remote.as_str()];
+        if let Err(err) = run_git(&command, &tree.path) {
+            *fetch_error = Some(format!("git fetch {remote}: {err}"));
+        }
+    }
+    if let Some(err) = fetch_error {
+        return SyncResult::Failed(err.to_string());
+    }
+    if tree.is_bare {
+        return SyncResult::Skipped(string!("bare repository"));
+    }
+
+    let Some(branch) = git::checked_out_branch(&tree.path) else {
+        return SyncResult::Skipped(string!("detached HEAD"));
+    };
+    let Some(upstream) = tree
+        .branches
+        .get(&branch)
+        .cloned()
+        .or_else(|| git::upstream_branch(&tree.path))
+    else {
+        return SyncResult::Skipped(format!("{branch} has no upstream branch"));
+    };
+    match git::is_dirty(&tree.path) {
+        Some(false) => (),
+        Some(true) => return SyncResult::Skipped(string!("uncommitted changes")),
+        None => return SyncResult::Failed(string!("unable to read the status")),
+    }
+    let Some((ahead, behind)) = git::ahead_behind(&tree.path, &upstream) else {
+        return SyncResult::Failed(format!("unable to compare {branch} with {upstream}"));
+    };
+    if behind == 0 {
+        return SyncResult::UpToDate;
+    }
+    if ahead > 0 {
+        return SyncResult::Skipped(format!(
+            "{branch} has diverged from {upstream}: {ahead} ahead, {behind} behind"
+        ));
+    }
+
+    let command = ["git", "merge", "--ff-only", "--quiet", upstream.as_str()];
+    match run_git(&command, &tree.path) {
+        Ok(()) => {
+            let commits = if behind == 1 { "commit" } else { "commits" };
+            SyncResult::Updated(format!("{branch}: {behind} {commits} from {upstream}"))
+        }
+        Err(err) => SyncResult::Failed(format!("git merge --ff-only {upstream}: {err}")),
+    }
+}
+
+/// Run a Git command and capture its output. The last line of output is returned
+/// as the error message when the command fails.
+fn run_git(command: &[&str], path: &std::path::Path) -> Result<(), String> {
+    let capture = cmd::exec_in_dir(command, path)
+        .stdin(subprocess::Redirection::Null)
+        .stdout(subprocess::Redirection::Pipe)
+        .stderr(subprocess::Redirection::Merge)
+        .capture()
+        .map_err(|err| err.to_string())?;
+    if capture.exit_status.success() {
+        return Ok(());
+    }
+    let output = capture.stdout_str();
+    let message = output
+        .lines()
+        .rfind(|line| !line.trim().is_empty())
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    if message.is_empty() {
+        Err(format!(
+            "exit status {}",
+            cmd::exit_status(capture.exit_status)
+        ))
+    } else {
+        Err(message)
+    }
+}
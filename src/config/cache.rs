@@ -0,0 +1,187 @@
+use std::hash::{Hash, Hasher};
+
+use crate::{constants, model, path, syntax};
+
+use super::reader;
+
+/// Cache entries are written by one version of garden and read by the same version.
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// A cached configuration and the state of the files that it was read from.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CacheEntry<C> {
+    version: String,
+    root: String,
+    stamps: Vec<Stamp>,
+    config: C,
+}
+
+/// The modification time and size of a file or directory.
+/// Paths that do not exist are recorded without a modification time.
+#[derive(Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+struct Stamp {
+    path: std::path::PathBuf,
+    modified: Option<(u64, u32)>,
+    size: u64,
+}
+
+impl Stamp {
+    /// Record the current state of a path.
+    fn new(path: &std::path::Path) -> Self {
+        let metadata = std::fs::metadata(path).ok();
+        let modified = metadata
+            .as_ref()
+            .and_then(|metadata| metadata.modified().ok())
+            .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|duration| (duration.as_secs(), duration.subsec_nanos()));
+        let size = metadata.map(|metadata| metadata.len()).unwrap_or_default();
+
+        Stamp {
+            path: path.to_path_buf(),
+            modified,
+            size,
+        }
+    }
+
+    /// Return true when the path has not changed since the stamp was recorded.
+    fn is_current(&self) -> bool {
+        *self == Stamp::new(&self.path)
+    }
+}
+
+/// Return true when the cache has been enabled using "GARDEN_CACHE=1".
+fn is_enabled() -> bool {
+    std::env::var(constants::ENV_GARDEN_CACHE)
+        .ok()
+        .and_then(|value| syntax::string_to_bool(&value))
+        .unwrap_or(false)
+}
+
+/// $XDG_CACHE_HOME/garden (typically ~/.cache/garden)
+fn cache_dir() -> std::path::PathBuf {
+    let mut cache_dir;
+
+    #[cfg(unix)]
+    {
+        let xdg_dirs = xdg::BaseDirectories::new();
+        if let Some(cache_home) = xdg_dirs.get_cache_home() {
+            cache_dir = cache_home;
+        } else {
+            cache_dir = path::home_dir();
+            cache_dir.push(".cache");
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        cache_dir = path::home_dir();
+        cache_dir.push(".cache");
+    }
+
+    cache_dir.push("garden");
+
+    cache_dir
+}
+
+/// Return the cache file for a configuration. Configurations are cached separately
+/// for each garden file and "garden.root" override.
+fn cache_path(config: &model::Configuration, root: &str) -> Option<std::path::PathBuf> {
+    let config_path = path::abspath(config.path.as_ref()?);
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    config_path.hash(&mut hasher);
+    root.hash(&mut hasher);
+
+    let mut cache_path = cache_dir();
+    cache_path.push(format!("{:016x}.json", hasher.finish()));
+
+    Some(cache_path)
+}
+
+/// Replace the configuration with the cached configuration when the cache is current.
+/// Returns true when the configuration was read from the cache.
+pub(crate) fn load(config: &mut model::Configuration, config_verbose: u8) -> bool {
+    if !is_enabled() {
+        return false;
+    }
+    let Some(cache_path) = cache_path(config, config.root.get_expr()) else {
+        return false;
+    };
+    let Ok(content) = std::fs::read_to_string(&cache_path) else {
+        return false;
+    };
+    let entry: CacheEntry<model::Configuration> = match serde_json::from_str(&content) {
+        Ok(entry) => entry,
+        Err(err) => {
+            if config_verbose > 0 {
+                debug!("cache: unable to read {:?}: {}", cache_path, err);
+            }
+            return false;
+        }
+    };
+    if entry.version != VERSION
+        || entry.root != *config.root.get_expr()
+        || !entry.stamps.iter().all(Stamp::is_current)
+    {
+        if config_verbose > 0 {
+            debug!("cache: stale: {:?}", cache_path);
+        }
+        return false;
+    }
+    if config_verbose > 0 {
+        debug!("cache: read {:?}", cache_path);
+    }
+    config.apply_cached(entry.config);
+
+    true
+}
+
+/// Write a freshly parsed configuration to the cache.
+/// The root expression is the "garden.root" value from before the configuration was parsed.
+pub(crate) fn store(
+    config: &model::Configuration,
+    root: String,
+    includes: &reader::IncludeStack,
+    config_verbose: u8,
+) {
+    if !is_enabled() || includes.is_dynamic() {
+        return;
+    }
+    let Some(cache_path) = cache_path(config, &root) else {
+        return;
+    };
+    let mut stamps: Vec<Stamp> = includes.paths().map(|path| Stamp::new(path)).collect();
+    // Rebuilding garden invalidates the cache.
+    if let Ok(exe_path) = std::env::current_exe() {
+        stamps.push(Stamp::new(&exe_path));
+    }
+    let entry = CacheEntry {
+        version: VERSION.to_string(),
+        root,
+        stamps,
+        config,
+    };
+    if let Err(err) = write_entry(&cache_path, &entry) {
+        if config_verbose > 0 {
+            debug!("cache: unable to write {:?}: {}", cache_path, err);
+        }
+    } else if config_verbose > 0 {
+        debug!("cache: wrote {:?}", cache_path);
+    }
+}
+
+/// Write a cache entry. The entry is written to a temporary file and renamed into place
+/// so that concurrent garden commands never read a partially written entry.
+fn write_entry(
+    cache_path: &std::path::Path,
+    entry: &CacheEntry<&model::Configuration>,
+) -> std::io::Result<()> {
+    if let Some(cache_dir) = cache_path.parent() {
+        std::fs::create_dir_all(cache_dir)?;
+    }
+    let mut tmp_path = cache_path.to_path_buf();
+    tmp_path.set_extension(format!("json.{}", std::process::id()));
+    let content = serde_json::to_vec(entry)?;
+    std::fs::write(&tmp_path, content)?;
+    std::fs::rename(&tmp_path, cache_path).inspect_err(|_| {
+        std::fs::remove_file(&tmp_path).unwrap_or(());
+    })
+}
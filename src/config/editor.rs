@@ -0,0 +1,460 @@
+use yaml_rust::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust::scanner::{Marker, TScalarStyle};
+use yaml_rust::{yaml, Yaml, YamlEmitter, YamlLoader};
+
+/// Update the `original` YAML text so that it represents `doc`.
+///
+/// The original document is compared against `doc` and only the entries that changed
+/// are rewritten. Comments, blank lines, anchors and formatting outside of the changed
+/// entries are left untouched. Block-style mappings are edited surgically while
+/// flow-style collections, sequences and multi-line scalars are re-emitted as a whole
+/// when they change.
+///
+/// Returns `None` when the original text cannot be edited in place.
+pub(crate) fn edit(original: &str, doc: &Yaml) -> Option<String> {
+    let docs = YamlLoader::load_from_str(original).ok()?;
+    let old_doc = docs.first()?;
+    if old_doc == doc {
+        return Some(original.to_string());
+    }
+    let (Yaml::Hash(old_hash), Yaml::Hash(new_hash)) = (old_doc, doc) else {
+        return None;
+    };
+    let mut builder = NodeBuilder::new(original);
+    Parser::new_from_str(original)
+        .load(&mut builder, false)
+        .ok()?;
+    if builder.invalid {
+        return None;
+    }
+    let Some(Node::Map(mut map)) = builder.root else {
+        return None;
+    };
+    if !map.block || map.entries.is_empty() {
+        return None;
+    }
+
+    let lines = Lines::new(original);
+    let start = lines.document_start();
+    let end = lines.document_end(start);
+    lines.add_extents(&mut map, start, end);
+
+    let region_start = map.entries.first()?.start;
+    let region_end = map.entries.last()?.end;
+    let mut result = lines.text(0, region_start);
+    result.push_str(&lines.edit_map(&map, old_hash, new_hash)?);
+    result.push_str(&lines.text(region_end, lines.len()));
+
+    Some(result)
+}
+
+/// Emit a YAML document as a string.
+pub(crate) fn emit(doc: &Yaml) -> String {
+    let mut string = String::new();
+    {
+        let mut emitter = YamlEmitter::new(&mut string);
+        emitter.multiline_strings(true);
+        emitter.dump(doc).unwrap_or(()); // dump the YAML object to a String
+    }
+    string += "\n";
+
+    string
+}
+
+/// Emit a single "key: value" entry indented by the specified number of spaces.
+fn emit_entry(key: &Yaml, value: &Yaml, indent: usize) -> String {
+    let mut hash = yaml::Hash::new();
+    hash.insert(key.clone(), value.clone());
+    let string = emit(&Yaml::Hash(hash));
+    let body = string.strip_prefix("---\n").unwrap_or(&string);
+    let prefix = " ".repeat(indent);
+
+    body.lines()
+        .map(|line| {
+            if line.is_empty() {
+                "\n".to_string()
+            } else {
+                format!("{prefix}{line}\n")
+            }
+        })
+        .collect()
+}
+
+/// Emit a scalar value for use on a single line.
+fn emit_scalar(value: &Yaml) -> Option<String> {
+    match value {
+        Yaml::String(string) if !string.contains('\n') => {}
+        Yaml::Integer(_) | Yaml::Real(_) | Yaml::Boolean(_) => {}
+        _ => return None,
+    }
+    let string = emit(value);
+    let scalar = string.strip_prefix("---").unwrap_or(&string).trim();
+
+    Some(scalar.to_string())
+}
+
+/// Return true if a value is represented by a single scalar.
+fn is_scalar(value: &Yaml) -> bool {
+    matches!(
+        value,
+        Yaml::String(_) | Yaml::Integer(_) | Yaml::Real(_) | Yaml::Boolean(_) | Yaml::Null
+    )
+}
+
+/// A structural view of the original document with line positions.
+#[derive(Debug)]
+enum Node {
+    /// A scalar starting at the specified line and column. Aliases have no style.
+    Scalar {
+        line: usize,
+        col: usize,
+        style: Option<TScalarStyle>,
+    },
+    Map(Map),
+    Sequence,
+}
+
+#[derive(Debug, Default)]
+struct Map {
+    block: bool,
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    key: String,
+    /// 0-based line of the key.
+    line: usize,
+    /// Character column of the key.
+    col: usize,
+    value: Node,
+    /// First line of the entry including the comments directly above it.
+    start: usize,
+    /// Line after the last line of the entry's value.
+    end: usize,
+}
+
+/// Build a tree of Nodes from parser events.
+struct NodeBuilder {
+    chars: Vec<char>,
+    root: Option<Node>,
+    /// Containers under construction. Mappings hold their pending key.
+    stack: Vec<(Node, Option<(String, Marker)>)>,
+    /// Set when a key that cannot be edited, e.g. a non-string key, is encountered.
+    invalid: bool,
+}
+
+impl MarkedEventReceiver for NodeBuilder {
+    fn on_event(&mut self, event: Event, marker: Marker) {
+        match event {
+            Event::MappingStart(..) => {
+                self.stack.push((Node::Map(Map::default()), None));
+            }
+            Event::SequenceStart(..) => {
+                self.stack.push((Node::Sequence, None));
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                if let Some((mut node, _)) = self.stack.pop() {
+                    if let Node::Map(map) = &mut node {
+                        // Flow mappings end on their closing brace while block mappings
+                        // end on the token that follows them.
+                        map.block = self.chars.get(marker.index()) != Some(&'}');
+                    }
+                    self.insert(node, None, marker);
+                }
+            }
+            Event::Scalar(value, style, _, _) => {
+                let node = Node::Scalar {
+                    line: marker.line().saturating_sub(1),
+                    col: marker.col(),
+                    style: Some(style),
+                };
+                self.insert(node, Some(value), marker);
+            }
+            Event::Alias(_) => {
+                let node = Node::Scalar {
+                    line: marker.line().saturating_sub(1),
+                    col: marker.col(),
+                    style: None,
+                };
+                self.insert(node, None, marker);
+            }
+            _ => (),
+        }
+    }
+}
+
+impl NodeBuilder {
+    fn new(source: &str) -> Self {
+        NodeBuilder {
+            chars: source.chars().collect(),
+            root: None,
+            stack: Vec::new(),
+            invalid: false,
+        }
+    }
+
+    /// Add a completed node to its parent container.
+    fn insert(&mut self, node: Node, scalar: Option<String>, marker: Marker) {
+        let Some((parent, pending_key)) = self.stack.last_mut() else {
+            self.root = Some(node);
+            return;
+        };
+        let Node::Map(map) = parent else {
+            return;
+        };
+        match pending_key.take() {
+            Some((key, key_marker)) => {
+                map.entries.push(Entry {
+                    key,
+                    line: key_marker.line().saturating_sub(1),
+                    col: key_marker.col(),
+                    value: node,
+                    start: 0,
+                    end: 0,
+                });
+            }
+            None => {
+                if scalar.is_none() {
+                    self.invalid = true;
+                }
+                *pending_key = Some((scalar.unwrap_or_default(), marker));
+            }
+        }
+    }
+}
+
+/// The lines of the original document. Each line includes its line ending.
+struct Lines<'a> {
+    lines: Vec<&'a str>,
+}
+
+impl<'a> Lines<'a> {
+    fn new(string: &'a str) -> Self {
+        Lines {
+            lines: string.split_inclusive('\n').collect(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Return the text for the lines in the range [start, end).
+    fn text(&self, start: usize, end: usize) -> String {
+        self.lines[start..end].concat()
+    }
+
+    fn is_blank(&self, idx: usize) -> bool {
+        self.lines[idx].trim().is_empty()
+    }
+
+    fn is_comment(&self, idx: usize) -> bool {
+        self.lines[idx].trim_start().starts_with('#')
+    }
+
+    fn is_document_marker(&self, idx: usize) -> bool {
+        matches!(self.lines[idx].trim_end(), "---" | "...")
+    }
+
+    /// Does the line contain YAML content?
+    fn is_content(&self, idx: usize) -> bool {
+        !self.is_blank(idx) && !self.is_comment(idx) && !self.is_document_marker(idx)
+    }
+
+    /// Return the number of leading spaces on a line.
+    fn indent(&self, idx: usize) -> usize {
+        self.lines[idx].chars().take_while(|c| *c == ' ').count()
+    }
+
+    /// Return the line after the "---" document start marker.
+    fn document_start(&self) -> usize {
+        (0..self.len())
+            .find(|idx| !self.is_blank(*idx) && !self.is_comment(*idx))
+            .filter(|idx| self.lines[*idx].trim_end() == "---")
+            .map(|idx| idx + 1)
+            .unwrap_or(0)
+    }
+
+    /// Return the line of the "..." document end marker.
+    fn document_end(&self, start: usize) -> usize {
+        (start..self.len())
+            .find(|idx| self.is_document_marker(*idx))
+            .unwrap_or(self.len())
+    }
+
+    /// Calculate the line extents for the entries of a block mapping.
+    /// Entries start at or after `limit` and end before `bound`.
+    fn add_extents(&self, map: &mut Map, limit: usize, bound: usize) {
+        let next_lines: Vec<usize> = map
+            .entries
+            .iter()
+            .skip(1)
+            .map(|entry| entry.line)
+            .chain(std::iter::once(bound))
+            .collect();
+        let mut previous_end = limit;
+        for (entry, next) in map.entries.iter_mut().zip(next_lines) {
+            entry.end = self.body_end(entry.line, next, entry.col);
+            entry.start = self.leading_start(entry.line, previous_end);
+            previous_end = entry.end;
+            if let Node::Map(child) = &mut entry.value {
+                if child.block {
+                    self.add_extents(child, entry.line + 1, entry.end);
+                }
+            }
+        }
+    }
+
+    /// Return the line after the last line of an entry's value.
+    /// Comments indented below the key directly after the value belong to the entry.
+    fn body_end(&self, line: usize, next: usize, col: usize) -> usize {
+        let mut end = (line + 1..next)
+            .rev()
+            .find(|idx| self.is_content(*idx))
+            .map_or(line + 1, |idx| idx + 1);
+        while end < next && self.is_comment(end) && self.indent(end) > col {
+            end += 1;
+        }
+
+        end
+    }
+
+    /// Return the first line of the comments directly above an entry.
+    fn leading_start(&self, line: usize, limit: usize) -> usize {
+        let mut start = line;
+        while start > limit && self.is_comment(start - 1) {
+            start -= 1;
+        }
+
+        start
+    }
+
+    /// Return the edited text for the entries of a block mapping.
+    fn edit_map(&self, map: &Map, old: &yaml::Hash, new: &yaml::Hash) -> Option<String> {
+        if new.is_empty() {
+            return None;
+        }
+        let indent = map.entries.first()?.col;
+        let mut chunks = Vec::new();
+        for (key, value) in new {
+            let key_str = key.as_str()?;
+            let chunk = match map.entries.iter().find(|entry| entry.key == key_str) {
+                Some(entry) => self.edit_entry(entry, old.get(key)?, value)?,
+                None => emit_entry(key, value, indent),
+            };
+            chunks.push(chunk);
+        }
+
+        // The text between entries stays in place when entries are reordered.
+        let gaps: Vec<String> = map
+            .entries
+            .windows(2)
+            .map(|pair| self.text(pair[0].end, pair[1].start))
+            .collect();
+        let default_gap = match gaps.last() {
+            Some(gap) if gap.trim().is_empty() => gap.clone(),
+            _ => String::new(),
+        };
+        let mut result = String::new();
+        for (idx, chunk) in chunks.iter().enumerate() {
+            if idx > 0 {
+                result.push_str(gaps.get(idx - 1).unwrap_or(&default_gap));
+            }
+            result.push_str(chunk);
+        }
+
+        Some(result)
+    }
+
+    /// Return the edited text for a single entry.
+    fn edit_entry(&self, entry: &Entry, old: &Yaml, new: &Yaml) -> Option<String> {
+        if old == new {
+            return Some(self.text(entry.start, entry.end));
+        }
+        match (&entry.value, old, new) {
+            // Recurse into block mappings.
+            (Node::Map(child), Yaml::Hash(old_hash), Yaml::Hash(new_hash))
+                if child.block && !child.entries.is_empty() && !new_hash.is_empty() =>
+            {
+                let child_start = child.entries.first()?.start;
+                let child_end = child.entries.last()?.end;
+                let mut result = self.text(entry.start, child_start);
+                result.push_str(&self.edit_map(child, old_hash, new_hash)?);
+                result.push_str(&self.text(child_end, entry.end));
+                Some(result)
+            }
+            // Replace single-line scalars in-place to retain trailing comments.
+            (Node::Scalar { line, col, style }, _, _)
+                if *line == entry.line
+                    && entry.end == entry.line + 1
+                    && is_scalar(old)
+                    && is_scalar(new) =>
+            {
+                let text = self.lines[*line];
+                let start = text
+                    .char_indices()
+                    .nth(*col)
+                    .map(|(idx, _)| idx)
+                    .unwrap_or(text.len());
+                let end = scalar_end(text, start, (*style)?)?;
+                let mut result = self.text(entry.start, entry.line);
+                result.push_str(&text[..start]);
+                result.push_str(&emit_scalar(new)?);
+                result.push_str(&text[end..]);
+                Some(result)
+            }
+            // Replace the entry while retaining the comments above it.
+            _ => {
+                let mut result = self.text(entry.start, entry.line);
+                result.push_str(&emit_entry(
+                    &Yaml::String(entry.key.clone()),
+                    new,
+                    entry.col,
+                ));
+                Some(result)
+            }
+        }
+    }
+}
+
+/// Return the byte offset after a single-line scalar that starts at `start`.
+fn scalar_end(line: &str, start: usize, style: TScalarStyle) -> Option<usize> {
+    let rest = &line[start..];
+    match style {
+        TScalarStyle::Plain => {
+            let value = match rest.find(" #") {
+                Some(idx) => &rest[..idx],
+                None => rest,
+            };
+            Some(start + value.trim_end().len())
+        }
+        TScalarStyle::SingleQuoted => {
+            let mut chars = rest.char_indices().skip(1).peekable();
+            while let Some((idx, c)) = chars.next() {
+                if c == '\'' {
+                    if chars.peek().map(|(_, next)| *next) == Some('\'') {
+                        chars.next();
+                        continue;
+                    }
+                    return Some(start + idx + 1);
+                }
+            }
+            None
+        }
+        TScalarStyle::DoubleQuoted => {
+            let mut chars = rest.char_indices().skip(1);
+            while let Some((idx, c)) = chars.next() {
+                match c {
+                    '\\' => {
+                        chars.next();
+                    }
+                    '"' => return Some(start + idx + 1),
+                    _ => (),
+                }
+            }
+            None
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,75 @@
+use yaml_rust::{yaml, Yaml};
+
+use crate::{constants, errors, model};
+
+use super::{reader, writer};
+
+/// The state of a tree recorded in "garden.lock".
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct LockedTree {
+    pub(crate) url: String,
+    pub(crate) branch: String,
+    pub(crate) commit: String,
+}
+
+/// Locked trees keyed by their qualified tree name, e.g. "graft::tree".
+pub(crate) type LockedTrees = model::IndexMap<String, LockedTree>;
+
+/// Return the path to the "garden.lock" file that lives next to the root configuration.
+pub(crate) fn lock_path(
+    app_context: &model::ApplicationContext,
+) -> Result<std::path::PathBuf, errors::GardenError> {
+    let config = app_context.get_root_config();
+    let Some(dirname) = config.dirname.as_ref() else {
+        return Err(errors::GardenError::ConfigurationError(
+            "unable to find the directory containing the garden file".into(),
+        ));
+    };
+
+    Ok(dirname.join(constants::GARDEN_LOCK))
+}
+
+/// Read the locked trees from a lock file.
+pub(crate) fn read(path: &std::path::Path) -> Result<LockedTrees, errors::GardenError> {
+    let doc = reader::read_yaml(path)?;
+    let mut locked_trees = LockedTrees::new();
+    let Some(trees) = doc[constants::TREES].as_hash() else {
+        return Ok(locked_trees);
+    };
+    for (name, value) in trees {
+        let Some(name) = name.as_str() else {
+            continue;
+        };
+        let field = |key: &str| value[key].as_str().unwrap_or_default().to_string();
+        locked_trees.insert(
+            name.to_string(),
+            LockedTree {
+                url: field(constants::URL),
+                branch: field(constants::BRANCH),
+                commit: field(constants::COMMIT),
+            },
+        );
+    }
+
+    Ok(locked_trees)
+}
+
+/// Write the locked trees to a lock file.
+pub(crate) fn write(
+    path: &std::path::Path,
+    locked_trees: &LockedTrees,
+) -> Result<(), errors::GardenError> {
+    let string = |value: &str| Yaml::String(value.to_string());
+    let mut trees = yaml::Hash::new();
+    for (name, locked_tree) in locked_trees {
+        let mut hash = yaml::Hash::new();
+        hash.insert(string(constants::URL), string(&locked_tree.url));
+        hash.insert(string(constants::BRANCH), string(&locked_tree.branch));
+        hash.insert(string(constants::COMMIT), string(&locked_tree.commit));
+        trees.insert(string(name), Yaml::Hash(hash));
+    }
+    let mut doc = yaml::Hash::new();
+    doc.insert(string(constants::TREES), Yaml::Hash(trees));
+
+    writer::write_yaml(&Yaml::Hash(doc), path)
+}
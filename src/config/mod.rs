@@ -1,3 +1,13 @@
+/// Parsed configuration cache
+#[cfg(feature = "cache")]
+pub(crate) mod cache;
+
+/// Format-preserving YAML editor
+pub(crate) mod editor;
+
+/// Lock file reader and writer
+pub(crate) mod lock;
+
 /// YAML reader
 pub mod reader;
 
@@ -115,6 +125,13 @@ pub fn parse(
     verbose: u8,
     cfg: &mut model::Configuration,
 ) -> Result<(), errors::GardenError> {
+    #[cfg(feature = "cache")]
+    if !cache::load(cfg, verbose) {
+        let root = cfg.root.get_expr().to_string();
+        let includes = reader::parse_tracked(app_context, config_string, verbose, cfg)?;
+        cache::store(cfg, root, &includes, verbose);
+    }
+    #[cfg(not(feature = "cache"))]
     reader::parse(app_context, config_string, verbose, cfg)?;
     // Initialize the configuration now that the values have been read.
     cfg.initialize(app_context);
@@ -148,11 +165,11 @@ pub fn read_grafts_recursive(
             let path = std::path::PathBuf::from(&path_str);
             if !path.exists() {
                 let config_path = config.get_path()?;
-                return Err(errors::GardenError::ConfigurationError(format!(
-                    "{}: invalid graft in {:?}",
-                    graft.get_name(),
-                    config_path
-                )));
+                let msg = match graft.get_location() {
+                    Some(location) => format!("{location}: {}: invalid graft", graft.get_name()),
+                    None => format!("{}: invalid graft in {:?}", graft.get_name(), config_path),
+                };
+                return Err(errors::GardenError::ConfigurationError(msg));
             }
             let root = if graft.root.is_empty() {
                 None
@@ -166,7 +183,8 @@ pub fn read_grafts_recursive(
 
     // Read child grafts recursively after the immutable scope has ended.
     for (graft_name, path, root) in details {
-        app.add_graft_config(id, &graft_name, &path, root.as_deref())?;
+        let graft_id = app.add_graft_config(id, &graft_name, &path, root.as_deref())?;
+        read_grafts_recursive(app, graft_id)?;
     }
 
     Ok(())
@@ -1,3 +1,5 @@
+use yaml_rust::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust::scanner::Marker;
 use yaml_rust::{yaml, Yaml, YamlLoader};
 
 use crate::{
@@ -13,7 +15,125 @@ pub fn parse(
     config_verbose: u8,
     config: &mut model::Configuration,
 ) -> Result<(), errors::GardenError> {
-    parse_recursive(app_context, string, config_verbose, config, None)
+    parse_tracked(app_context, string, config_verbose, config)?;
+
+    Ok(())
+}
+
+/// Apply YAML Configuration from a string and return the files that were read.
+pub(crate) fn parse_tracked(
+    app_context: &model::ApplicationContext,
+    string: &str,
+    config_verbose: u8,
+    config: &mut model::Configuration,
+) -> Result<IncludeStack, errors::GardenError> {
+    let mut includes = IncludeStack::default();
+    if let Some(path) = config.path.as_ref() {
+        includes.push(path::abspath(path));
+    }
+    parse_recursive(
+        app_context,
+        string,
+        config_verbose,
+        config,
+        None,
+        &mut includes,
+    )?;
+
+    Ok(includes)
+}
+
+/// Track the garden files that are read while processing "garden.includes".
+#[derive(Default)]
+#[cfg_attr(not(feature = "cache"), allow(dead_code))]
+pub(crate) struct IncludeStack {
+    /// Canonical paths for the files that are currently being read.
+    active: Vec<std::path::PathBuf>,
+    /// Canonical paths for all of the files that have been read.
+    visited: model::IndexSet<std::path::PathBuf>,
+    /// Directories that are searched when resolving include paths.
+    directories: model::IndexSet<std::path::PathBuf>,
+    /// Includes with conditions, "$ exec" paths or ${variable} paths depend on more than
+    /// the files that were read.
+    dynamic: bool,
+}
+
+#[cfg_attr(not(feature = "cache"), allow(dead_code))]
+impl IncludeStack {
+    /// Return the files that were read and the directories that were searched.
+    pub(crate) fn paths(&self) -> impl Iterator<Item = &std::path::PathBuf> {
+        self.visited.iter().chain(self.directories.iter())
+    }
+
+    /// Return true when the includes depend on more than the files that were read.
+    pub(crate) fn is_dynamic(&self) -> bool {
+        self.dynamic
+    }
+
+    /// Record the directories that are searched when resolving an include path.
+    /// Adding or removing files in these directories can change the files that are included.
+    /// Directories that do not exist yet are also recorded so that creating them is noticed.
+    fn watch(
+        &mut self,
+        config: &model::Configuration,
+        include_path: Option<&std::path::Path>,
+        value: &str,
+    ) {
+        // Directories leading up to the first glob wildcard are watched.
+        let mut prefix = std::path::PathBuf::new();
+        let parent = std::path::Path::new(value).parent();
+        for component in parent.iter().flat_map(|parent| parent.components()) {
+            let component_str = component.as_os_str().to_string_lossy();
+            if glob::Pattern::escape(&component_str) != component_str {
+                break;
+            }
+            prefix.push(component);
+        }
+        if prefix.is_absolute() {
+            self.directories.insert(prefix);
+            return;
+        }
+        let include_dirname = include_path.and_then(|path| path.parent());
+        for dirname in include_dirname.into_iter().chain(config.dirname.as_deref()) {
+            self.directories
+                .insert(path::abspath(dirname).join(&prefix));
+        }
+    }
+
+    /// Record a file as being actively read.
+    fn push(&mut self, path: std::path::PathBuf) {
+        self.visited.insert(path.clone());
+        self.active.push(path);
+    }
+
+    /// Stop tracking the most recently pushed file.
+    fn pop(&mut self) {
+        self.active.pop();
+    }
+
+    /// Return an error describing the include chain when the path is already being read.
+    fn check_cycle(&self, path: &std::path::Path) -> Result<(), errors::GardenError> {
+        if !self.active.iter().any(|active| active == path) {
+            return Ok(());
+        }
+        let chain = self
+            .active
+            .iter()
+            .map(|active| active.as_path())
+            .chain(std::iter::once(path))
+            .map(|include| path::relative_path(include).to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join(" -> ");
+
+        Err(errors::GardenError::InvalidConfiguration {
+            msg: format!("include cycle detected: {chain}"),
+        })
+    }
+
+    /// Return true when the path has already been read.
+    fn contains(&self, path: &std::path::Path) -> bool {
+        self.visited.contains(path)
+    }
 }
 
 /// The recursive guts of `parse()`.
@@ -23,18 +143,23 @@ fn parse_recursive(
     config_verbose: u8,
     config: &mut model::Configuration,
     current_include: Option<&std::path::Path>,
+    includes: &mut IncludeStack,
 ) -> Result<(), errors::GardenError> {
-    let docs =
-        YamlLoader::load_from_str(string).map_err(|scan_err| errors::GardenError::ReadConfig {
-            err: scan_err,
-            path: config.get_path_for_display(),
-        })?;
+    let loader = load_with_marks(string).map_err(|scan_err| errors::GardenError::ReadConfig {
+        err: scan_err,
+        path: config.get_path_for_display(),
+    })?;
+    let docs = loader.yaml.documents();
     if docs.is_empty() {
         return Err(errors::GardenError::EmptyConfiguration {
             path: config.get_path()?.into(),
         });
     }
     let doc = &docs[0];
+    let source_path = current_include
+        .map(std::path::Path::to_path_buf)
+        .or_else(|| config.path.clone());
+    let source = Source::new(source_path, doc, loader.marks.docs.first());
 
     // Debug support
     if config_verbose > 2 {
@@ -150,9 +275,11 @@ fn parse_recursive(
     // We use 0 here so that the config's verbosity level is maintained.
     config.update_quiet_and_verbose_variables(config.quiet, 0);
 
-    // Variables are read early to make them available to config.eval_config_pathbuf_from_include().
+    // Variables are read early to make them available to config.eval_include_pathbufs().
     // Variables are reloaded after "includes" to give the current garden file the highest priority.
-    if !get_variables_map(&doc[constants::VARIABLES], &mut config.variables) && config_verbose > 1 {
+    if !get_variables_map(&source, &doc[constants::VARIABLES], &mut config.variables)
+        && config_verbose > 1
+    {
         debug!("config: no variables");
     }
 
@@ -160,47 +287,72 @@ fn parse_recursive(
     // This allows the path strings to reference these ${variables}.
     // This also means that variables defined by the outer-most garden config
     // override the same variables when also defined in an included garden file.
-    let mut config_includes = Vec::new();
-    if get_vec_variables(
-        constants::INCLUDES,
-        &doc[constants::GARDEN][constants::INCLUDES],
-        &mut config_includes,
-    ) {
+    let config_includes = get_includes(&doc[constants::GARDEN][constants::INCLUDES]);
+    if !config_includes.is_empty() {
         for garden_include in &config_includes {
-            let pathbuf = match config.eval_config_pathbuf_from_include(
-                app_context,
-                current_include,
-                garden_include.get_expr(),
-            ) {
-                Some(pathbuf) => pathbuf,
-                None => continue,
-            };
-            if !pathbuf.exists() {
+            if garden_include.condition.is_some()
+                || syntax::is_exec(&garden_include.path)
+                || garden_include.path.contains("${")
+            {
+                includes.dynamic = true;
+            }
+            if !config.eval_include_condition(app_context, garden_include) {
                 if config_verbose > 0 {
-                    debug!(
-                        "warning: garden.includes entry not found: {:?} -> {:?}",
-                        garden_include, pathbuf
-                    );
+                    debug!("garden.includes entry skipped: {:?}", garden_include);
                 }
                 continue;
             }
-            if pathbuf.exists() {
+            let value = eval::value(app_context, config, &garden_include.path);
+            includes.watch(config, current_include, &value);
+            for pathbuf in config.include_pathbufs(current_include, &value) {
+                if !pathbuf.exists() {
+                    if !garden_include.optional {
+                        return Err(errors::GardenError::IncludeNotFound { path: pathbuf });
+                    }
+                    if config_verbose > 0 {
+                        debug!(
+                            "warning: garden.includes entry not found: {:?} -> {:?}",
+                            garden_include, pathbuf
+                        );
+                    }
+                    continue;
+                }
+                let canonical_path = path::abspath(&pathbuf);
+                includes.check_cycle(&canonical_path)?;
+                if includes.contains(&canonical_path) {
+                    if config_verbose > 0 {
+                        debug!(
+                            "garden.includes entry already included: {:?} -> {:?}",
+                            garden_include, pathbuf
+                        );
+                    }
+                    continue;
+                }
                 if let Ok(content) = std::fs::read_to_string(&pathbuf) {
-                    parse_recursive(
+                    includes.push(canonical_path);
+                    let result = parse_recursive(
                         app_context,
                         &content,
                         config_verbose,
                         config,
                         Some(&pathbuf),
-                    )
-                    .unwrap_or(());
+                        includes,
+                    );
+                    includes.pop();
+                    // Include files that cannot be read are ignored.
+                    match result {
+                        Ok(())
+                        | Err(errors::GardenError::ReadConfig { .. })
+                        | Err(errors::GardenError::EmptyConfiguration { .. }) => (),
+                        Err(_) => return result,
+                    }
                 }
             }
         }
 
         // Reload variables after processing includes. This gives the local garden file the highest priority
         // when defining variables while also making variables available to the "includes" lines.
-        if !get_variables_map(&doc[constants::VARIABLES], &mut config.variables)
+        if !get_variables_map(&source, &doc[constants::VARIABLES], &mut config.variables)
             && config_verbose > 1
         {
             debug!("config: no reloaded variables");
@@ -211,17 +363,21 @@ fn parse_recursive(
     if config_verbose > 1 {
         debug!("config: grafts");
     }
-    if !get_grafts(&doc[constants::GRAFTS], &mut config.grafts) && config_verbose > 1 {
+    if !get_grafts(&source, &doc[constants::GRAFTS], &mut config.grafts) && config_verbose > 1 {
         debug!("config: no grafts");
     }
 
-    get_multivariables(&doc[constants::ENVIRONMENT], &mut config.environment);
+    get_multivariables(
+        &source,
+        &doc[constants::ENVIRONMENT],
+        &mut config.environment,
+    );
 
     // commands
     if config_verbose > 1 {
         debug!("config: commands");
     }
-    if !get_multivariables_map(&doc[constants::COMMANDS], &mut config.commands)
+    if !get_multivariables_map(&source, &doc[constants::COMMANDS], &mut config.commands)
         && config_verbose > 1
     {
         debug!("config: no commands");
@@ -232,6 +388,7 @@ fn parse_recursive(
         debug!("config: templates");
     }
     if !get_templates(
+        &source,
         &doc["templates"],
         &config.templates.clone(),
         &mut config.templates,
@@ -244,7 +401,7 @@ fn parse_recursive(
     if config_verbose > 1 {
         debug!("config: trees");
     }
-    if !get_trees(app_context, config, &doc[constants::TREES]) && config_verbose > 1 {
+    if !get_trees(app_context, config, &source, &doc[constants::TREES])? && config_verbose > 1 {
         debug!("config: no trees");
     }
 
@@ -252,7 +409,7 @@ fn parse_recursive(
     if config_verbose > 1 {
         debug!("config: groups");
     }
-    if !get_groups(&doc[constants::GROUPS], &mut config.groups) && config_verbose > 1 {
+    if !get_groups(&source, &doc[constants::GROUPS], &mut config.groups) && config_verbose > 1 {
         debug!("config: no groups");
     }
 
@@ -260,13 +417,183 @@ fn parse_recursive(
     if config_verbose > 1 {
         debug!("config: gardens");
     }
-    if !get_gardens(&doc[constants::GARDENS], &mut config.gardens) && config_verbose > 1 {
+    if !get_gardens(&source, &doc[constants::GARDENS], &mut config.gardens) && config_verbose > 1 {
         debug!("config: no gardens");
     }
 
     Ok(())
 }
 
+/// Line numbers for the nodes of a parsed YAML document.
+struct Source {
+    /// The garden file that the document was read from.
+    path: Option<std::path::PathBuf>,
+    /// Line numbers keyed by the address of each node in the document.
+    lines: std::collections::HashMap<*const Yaml, usize>,
+}
+
+impl Source {
+    /// Record the line numbers for the nodes in `doc` using the marks from the same parse.
+    fn new(path: Option<std::path::PathBuf>, doc: &Yaml, mark: Option<&Mark>) -> Self {
+        let mut source = Source {
+            path,
+            lines: std::collections::HashMap::new(),
+        };
+        if let Some(mark) = mark {
+            source.add_lines(doc, mark);
+        }
+
+        source
+    }
+
+    /// Walk the document and its marks in lockstep to record line numbers.
+    fn add_lines(&mut self, yaml: &Yaml, mark: &Mark) {
+        self.lines.insert(yaml as *const Yaml, mark.line());
+        match (yaml, mark) {
+            (Yaml::Array(values), Mark::Array(_, marks)) => {
+                for (value, value_mark) in values.iter().zip(marks) {
+                    self.add_lines(value, value_mark);
+                }
+            }
+            (Yaml::Hash(hash), Mark::Hash(_, marks)) => {
+                for ((key, value), (key_mark, value_mark)) in hash.iter().zip(marks) {
+                    self.add_lines(key, key_mark);
+                    self.add_lines(value, value_mark);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Return the location of a node from the document.
+    fn location(&self, yaml: &Yaml) -> Option<model::SourceLocation> {
+        self.lines
+            .get(&(yaml as *const Yaml))
+            .map(|line| model::SourceLocation::new(self.path.clone(), *line))
+    }
+}
+
+/// Line numbers for a YAML node and its children.
+#[derive(Clone, Debug)]
+enum Mark {
+    Scalar(usize),
+    Array(usize, Vec<Mark>),
+    Hash(usize, Vec<(Mark, Mark)>),
+}
+
+impl Mark {
+    fn line(&self) -> usize {
+        match self {
+            Mark::Scalar(line) | Mark::Array(line, _) | Mark::Hash(line, _) => *line,
+        }
+    }
+}
+
+/// Parse YAML documents and their marks in a single pass.
+fn load_with_marks(string: &str) -> Result<DocumentLoader, yaml_rust::ScanError> {
+    let mut loader = DocumentLoader::default();
+    Parser::new_from_str(string).load(&mut loader, true)?;
+    // YamlLoader stops building documents when it encounters an error, e.g. duplicate keys,
+    // and does not expose the error. Load the documents again to report the error.
+    if loader.yaml.documents().len() != loader.marks.docs.len() {
+        YamlLoader::load_from_str(string)?;
+    }
+
+    Ok(loader)
+}
+
+/// Forward parser events to both YamlLoader and MarkLoader.
+#[derive(Default)]
+struct DocumentLoader {
+    yaml: YamlLoader,
+    marks: MarkLoader,
+}
+
+impl MarkedEventReceiver for DocumentLoader {
+    fn on_event(&mut self, event: Event, marker: Marker) {
+        self.marks.on_event(event.clone(), marker);
+        self.yaml.on_event(event, marker);
+    }
+}
+
+/// Build a tree of marks that mirrors the structure built by YamlLoader.
+#[derive(Default)]
+struct MarkLoader {
+    docs: Vec<Mark>,
+    /// Nodes under construction and their anchor IDs.
+    stack: Vec<(Mark, usize)>,
+    /// The pending key for each mapping under construction.
+    keys: Vec<Option<Mark>>,
+    anchors: std::collections::HashMap<usize, Mark>,
+}
+
+impl MarkedEventReceiver for MarkLoader {
+    fn on_event(&mut self, event: Event, marker: Marker) {
+        match event {
+            Event::DocumentEnd => {
+                if let Some((mark, _)) = self.stack.pop() {
+                    self.docs.push(mark);
+                }
+            }
+            Event::SequenceStart(anchor_id, _) => {
+                self.stack
+                    .push((Mark::Array(marker.line(), Vec::new()), anchor_id));
+            }
+            Event::MappingStart(anchor_id, _) => {
+                self.stack
+                    .push((Mark::Hash(marker.line(), Vec::new()), anchor_id));
+                self.keys.push(None);
+            }
+            Event::SequenceEnd => {
+                if let Some(node) = self.stack.pop() {
+                    self.insert(node);
+                }
+            }
+            Event::MappingEnd => {
+                self.keys.pop();
+                if let Some(node) = self.stack.pop() {
+                    self.insert(node);
+                }
+            }
+            Event::Scalar(_, _, anchor_id, _) => {
+                self.insert((Mark::Scalar(marker.line()), anchor_id));
+            }
+            Event::Alias(anchor_id) => {
+                let mark = self
+                    .anchors
+                    .get(&anchor_id)
+                    .cloned()
+                    .unwrap_or(Mark::Scalar(marker.line()));
+                self.insert((mark, 0));
+            }
+            _ => (),
+        }
+    }
+}
+
+impl MarkLoader {
+    /// Add a completed node to its parent.
+    fn insert(&mut self, node: (Mark, usize)) {
+        let (mark, anchor_id) = node;
+        if anchor_id > 0 {
+            self.anchors.insert(anchor_id, mark.clone());
+        }
+        match self.stack.last_mut() {
+            Some((Mark::Array(_, values), _)) => values.push(mark),
+            Some((Mark::Hash(_, entries), _)) => {
+                if let Some(pending_key) = self.keys.last_mut() {
+                    match pending_key.take() {
+                        Some(key) => entries.push((key, mark)),
+                        None => *pending_key = Some(mark),
+                    }
+                }
+            }
+            Some((Mark::Scalar(_), _)) => (),
+            None => self.stack.push((mark, 0)),
+        }
+    }
+}
+
 /// Print 4 spaces for every indent level.
 fn print_indent(indent: usize) {
     for _ in 0..indent {
@@ -359,6 +686,24 @@ fn get_bool(yaml: &Yaml, value: &mut bool) -> bool {
     }
 }
 
+/// Extract a `SubmoduleMode` from `Yaml::Boolean` or a `Yaml::String` such as "recursive".
+/// Return `false` when `yaml` is not a valid submodule mode.
+pub(crate) fn get_submodule_mode(yaml: &Yaml, value: &mut Option<model::SubmoduleMode>) -> bool {
+    let mode = match yaml {
+        Yaml::Boolean(true) => Some(model::SubmoduleMode::Init),
+        Yaml::Boolean(false) => Some(model::SubmoduleMode::None),
+        Yaml::String(yaml_string) => yaml_string.parse().ok(),
+        _ => None,
+    };
+    match mode {
+        Some(mode) => {
+            *value = Some(mode);
+            true
+        }
+        None => false,
+    }
+}
+
 /// Extract a `StringSet` from `Yaml::String` or `Yaml::Array<Yaml::String>`.
 /// Return `false` when `yaml` is not `Yaml::String` or `Yaml::Array<Yaml::String>`.
 /// This function promotes a scalar `Yaml::String` into a `StringSet`
@@ -424,9 +769,24 @@ fn variable_from_yaml(name: String, yaml: &Yaml) -> Option<model::Variable> {
     }
 }
 
+/// Construct a model::Variable that records the location of the `node` it was read from.
+fn located_variable_from_yaml(
+    source: &Source,
+    name: String,
+    yaml: &Yaml,
+    node: &Yaml,
+) -> Option<model::Variable> {
+    let mut variable = variable_from_yaml(name, yaml)?;
+    if let Some(location) = source.location(node) {
+        variable.add_location(location);
+    }
+
+    Some(variable)
+}
+
 // Extract a `Variable` from `yaml`. Return `false` when `yaml` is not a `Yaml::String`.
-fn get_variable(name: String, yaml: &Yaml, value: &mut model::Variable) -> bool {
-    if let Some(variable) = variable_from_yaml(name, yaml) {
+fn get_variable(source: &Source, name: String, yaml: &Yaml, value: &mut model::Variable) -> bool {
+    if let Some(variable) = located_variable_from_yaml(source, name, yaml, yaml) {
         *value = variable;
 
         true
@@ -436,17 +796,24 @@ fn get_variable(name: String, yaml: &Yaml, value: &mut model::Variable) -> bool
 }
 
 /// Promote `Yaml::String` or `Yaml::Array<Yaml::String>` into a `Vec<Variable>`.
-fn get_vec_variables(name: &str, yaml: &Yaml, vec: &mut Vec<model::Variable>) -> bool {
+fn get_vec_variables(
+    source: &Source,
+    name: &str,
+    yaml: &Yaml,
+    vec: &mut Vec<model::Variable>,
+) -> bool {
     if let Yaml::Array(yaml_array) = yaml {
         for value in yaml_array {
-            if let Some(variable) = variable_from_yaml(name.to_string(), value) {
+            if let Some(variable) =
+                located_variable_from_yaml(source, name.to_string(), value, value)
+            {
                 vec.push(variable);
             }
         }
         return true;
     }
 
-    if let Some(variable) = variable_from_yaml(name.to_string(), yaml) {
+    if let Some(variable) = located_variable_from_yaml(source, name.to_string(), yaml, yaml) {
         vec.push(variable);
         return true;
     }
@@ -454,9 +821,40 @@ fn get_vec_variables(name: &str, yaml: &Yaml, vec: &mut Vec<model::Variable>) ->
     false
 }
 
+/// Read the entries from a "garden.includes" list. Entries are either path strings
+/// or hashes with "path", "optional" and "if" keys.
+pub(crate) fn get_includes(yaml: &Yaml) -> Vec<model::Include> {
+    let entries = match yaml {
+        Yaml::Array(yaml_array) => yaml_array.iter().collect(),
+        _ => vec![yaml],
+    };
+    let mut includes = Vec::new();
+    for entry in entries {
+        match entry {
+            Yaml::String(path) => includes.push(model::Include::new(path.clone())),
+            Yaml::Hash(_) => {
+                let mut include = model::Include::default();
+                if !get_str(&entry[constants::PATH], &mut include.path) {
+                    continue;
+                }
+                get_bool(&entry[constants::OPTIONAL], &mut include.optional);
+                include.condition = match &entry[constants::IF] {
+                    Yaml::String(condition) => Some(condition.clone()),
+                    Yaml::Boolean(condition) => Some(syntax::bool_to_string(*condition)),
+                    _ => None,
+                };
+                includes.push(include);
+            }
+            _ => (),
+        }
+    }
+
+    includes
+}
+
 /// Extract variable definitions from a `yaml::Hash` into a `VariablesMap`.
 /// Return `false` when `yaml` is not a `Yaml::Hash`.
-fn get_variables_map(yaml: &Yaml, map: &mut model::VariableMap) -> bool {
+fn get_variables_map(source: &Source, yaml: &Yaml, map: &mut model::VariableMap) -> bool {
     match yaml {
         Yaml::Hash(hash) => {
             for (k, v) in hash {
@@ -466,8 +864,8 @@ fn get_variables_map(yaml: &Yaml, map: &mut model::VariableMap) -> bool {
                         continue;
                     }
                 };
-                if let Some(variable) = variable_from_yaml(key.to_string(), v) {
-                    map.insert(key, variable);
+                if let Some(variable) = located_variable_from_yaml(source, key.to_string(), v, k) {
+                    model::insert_variable(map, key, variable);
                 }
             }
             true
@@ -477,29 +875,20 @@ fn get_variables_map(yaml: &Yaml, map: &mut model::VariableMap) -> bool {
 }
 
 /// Read `MultiVariable` definitions (e.g. "commands" and "environment").
-fn get_multivariables(yaml: &Yaml, vec: &mut Vec<model::MultiVariable>) -> bool {
+fn get_multivariables(source: &Source, yaml: &Yaml, vec: &mut Vec<model::MultiVariable>) -> bool {
     if let Yaml::Hash(hash) = yaml {
         for (k, v) in hash {
             let key = match k.as_str() {
                 Some(key_value) => key_value.to_string(),
                 None => continue,
             };
-            // Special-case arrays.
-            if let Yaml::Array(yaml_array) = v {
-                let mut variables = Vec::new();
-                for value in yaml_array {
-                    if let Some(variable) = variable_from_yaml(key.to_string(), value) {
-                        variables.push(variable);
-                    }
-                }
-                vec.push(model::MultiVariable::new(key, variables));
+            let variables = get_located_variables(source, &key, k, v);
+            if variables.is_empty() && !v.is_array() {
                 continue;
             }
-
-            if let Some(variable) = variable_from_yaml(key.to_string(), v) {
-                let variables = vec![variable];
-                vec.push(model::MultiVariable::new(key, variables));
-            }
+            let mut multivariable = model::MultiVariable::new(key, variables);
+            multivariable.set_location(source.location(k));
+            vec.push(multivariable);
         }
 
         return true;
@@ -509,7 +898,11 @@ fn get_multivariables(yaml: &Yaml, vec: &mut Vec<model::MultiVariable>) -> bool
 }
 
 /// Read a `Yaml::Hash` of variable definitions into a `MultiVariableMap`.
-fn get_multivariables_map(yaml: &Yaml, multivariables: &mut model::MultiVariableMap) -> bool {
+fn get_multivariables_map(
+    source: &Source,
+    yaml: &Yaml,
+    multivariables: &mut model::MultiVariableMap,
+) -> bool {
     match yaml {
         Yaml::Hash(hash) => {
             for (k, v) in hash {
@@ -517,21 +910,11 @@ fn get_multivariables_map(yaml: &Yaml, multivariables: &mut model::MultiVariable
                     Some(key_value) => key_value.to_string(),
                     None => continue,
                 };
-                if let Yaml::Array(yaml_array) = v {
-                    let mut variables = Vec::new();
-                    for value in yaml_array {
-                        if let Some(variable) = variable_from_yaml(key.to_string(), value) {
-                            variables.push(variable);
-                        }
-                    }
-                    multivariables.insert(key, variables);
+                let variables = get_located_variables(source, &key, k, v);
+                if variables.is_empty() && !v.is_array() {
                     continue;
                 }
-
-                if let Some(variable) = variable_from_yaml(key.to_string(), v) {
-                    let variables = vec![variable];
-                    multivariables.insert(key, variables);
-                }
+                multivariables.insert(key, variables);
             }
             true
         }
@@ -539,8 +922,30 @@ fn get_multivariables_map(yaml: &Yaml, multivariables: &mut model::MultiVariable
     }
 }
 
+/// Read the variables for a single "commands" or "environment" entry.
+/// Array entries record their own line. Scalar entries record the line of their key.
+fn get_located_variables(
+    source: &Source,
+    name: &str,
+    key: &Yaml,
+    yaml: &Yaml,
+) -> Vec<model::Variable> {
+    // Special-case arrays.
+    if let Yaml::Array(yaml_array) = yaml {
+        return yaml_array
+            .iter()
+            .filter_map(|value| located_variable_from_yaml(source, name.to_string(), value, value))
+            .collect();
+    }
+
+    located_variable_from_yaml(source, name.to_string(), yaml, key)
+        .into_iter()
+        .collect()
+}
+
 /// Read template definitions.
 fn get_templates(
+    source: &Source,
     yaml: &Yaml,
     config_templates: &IndexMap<String, model::Template>,
     templates: &mut IndexMap<String, model::Template>,
@@ -554,7 +959,7 @@ fn get_templates(
                 };
                 templates.insert(
                     template_name,
-                    get_template(name, value, config_templates, yaml),
+                    get_template(source, name, value, config_templates, yaml),
                 );
             }
             true
@@ -565,6 +970,7 @@ fn get_templates(
 
 /// Read a single template definition.
 fn get_template(
+    source: &Source,
     name: &Yaml,
     value: &Yaml,
     config_templates: &IndexMap<String, model::Template>,
@@ -582,7 +988,7 @@ fn get_template(
         if get_str(value, &mut url) {
             template.tree.remotes.insert(
                 constants::ORIGIN.to_string(),
-                model::Variable::from_expr(constants::ORIGIN.to_string(), url),
+                located_variable(source, constants::ORIGIN, url, value),
             );
             return template;
         }
@@ -591,7 +997,7 @@ fn get_template(
         if get_str(&value[constants::URL], &mut url) {
             template.tree.remotes.insert(
                 string!(constants::ORIGIN),
-                model::Variable::from_expr(constants::URL.to_string(), url),
+                located_variable(source, constants::URL, url, &value[constants::URL]),
             );
         }
     }
@@ -606,6 +1012,7 @@ fn get_template(
         // We check here first so that parsing is not order-dependent.
         if let Yaml::Hash(_) = templates[template_name.as_ref()] {
             let base = get_template(
+                source,
                 &Yaml::String(template_name.clone()),
                 &templates[template_name.as_ref()],
                 config_templates,
@@ -613,6 +1020,7 @@ fn get_template(
             );
 
             base.apply(&mut template.tree);
+            model::append_variables(&mut template.parameters, &base.parameters);
         } else {
             // If the template didn't exist in the local YAML then read it from
             // the previously-parsed templates. This allows templates to be used
@@ -620,36 +1028,73 @@ fn get_template(
             // file and not present in the current YAML payload.
             if let Some(base) = config_templates.get(template_name) {
                 base.apply(&mut template.tree);
+                model::append_variables(&mut template.parameters, &base.parameters);
             }
         }
         // The base templates were already processed.
         template.tree.templates.truncate(0);
     }
 
-    get_tree_fields(value, &mut template.tree);
+    get_parameters(
+        source,
+        &value[constants::PARAMETERS],
+        &mut template.parameters,
+    );
+    get_tree_fields(source, value, &mut template.tree);
 
     template
 }
 
+/// Read template parameters. Parameters without a value are required parameters.
+fn get_parameters(source: &Source, yaml: &Yaml, parameters: &mut model::VariableMap) {
+    let Yaml::Hash(hash) = yaml else {
+        return;
+    };
+    for (key, value) in hash {
+        let Some(name) = key.as_str() else {
+            continue;
+        };
+        let parameter = match value {
+            Yaml::Null => {
+                let mut parameter = model::Variable::new(name.to_string(), String::new(), true);
+                if let Some(location) = source.location(key) {
+                    parameter.add_location(location);
+                }
+                Some(parameter)
+            }
+            _ => located_variable_from_yaml(source, name.to_string(), value, key),
+        };
+        if let Some(parameter) = parameter {
+            model::insert_variable(parameters, name.to_string(), parameter);
+        }
+    }
+}
+
 /// Read tree definitions.
 fn get_trees(
     app_context: &model::ApplicationContext,
     config: &mut model::Configuration,
+    source: &Source,
     yaml: &Yaml,
-) -> bool {
+) -> Result<bool, errors::GardenError> {
     match yaml {
         Yaml::Hash(hash) => {
             for (name, value) in hash {
+                let location = source.location(name);
                 if let Yaml::String(url) = value {
                     // If the tree already exists then update it, otherwise create a new entry.
-                    let tree = get_tree_from_url(name, url);
+                    let mut tree = get_tree_from_url(source, name, value, url);
                     if let Some(current_tree) = config.trees.get_mut(tree.get_name()) {
                         current_tree.clone_from_tree(&tree);
+                        if let Some(location) = location {
+                            model::add_location(&mut current_tree.locations, location);
+                        }
                     } else {
+                        tree.locations.extend(location);
                         config.trees.insert(tree.get_name().to_string(), tree);
                     }
                 } else {
-                    let tree = get_tree(app_context, config, name, value, hash, true);
+                    let mut tree = get_tree(app_context, config, source, name, value, hash, true)?;
 
                     // Should we replace the current entry or sparsely override it?
                     // We sparsely override by default.
@@ -662,21 +1107,25 @@ fn get_trees(
                     match current_tree_opt {
                         Some(current_tree) if !replace => {
                             current_tree.clone_from_tree(&tree);
+                            if let Some(location) = location {
+                                model::add_location(&mut current_tree.locations, location);
+                            }
                         }
                         _ => {
+                            tree.locations.extend(location);
                             config.trees.insert(tree.get_name().to_string(), tree);
                         }
                     }
                 }
             }
-            true
+            Ok(true)
         }
-        _ => false,
+        _ => Ok(false),
     }
 }
 
 /// Return a tree from a oneline `tree: <url>` entry.
-fn get_tree_from_url(name: &Yaml, url: &str) -> model::Tree {
+fn get_tree_from_url(source: &Source, name: &Yaml, value: &Yaml, url: &str) -> model::Tree {
     let mut tree = model::Tree::default();
 
     // Tree name
@@ -691,53 +1140,95 @@ fn get_tree_from_url(name: &Yaml, url: &str) -> model::Tree {
     }
     tree.remotes.insert(
         constants::ORIGIN.to_string(),
-        model::Variable::from_expr(constants::ORIGIN.to_string(), url.to_string()),
+        located_variable(source, constants::ORIGIN, url.to_string(), value),
     );
 
     tree
 }
 
+/// Construct a model::Variable from an expression found at the specified node.
+fn located_variable(source: &Source, name: &str, expr: String, node: &Yaml) -> model::Variable {
+    let mut variable = model::Variable::from_expr(name.to_string(), expr);
+    if let Some(location) = source.location(node) {
+        variable.add_location(location);
+    }
+
+    variable
+}
+
 /// Read fields common to trees and templates.
 #[inline]
-fn get_tree_fields(value: &Yaml, tree: &mut model::Tree) {
-    get_variables_map(&value[constants::VARIABLES], &mut tree.variables);
-    get_multivariables_map(&value[constants::GITCONFIG], &mut tree.gitconfig);
+fn get_tree_fields(source: &Source, value: &Yaml, tree: &mut model::Tree) {
+    get_variables_map(source, &value[constants::VARIABLES], &mut tree.variables);
+    get_multivariables_map(source, &value[constants::GITCONFIG], &mut tree.gitconfig);
     get_str(&value[constants::DEFAULT_REMOTE], &mut tree.default_remote);
     get_str_trimmed(&value[constants::DESCRIPTION], &mut tree.description);
-    get_str_variables_map(&value[constants::REMOTES], &mut tree.remotes);
-    get_vec_variables(constants::LINKS, &value[constants::LINKS], &mut tree.links);
+    get_indexset_str(&value[constants::TAGS], &mut tree.tags);
+    get_str_variables_map(source, &value[constants::REMOTES], &mut tree.remotes);
+    get_vec_variables(
+        source,
+        constants::LINKS,
+        &value[constants::LINKS],
+        &mut tree.links,
+    );
 
-    get_multivariables(&value[constants::ENVIRONMENT], &mut tree.environment);
-    get_multivariables_map(&value[constants::COMMANDS], &mut tree.commands);
+    get_multivariables(
+        source,
+        &value[constants::ENVIRONMENT],
+        &mut tree.environment,
+    );
+    get_multivariables_map(source, &value[constants::COMMANDS], &mut tree.commands);
 
     get_variable(
+        source,
         constants::BRANCH.to_string(),
         &value[constants::BRANCH],
         &mut tree.branch,
     );
-    get_variables_map(&value[constants::BRANCHES], &mut tree.branches);
+    get_variables_map(source, &value[constants::BRANCHES], &mut tree.branches);
+    get_variable(
+        source,
+        constants::COMMIT.to_string(),
+        &value[constants::COMMIT],
+        &mut tree.commit,
+    );
     get_variable(
+        source,
+        constants::TAG.to_string(),
+        &value[constants::TAG],
+        &mut tree.tag,
+    );
+    get_variable(
+        source,
         constants::SYMLINK.to_string(),
         &value[constants::SYMLINK],
         &mut tree.symlink,
     );
     get_variable(
+        source,
         constants::WORKTREE.to_string(),
         &value[constants::WORKTREE],
         &mut tree.worktree,
     );
 
     get_i64(&value[constants::DEPTH], &mut tree.clone_depth);
+    get_str(&value[constants::FILTER], &mut tree.clone_filter);
     get_bool(&value[constants::BARE], &mut tree.is_bare_repository);
     get_bool(&value[constants::SINGLE_BRANCH], &mut tree.is_single_branch);
+    get_indexset_str(
+        &value[constants::SPARSE_CHECKOUT],
+        &mut tree.sparse_checkout,
+    );
+    get_submodule_mode(&value[constants::SUBMODULES], &mut tree.submodules);
 
     // Load the URL and store it in the "origin" remote.
     {
         let mut url = String::new();
         if get_str(&value[constants::URL], &mut url) {
-            tree.remotes.insert(
+            model::insert_variable(
+                &mut tree.remotes,
                 tree.default_remote.to_string(),
-                model::Variable::from_expr(constants::URL.to_string(), url),
+                located_variable(source, constants::URL, url, &value[constants::URL]),
             );
         }
     }
@@ -749,11 +1240,12 @@ fn get_tree_fields(value: &Yaml, tree: &mut model::Tree) {
 fn get_tree(
     app_context: &model::ApplicationContext,
     config: &mut model::Configuration,
+    source: &Source,
     name: &Yaml,
     value: &Yaml,
     trees: &yaml::Hash,
     variables: bool,
-) -> model::Tree {
+) -> Result<model::Tree, errors::GardenError> {
     // The tree that will be built and returned.
     let mut tree = model::Tree::default();
 
@@ -763,7 +1255,15 @@ fn get_tree(
         // Holds a base tree specified using "extend: <tree>".
         let tree_name = Yaml::String(extend.clone());
         if let Some(tree_values) = trees.get(&tree_name) {
-            let base_tree = get_tree(app_context, config, &tree_name, tree_values, trees, false);
+            let base_tree = get_tree(
+                app_context,
+                config,
+                source,
+                &tree_name,
+                tree_values,
+                trees,
+                false,
+            )?;
             tree.clone_from_tree(&base_tree);
         } else {
             // Allow the referenced tree to be found from an earlier include.
@@ -781,7 +1281,15 @@ fn get_tree(
         if !parent_expr.is_empty() {
             let tree_name = Yaml::String(parent_name);
             if let Some(tree_values) = trees.get(&tree_name) {
-                let base = get_tree(app_context, config, &tree_name, tree_values, trees, true);
+                let base = get_tree(
+                    app_context,
+                    config,
+                    source,
+                    &tree_name,
+                    tree_values,
+                    trees,
+                    true,
+                )?;
                 tree.clone_from_tree(&base);
             }
         }
@@ -791,19 +1299,37 @@ fn get_tree(
     // Templates
     // Process the base templates in the specified order before processing
     // the template itself.
-    get_indexset_str(&value[constants::TEMPLATES], &mut tree.templates);
-    for template_name in &tree.templates.clone() {
+    // Entries are either template names or hashes with "name" and "args" keys.
+    for (template_name, entry) in get_template_entries(&value[constants::TEMPLATES]) {
+        tree.templates.insert(template_name.to_string());
         // Do we have a template by this name? If so, apply the template.
-        if let Some(template) = config.templates.get(template_name) {
-            template.apply(&mut tree);
+        let Some(template) = config.templates.get(template_name) else {
+            continue;
+        };
+        let mut args = model::VariableMap::new();
+        get_variables_map(source, &entry[constants::ARGS], &mut args);
+        if let Some(parameter) = template.missing_parameter(&args) {
+            let tree_name = name.as_str().unwrap_or_default();
+            let msg = format!(
+                "{tree_name}: template \"{template_name}\" requires parameter \"{parameter}\""
+            );
+            let location = source.location(entry).or_else(|| source.location(name));
+            return Err(errors::GardenError::ConfigurationError(
+                model::format_with_location(location.as_ref(), &msg),
+            ));
         }
+        template.apply_with_args(&mut tree, &args);
     }
 
     // Tree name
     get_str(name, tree.get_name_mut());
 
     // Tree path
-    if !get_str(&value[constants::PATH], tree.get_path_mut().get_expr_mut()) {
+    if get_str(&value[constants::PATH], tree.get_path_mut().get_expr_mut()) {
+        if let Some(location) = source.location(&value[constants::PATH]) {
+            tree.get_path_mut().add_location(location);
+        }
+    } else {
         // Default to the name when "path" is unspecified.
         let tree_name = tree.get_name().to_string();
         tree.get_path_mut().set_expr(tree_name.to_string());
@@ -819,35 +1345,64 @@ fn get_tree(
         tree.add_builtin_variables();
     }
 
-    get_tree_fields(value, &mut tree);
+    get_tree_fields(source, value, &mut tree);
 
-    tree
+    Ok(tree)
+}
+
+/// Return the template names and entries from a tree's "templates" field.
+fn get_template_entries(yaml: &Yaml) -> Vec<(&str, &Yaml)> {
+    let entries = match yaml {
+        Yaml::Array(yaml_array) => yaml_array.iter().collect(),
+        _ => vec![yaml],
+    };
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            entry
+                .as_str()
+                .or_else(|| entry[constants::NAME].as_str())
+                .map(|template_name| (template_name, entry))
+        })
+        .collect()
 }
 
 /// Read simple string values into a garden::model::VariableMap.
-fn get_str_variables_map(yaml: &Yaml, remotes: &mut model::VariableMap) {
+fn get_str_variables_map(source: &Source, yaml: &Yaml, remotes: &mut model::VariableMap) {
     let hash = match yaml {
         Yaml::Hash(hash) => hash,
         _ => return,
     };
     for (name, value) in hash {
         if let (Some(name_str), Some(value_str)) = (name.as_str(), value.as_str()) {
-            remotes.insert(
+            model::insert_variable(
+                remotes,
                 name_str.to_string(),
-                model::Variable::from_expr(name_str.to_string(), value_str.to_string()),
+                located_variable(source, name_str, value_str.to_string(), name),
             );
         }
     }
 }
 
 /// Read group definitions. Return `false` when `yaml` is not a `Yaml::Hash`.
-fn get_groups(yaml: &Yaml, groups: &mut IndexMap<model::GroupName, model::Group>) -> bool {
+fn get_groups(
+    source: &Source,
+    yaml: &Yaml,
+    groups: &mut IndexMap<model::GroupName, model::Group>,
+) -> bool {
     match yaml {
         Yaml::Hash(hash) => {
             for (name, value) in hash {
                 let mut group = model::Group::default();
                 get_str(name, group.get_name_mut());
                 get_indexset_str(value, &mut group.members);
+                // Groups are replaced by later definitions. Retain the earlier locations.
+                if let Some(previous) = groups.get(group.get_name()) {
+                    group.locations.clone_from(&previous.locations);
+                }
+                if let Some(location) = source.location(name) {
+                    model::add_location(&mut group.locations, location);
+                }
                 groups.insert(group.get_name_owned(), group);
             }
             true
@@ -857,7 +1412,11 @@ fn get_groups(yaml: &Yaml, groups: &mut IndexMap<model::GroupName, model::Group>
 }
 
 /// Read garden definitions. Return `false` when `yaml` is not a `Yaml::Hash`.
-fn get_gardens(yaml: &Yaml, gardens: &mut IndexMap<String, model::Garden>) -> bool {
+fn get_gardens(
+    source: &Source,
+    yaml: &Yaml,
+    gardens: &mut IndexMap<String, model::Garden>,
+) -> bool {
     match yaml {
         Yaml::Hash(hash) => {
             for (name, value) in hash {
@@ -865,10 +1424,21 @@ fn get_gardens(yaml: &Yaml, gardens: &mut IndexMap<String, model::Garden>) -> bo
                 get_str(name, garden.get_name_mut());
                 get_indexset_str(&value[constants::GROUPS], &mut garden.groups);
                 get_indexset_str(&value[constants::TREES], &mut garden.trees);
-                get_multivariables_map(&value[constants::GITCONFIG], &mut garden.gitconfig);
-                get_variables_map(&value[constants::VARIABLES], &mut garden.variables);
-                get_multivariables(&value[constants::ENVIRONMENT], &mut garden.environment);
-                get_multivariables_map(&value[constants::COMMANDS], &mut garden.commands);
+                get_multivariables_map(source, &value[constants::GITCONFIG], &mut garden.gitconfig);
+                get_variables_map(source, &value[constants::VARIABLES], &mut garden.variables);
+                get_multivariables(
+                    source,
+                    &value[constants::ENVIRONMENT],
+                    &mut garden.environment,
+                );
+                get_multivariables_map(source, &value[constants::COMMANDS], &mut garden.commands);
+                // Gardens are replaced by later definitions. Retain the earlier locations.
+                if let Some(previous) = gardens.get(garden.get_name()) {
+                    garden.locations.clone_from(&previous.locations);
+                }
+                if let Some(location) = source.location(name) {
+                    model::add_location(&mut garden.locations, location);
+                }
                 gardens.insert(garden.get_name().to_string(), garden);
             }
             true
@@ -879,11 +1449,16 @@ fn get_gardens(yaml: &Yaml, gardens: &mut IndexMap<String, model::Garden>) -> bo
 
 /// Read a "grafts" block from `yaml` into a `Vec<Graft>`.
 /// Return `false` when `yaml` is not a `Yaml::Hash`.
-fn get_grafts(yaml: &Yaml, grafts: &mut IndexMap<model::GardenName, model::Graft>) -> bool {
+fn get_grafts(
+    source: &Source,
+    yaml: &Yaml,
+    grafts: &mut IndexMap<model::GardenName, model::Graft>,
+) -> bool {
     match yaml {
         Yaml::Hash(yaml_hash) => {
             for (name, value) in yaml_hash {
-                let graft = get_graft(name, value);
+                let mut graft = get_graft(name, value);
+                graft.set_location(source.location(name));
                 grafts.insert(graft.get_name().to_string(), graft);
             }
             true
@@ -914,6 +1489,14 @@ fn get_graft(name: &Yaml, graft: &Yaml) -> model::Graft {
 
 /// Read and parse YAML from a file path.
 pub fn read_yaml<P>(path: P) -> Result<Yaml, errors::GardenError>
+where
+    P: std::convert::AsRef<std::path::Path> + std::fmt::Debug,
+{
+    read_yaml_and_string(path).map(|(doc, _string)| doc)
+}
+
+/// Read and parse YAML from a file path. Return the document and the file's contents.
+pub fn read_yaml_and_string<P>(path: P) -> Result<(Yaml, String), errors::GardenError>
 where
     P: std::convert::AsRef<std::path::Path> + std::fmt::Debug,
 {
@@ -934,7 +1517,7 @@ where
         });
     }
 
-    Ok(docs[0].clone())
+    Ok((docs[0].clone(), string))
 }
 
 /// Return an empty `Yaml::Hash` as a `Yaml` document.
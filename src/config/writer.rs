@@ -1,7 +1,8 @@
 use std::io::Write;
 
-use yaml_rust::{Yaml, YamlEmitter};
+use yaml_rust::Yaml;
 
+use crate::config::editor;
 use crate::errors;
 
 /// Write a Yaml object to a file
@@ -9,15 +10,76 @@ pub fn write_yaml<P>(doc: &Yaml, path: P) -> Result<(), errors::GardenError>
 where
     P: std::convert::AsRef<std::path::Path> + std::fmt::Debug,
 {
-    // Emit the YAML configuration into a string
-    let mut out_str = String::new();
-    {
-        let mut emitter = YamlEmitter::new(&mut out_str);
-        emitter.multiline_strings(true);
-        emitter.dump(doc).unwrap_or(()); // dump the YAML object to a String
+    write_string(&editor::emit(doc), path)
+}
+
+/// Write a Yaml object to a file that was read from the `original` string.
+/// Comments and formatting from the original are preserved for unmodified entries.
+pub fn update_yaml<P>(original: &str, doc: &Yaml, path: P) -> Result<(), errors::GardenError>
+where
+    P: std::convert::AsRef<std::path::Path> + std::fmt::Debug,
+{
+    let string = match editor::edit(original, doc) {
+        Some(string) => string,
+        None => {
+            // Re-emitting the whole document drops the original comments.
+            if original.lines().any(|line| line.contains('#')) {
+                eprintln!(
+                    "warning: {}: unable to preserve comments and formatting",
+                    path.as_ref().display()
+                );
+            }
+            editor::emit(doc)
+        }
+    };
+    write_string(&string, path)
+}
+
+/// Format a Yaml object as a YAML document string
+pub fn to_yaml_string(doc: &Yaml) -> String {
+    editor::emit(doc)
+}
+
+/// Format a Yaml object as a JSON string
+pub fn to_json_string(doc: &Yaml) -> String {
+    serde_json::to_string_pretty(&json_value(doc)).unwrap_or_default()
+}
+
+/// Convert a Yaml object into a JSON value. Hash keys are converted into strings.
+fn json_value(doc: &Yaml) -> serde_json::Value {
+    match doc {
+        Yaml::String(value) => serde_json::Value::String(value.clone()),
+        Yaml::Integer(value) => serde_json::Value::from(*value),
+        Yaml::Real(value) => value
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(serde_json::Value::Number)
+            .unwrap_or_else(|| serde_json::Value::String(value.clone())),
+        Yaml::Boolean(value) => serde_json::Value::Bool(*value),
+        Yaml::Array(values) => serde_json::Value::Array(values.iter().map(json_value).collect()),
+        Yaml::Hash(hash) => {
+            let mut map = serde_json::Map::new();
+            for (key, value) in hash {
+                let key = match key {
+                    Yaml::String(key) | Yaml::Real(key) => key.clone(),
+                    Yaml::Integer(key) => key.to_string(),
+                    Yaml::Boolean(key) => key.to_string(),
+                    _ => continue,
+                };
+                map.insert(key, json_value(value));
+            }
+            serde_json::Value::Object(map)
+        }
+        _ => serde_json::Value::Null,
     }
-    out_str += "\n";
+}
 
+/// Write a string to a file
+fn write_string<P>(string: &str, path: P) -> Result<(), errors::GardenError>
+where
+    P: std::convert::AsRef<std::path::Path> + std::fmt::Debug,
+{
     let mut file = std::fs::File::create(&path).map_err(|io_err| {
         errors::GardenError::CreateConfigurationError {
             path: path.as_ref().into(),
@@ -25,7 +87,7 @@ where
         }
     })?;
 
-    file.write_all(&out_str.into_bytes()).map_err(|_| {
+    file.write_all(string.as_bytes()).map_err(|_| {
         errors::GardenError::WriteConfigurationError {
             path: path.as_ref().into(),
         }
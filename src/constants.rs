@@ -1,3 +1,6 @@
+/// The "args" key in a "templates" entry provides values for the template's parameters.
+pub const ARGS: &str = "args";
+
 /// The "bare" key in a tree block defines a bare repository.
 pub const BARE: &str = "bare";
 
@@ -7,6 +10,9 @@ pub const BRANCH: &str = "branch";
 /// The "branches" section in a tree block maps local to remote branches.
 pub const BRANCHES: &str = "branches";
 
+/// The "commit" key in a tree block pins the tree to a commit.
+pub const COMMIT: &str = "commit";
+
 /// The "config" key in a graft definition defines the path to a garden config file.
 pub const CONFIG: &str = "config";
 
@@ -34,9 +40,16 @@ pub const DEPTH: &str = "depth";
 /// The "description" key in a tree block describes the tree.
 pub const DESCRIPTION: &str = "description";
 
+/// The "filter" key in a tree block creates partial clones using "git clone --filter".
+pub const FILTER: &str = "filter";
+
 /// The default "." tree query selects the tree in the current directory.
 pub(crate) const DOT: &str = ".";
 
+/// The "GARDEN_CACHE" environment variable enables the parsed configuration cache.
+#[cfg(feature = "cache")]
+pub(crate) const ENV_GARDEN_CACHE: &str = "GARDEN_CACHE";
+
 /// The "HOME" environment variable is used to resolve "~".
 pub(crate) const ENV_HOME: &str = "HOME";
 
@@ -71,6 +84,9 @@ pub const GARDEN_CMD_VERBOSE: &str = "GARDEN_CMD_VERBOSE";
 /// The default "garden.yaml" configuration file.
 pub const GARDEN_CONFIG: &str = "garden.yaml";
 
+/// The "garden.lock" file records the commit that is checked out in each tree.
+pub const GARDEN_LOCK: &str = "garden.lock";
+
 /// Builtin variable for the "garden.yaml" configuration directory.
 pub const GARDEN_CONFIG_DIR: &str = "GARDEN_CONFIG_DIR";
 
@@ -101,6 +117,10 @@ pub const GRAFTS: &str = "grafts";
 /// The "groups" section of the configuration defines named groups of trees.
 pub const GROUPS: &str = "groups";
 
+/// The "if" key in a "garden.includes" entry makes the include conditional.
+/// The file is only included when the expression evaluates to a truthy value.
+pub const IF: &str = "if";
+
 /// The "includes" key in the garden block reads additional configuration
 /// files directly into the configuration.
 pub const INCLUDES: &str = "includes";
@@ -112,9 +132,20 @@ pub const INTERACTIVE_SHELL: &str = "interactive-shell";
 /// The "links" key in a tree block defines URLs displayed by "garden ls".
 pub const LINKS: &str = "links";
 
+/// The "name" key in a "templates" entry specifies the template to apply.
+pub const NAME: &str = "name";
+
+/// The "optional" key in a "garden.includes" entry controls whether a missing
+/// include file is an error. Includes are optional by default.
+pub const OPTIONAL: &str = "optional";
+
 /// The "origin" remote is the default Git remote name.
 pub(crate) const ORIGIN: &str = "origin";
 
+/// The "parameters" key in a template block declares parameters and their default values.
+/// Trees provide values for the parameters using "args".
+pub const PARAMETERS: &str = "parameters";
+
 /// The "path" key in a tree block defines the location for a tree.
 /// A directory relative to "garden.root" named after the tree is used as
 /// the tree's path by default.
@@ -182,9 +213,24 @@ pub(crate) const SHELL_ZSH: &str = "zsh";
 /// are cloned and fetched by default.
 pub const SINGLE_BRANCH: &str = "single-branch";
 
+/// The "sparse-checkout" key in a tree block lists the directories that are checked out
+/// using cone-mode "git sparse-checkout".
+pub const SPARSE_CHECKOUT: &str = "sparse-checkout";
+
+/// The "submodules" key in a tree block initializes submodules when set to "true"
+/// and initializes nested submodules when set to "recursive".
+pub const SUBMODULES: &str = "submodules";
+
 /// The "symlink" key in a tree block creates a symlink.
 pub const SYMLINK: &str = "symlink";
 
+/// The "tag" key in a tree block pins the tree to a tag.
+pub const TAG: &str = "tag";
+
+/// The "tags" key in a tree or template block adds tags that can be used
+/// to select trees using "#tag" tree queries.
+pub const TAGS: &str = "tags";
+
 /// The "templates" section defines tree templates that can be used when
 /// defining tree entries.
 pub const TEMPLATES: &str = "templates";
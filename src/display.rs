@@ -45,51 +45,46 @@ pub(crate) fn display_tree(
     tree: &model::Tree,
     path_str: &str,
     tree_branches: bool,
+    pin: Option<&model::TreePin>,
     verbose: u8,
 ) -> String {
-    if verbose > 0 {
-        if tree_branches {
-            if let Some(path) = tree.canonical_pathbuf() {
-                if let Some(branch) = git::branch(&path) {
-                    return format!(
-                        "{} {} {}{}{} {}",
-                        "#".cyan(),
-                        tree.get_name().blue().bold(),
-                        "[".blue(),
-                        branch.green().bold(),
-                        "]".blue(),
-                        path_str.blue()
-                    );
-                }
-            }
-        }
-        format!(
-            "{} {} {}",
-            "#".cyan(),
-            tree.get_name().blue().bold(),
-            path_str.blue()
-        )
+    let mut output = format!("{} {}", "#".cyan(), tree.get_name().blue().bold());
+    let branch = if tree_branches {
+        tree.canonical_pathbuf().and_then(|path| git::branch(&path))
     } else {
-        if tree_branches {
-            if let Some(path) = tree.canonical_pathbuf() {
-                if let Some(branch) = git::branch(&path) {
-                    return format!(
-                        "{} {} {}{}{}",
-                        "#".cyan(),
-                        tree.get_name().blue().bold(),
-                        "[".blue(),
-                        branch.green().bold(),
-                        "]".blue()
-                    );
-                }
+        None
+    };
+    // Pins are displayed next to the branch, e.g. "[main @ v1.0]".
+    if branch.is_some() || pin.is_some() {
+        output.push_str(&format!(" {}", "[".blue()));
+        if let Some(branch) = &branch {
+            output.push_str(&branch.green().bold().to_string());
+        }
+        if let Some(pin) = pin {
+            if branch.is_some() {
+                output.push(' ');
             }
+            output.push_str(&format!("{} {}", "@".blue(), pin.name().yellow()));
         }
-        format!("{} {}", "#".cyan(), tree.get_name().blue().bold())
+        output.push_str(&"]".blue().to_string());
+        // Checking for drift runs git so it is only done in verbose mode.
+        if verbose > 0 && pin.is_some_and(|pin| is_pin_drifted(tree, pin)) {
+            output.push_str(&format!(" {}", "(drifted)".red()));
+        }
+    }
+    if verbose > 0 {
+        output.push_str(&format!(" {}", path_str.blue()));
     }
+
+    output
 }
 
 /// Print a tree if it exists, otherwise print a missing tree
-pub(crate) fn print_tree(tree: &model::Tree, options: &DisplayOptions) -> bool {
+pub(crate) fn print_tree(
+    tree: &model::Tree,
+    pin: Option<&model::TreePin>,
+    options: &DisplayOptions,
+) -> bool {
     if let Ok(path) = tree.path_as_ref() {
         // Sparse gardens/missing trees are expected. Skip these entries.
         if !std::path::PathBuf::from(&path).exists() {
@@ -102,7 +97,7 @@ pub(crate) fn print_tree(tree: &model::Tree, options: &DisplayOptions) -> bool {
             return false;
         }
 
-        print_tree_details(tree, options.branches, options.verbose, options.quiet);
+        print_tree_details(tree, options.branches, pin, options.verbose, options.quiet);
         return true;
     }
     if !options.quiet {
@@ -119,6 +114,7 @@ pub(crate) fn print_tree(tree: &model::Tree, options: &DisplayOptions) -> bool {
 pub(crate) fn print_tree_details(
     tree: &model::Tree,
     tree_branches: bool,
+    pin: Option<&model::TreePin>,
     verbose: u8,
     quiet: bool,
 ) {
@@ -126,7 +122,7 @@ pub(crate) fn print_tree_details(
         return;
     }
     if let Ok(path) = tree.path_as_ref() {
-        eprintln!("{}", display_tree(tree, path, tree_branches, verbose));
+        eprintln!("{}", display_tree(tree, path, tree_branches, pin, verbose));
     }
 }
 
@@ -170,7 +166,18 @@ pub(crate) fn print_symlink_tree_entry(tree: &model::Tree, path: &str, verbose:
     }
 }
 
-/// Print the description, url, remotes and links for a tree
+/// Return true when the commit that is checked out in a tree differs from its pin.
+fn is_pin_drifted(tree: &model::Tree, pin: &model::TreePin) -> bool {
+    match tree.canonical_pathbuf() {
+        Some(path) if !tree.is_bare_repository && !tree.is_symlink => {
+            let pinned = git::commit_id(&path, &pin.revision());
+            pinned.is_none() || pinned != git::commit_id(&path, "HEAD")
+        }
+        _ => false,
+    }
+}
+
+/// Print the description, tags, url, remotes and links for a tree
 pub(crate) fn print_tree_extended_details(
     app_context: &model::ApplicationContext,
     context: &model::TreeContext,
@@ -184,6 +191,21 @@ pub(crate) fn print_tree_extended_details(
     if !tree.description.is_empty() {
         println!("{}", tree.description.green());
     }
+    if !tree.tags.is_empty() {
+        let tags = tree
+            .tags
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(" ");
+        println!("{} {}", "tags:".blue(), tags.yellow());
+    }
+    if display_options.verbose > 0 && !tree.get_locations().is_empty() {
+        println!("{}", "sources:".blue());
+        for location in tree.get_locations() {
+            println!("  {} {}", "-".blue(), location.to_string().yellow());
+        }
+    }
     if tree.is_worktree && !display_options.worktrees {
         return;
     }
@@ -243,19 +265,38 @@ pub(crate) fn print_commands(
 }
 
 /// Print groups
-pub(crate) fn print_groups(groups: &model::GroupMap) {
+pub(crate) fn print_groups(groups: &model::GroupMap, verbose: u8) {
     println!("{}", "groups:".blue());
-    for group in groups.keys() {
-        println!("  {} {}", "-".blue(), group.yellow());
+    for (name, group) in groups {
+        println!(
+            "  {} {}{}",
+            "-".blue(),
+            name.yellow(),
+            display_locations(group.get_locations(), verbose)
+        );
     }
 }
 
 /// Print gardens
-pub(crate) fn print_gardens(gardens: &model::GardenMap) {
+pub(crate) fn print_gardens(gardens: &model::GardenMap, verbose: u8) {
     println!("{}", "gardens:".blue());
-    for garden in gardens.keys() {
-        println!("  {} {}", "-".blue(), garden.yellow());
+    for (name, garden) in gardens {
+        println!(
+            "  {} {}{}",
+            "-".blue(),
+            name.yellow(),
+            display_locations(garden.get_locations(), verbose)
+        );
+    }
+}
+
+/// Display " (path:line, ...)" locations in verbose mode.
+fn display_locations(locations: &[model::SourceLocation], verbose: u8) -> String {
+    if verbose == 0 || locations.is_empty() {
+        return String::new();
     }
+    let locations: Vec<String> = locations.iter().map(ToString::to_string).collect();
+    format!(" {}", format!("({})", locations.join(", ")).dim())
 }
 
 /// Print a command argument list
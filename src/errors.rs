@@ -40,6 +40,9 @@ pub enum GardenError {
     #[error("{0}")]
     IOError(String),
 
+    #[error("include file not found: {path:?}")]
+    IncludeNotFound { path: std::path::PathBuf },
+
     #[error("invalid configuration: {msg}")]
     InvalidConfiguration { msg: String },
 
@@ -73,6 +76,9 @@ pub enum GardenError {
     #[error("invalid arguments: {0}")]
     Usage(String),
 
+    #[error("variable cycle detected: {cycle}")]
+    VariableCycle { cycle: String },
+
     #[error("error creating {tree:?}: 'git checkout' returned exit status {status:?}")]
     WorktreeGitCheckoutError { tree: String, status: u32 },
 
@@ -125,6 +131,7 @@ impl std::convert::From<GardenError> for u32 {
             GardenError::GardenNotFound { .. } => EX_USAGE,
             GardenError::GardenPatternError { .. } => EX_DATAERR,
             GardenError::IOError(_) => EX_IOERR,
+            GardenError::IncludeNotFound { .. } => EX_CONFIG,
             GardenError::InvalidConfiguration { .. } => EX_CONFIG,
             GardenError::InvalidGardenArgument { .. } => EX_USAGE,
             GardenError::OSError(_) => EX_OSERR,
@@ -133,6 +140,7 @@ impl std::convert::From<GardenError> for u32 {
             GardenError::SyncConfigurationError { .. } => EX_IOERR,
             GardenError::TreeNotFound { .. } => EX_USAGE,
             GardenError::Usage(_) => EX_USAGE,
+            GardenError::VariableCycle { .. } => EX_CONFIG,
             GardenError::WorktreeGitCheckoutError { .. } => EX_CANTCREAT,
             GardenError::WorktreeParentCreationError { .. } => EX_CANTCREAT,
             GardenError::WorktreeParentNotPlantedError { .. } => EX_CONFIG,
@@ -20,12 +20,14 @@ fn expand_tree_vars(
     }
     // Check for the variable in override scope defined by "garden -D name=value".
     if let Some(var) = config.override_variables.get(name) {
-        return Some(tree_variable(
+        return Some(scoped_tree_variable(
             app_context,
             config,
             graft_config,
             tree_name,
             garden_name,
+            name,
+            model::VariableScope::Override,
             var,
         ));
     }
@@ -78,12 +80,14 @@ fn expand_tree_vars(
             .and_then(|cfg| cfg.gardens.get(garden_name))
             .and_then(|garden| garden.variables.get(name))
         {
-            return Some(tree_variable(
+            return Some(scoped_tree_variable(
                 app_context,
                 config,
                 graft_config,
                 tree_name,
                 Some(garden_name),
+                name,
+                model::VariableScope::Graft,
                 var,
             ));
         }
@@ -94,12 +98,14 @@ fn expand_tree_vars(
             .get(garden_name)
             .and_then(|garden| garden.variables.get(name))
         {
-            return Some(tree_variable(
+            return Some(scoped_tree_variable(
                 app_context,
                 config,
                 graft_config,
                 tree_name,
                 Some(garden_name),
+                name,
+                model::VariableScope::Garden,
                 var,
             ));
         }
@@ -112,23 +118,27 @@ fn expand_tree_vars(
             .get(tree_name)
             .and_then(|tree| tree.variables.get(name))
         {
-            return Some(tree_variable(
+            return Some(scoped_tree_variable(
                 app_context,
                 config,
                 graft_config,
                 tree_name,
                 garden_name,
+                name,
+                model::VariableScope::Graft,
                 var,
             ));
         }
         // Nothing was found. Check for the variable in global/config scope.
         if let Some(var) = graft_cfg.variables.get(name) {
-            return Some(tree_variable(
+            return Some(scoped_tree_variable(
                 app_context,
                 config,
                 graft_config,
                 tree_name,
                 garden_name,
+                name,
+                model::VariableScope::Graft,
                 var,
             ));
         }
@@ -140,12 +150,14 @@ fn expand_tree_vars(
         .get(tree_name)
         .and_then(|tree| tree.variables.get(name))
     {
-        return Some(tree_variable(
+        return Some(scoped_tree_variable(
             app_context,
             config,
             graft_config,
             tree_name,
             garden_name,
+            name,
+            model::VariableScope::Tree,
             var,
         ));
     }
@@ -155,12 +167,14 @@ fn expand_tree_vars(
 
     // Nothing was found. Check for the variable in global/config scope.
     if let Some(var) = config.variables.get(name) {
-        return Some(tree_variable(
+        return Some(scoped_tree_variable(
             app_context,
             config,
             graft_config,
             tree_name,
             garden_name,
+            name,
+            global_scope(config),
             var,
         ));
     }
@@ -172,7 +186,10 @@ fn expand_tree_vars(
         garden_name.cloned(),
         None,
     );
-    if let Some(environ) = environment_value(app_context, config, graft_config, &context, name) {
+    app_context.push_variable(name, model::VariableScope::Environment, &[]);
+    let environ = environment_value(app_context, config, graft_config, &context, name);
+    app_context.pop_variable();
+    if let Some(environ) = environ {
         return Some(environ);
     }
 
@@ -197,7 +214,13 @@ fn expand_vars(
     }
     // Check for the variable in override scope defined by "garden -D name=value".
     if let Some(var) = config.override_variables.get(name) {
-        return Some(variable(app_context, config, var));
+        return Some(scoped_variable(
+            app_context,
+            config,
+            name,
+            model::VariableScope::Override,
+            var,
+        ));
     }
 
     if syntax::is_graft(name) {
@@ -210,7 +233,13 @@ fn expand_vars(
 
     // Check for the variable in the current configuration's global scope.
     if let Some(var) = config.variables.get(name) {
-        return Some(variable(app_context, config, var));
+        return Some(scoped_variable(
+            app_context,
+            config,
+            name,
+            global_scope(config),
+            var,
+        ));
     }
 
     // Walk up the parent hierarchy to resolve variables defined by graft parents.
@@ -406,7 +435,7 @@ pub(crate) fn variables_for_shell(
             context.garden.as_ref(),
         );
         let value = get_value_from_environment(var, raw_value);
-        var.set_value(value.to_string());
+        set_variable_value(app_context, var, value.to_string());
 
         result.push(value);
     }
@@ -924,6 +953,7 @@ pub(crate) fn tree_variable(
         return var_value.to_string();
     }
     if var.is_evaluating() {
+        app_context.add_variable_cycle();
         return String::new();
     }
     var.set_evaluation_started();
@@ -937,7 +967,34 @@ pub(crate) fn tree_variable(
         garden_name,
     );
     let value = get_value_from_environment(var, raw_value);
-    var.set_value(value.to_string());
+    set_variable_value(app_context, var, value.to_string());
+
+    value
+}
+
+/// Evaluate a variable that was found in the specified scope.
+/// The variable is recorded in the evaluation chain so that cycles can be reported.
+#[allow(clippy::too_many_arguments)]
+fn scoped_tree_variable(
+    app_context: &model::ApplicationContext,
+    config: &model::Configuration,
+    graft_config: Option<&model::Configuration>,
+    tree_name: &str,
+    garden_name: Option<&model::GardenName>,
+    name: &str,
+    scope: model::VariableScope,
+    var: &model::Variable,
+) -> String {
+    app_context.push_variable(name, scope, var.get_locations());
+    let value = tree_variable(
+        app_context,
+        config,
+        graft_config,
+        tree_name,
+        garden_name,
+        var,
+    );
+    app_context.pop_variable();
 
     value
 }
@@ -952,17 +1009,56 @@ pub(crate) fn variable(
         return var_value.to_string();
     }
     if var.is_evaluating() {
+        app_context.add_variable_cycle();
         return String::new();
     }
     var.set_evaluation_started();
     let expr = var.get_expr();
     let raw_value = value(app_context, config, expr);
     let value = get_value_from_environment(var, raw_value);
-    var.set_value(value.to_string());
+    set_variable_value(app_context, var, value.to_string());
+
+    value
+}
+
+/// Evaluate a global variable that was found in the specified scope.
+/// The variable is recorded in the evaluation chain so that cycles can be reported.
+fn scoped_variable(
+    app_context: &model::ApplicationContext,
+    config: &model::Configuration,
+    name: &str,
+    scope: model::VariableScope,
+    var: &model::Variable,
+) -> String {
+    app_context.push_variable(name, scope, var.get_locations());
+    let value = variable(app_context, config, var);
+    app_context.pop_variable();
 
     value
 }
 
+/// Return the scope for variables defined in the top-level "variables" block.
+fn global_scope(config: &model::Configuration) -> model::VariableScope {
+    if config.parent_id.is_some() {
+        model::VariableScope::Graft
+    } else {
+        model::VariableScope::Global
+    }
+}
+
+/// Store the evaluated value for a variable.
+fn set_variable_value(
+    app_context: &model::ApplicationContext,
+    var: &model::Variable,
+    value: String,
+) {
+    if app_context.is_checking_cycles() {
+        var.set_unchecked_value(value);
+    } else {
+        var.set_value(value);
+    }
+}
+
 /// Fallback to environment variables when required variables are empty.
 fn get_value_from_environment(variable: &model::Variable, value: String) -> String {
     if variable.is_required() && value.is_empty() {
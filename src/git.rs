@@ -104,6 +104,147 @@ pub(crate) fn branch(path: &std::path::Path) -> Option<String> {
     None
 }
 
+/// Return the checked-out branch name. Returns None for detached heads.
+pub(crate) fn checked_out_branch(path: &std::path::Path) -> Option<String> {
+    let cmd = ["git", "symbolic-ref", "--quiet", "--short", "HEAD"];
+    let exec = cmd::exec_in_dir(&cmd, &path);
+    let output = cmd::stdout_to_string(exec).ok()?;
+    if output.is_empty() {
+        return None;
+    }
+
+    Some(output)
+}
+
+/// Return the full commit ID for a revision. Returns None when the revision does not exist.
+pub(crate) fn commit_id(path: &std::path::Path, revision: &str) -> Option<String> {
+    let revision = format!("{revision}^{{commit}}");
+    let cmd = ["git", "rev-parse", "--verify", "--quiet", revision.as_str()];
+    let exec = cmd::exec_in_dir(&cmd, &path);
+    let output = cmd::stdout_to_string(exec).ok()?;
+    if output.is_empty() {
+        return None;
+    }
+
+    Some(output)
+}
+
+/// Abbreviate a commit ID for display.
+pub(crate) fn short_commit_id(commit: &str) -> &str {
+    commit.get(..12).unwrap_or(commit)
+}
+
+/// Return the upstream branch configured for the current branch, e.g. "origin/main".
+pub(crate) fn upstream_branch(path: &std::path::Path) -> Option<String> {
+    let cmd = [
+        "git",
+        "rev-parse",
+        "--abbrev-ref",
+        "--symbolic-full-name",
+        "@{upstream}",
+    ];
+    let exec = cmd::exec_in_dir(&cmd, &path);
+    let output = cmd::stdout_to_string(exec).ok()?;
+    if output.is_empty() {
+        return None;
+    }
+
+    Some(output)
+}
+
+/// Return true when the repository has uncommitted changes or untracked files.
+/// Returns None when the status cannot be determined.
+pub(crate) fn is_dirty(path: &std::path::Path) -> Option<bool> {
+    let cmd = ["git", "status", "--porcelain"];
+    let exec = cmd::exec_in_dir(&cmd, &path);
+    let output = cmd::stdout_to_string(exec).ok()?;
+
+    Some(!output.is_empty())
+}
+
+/// Return the number of commits that the current branch is (ahead, behind) the upstream
+/// revision, e.g. "@{upstream}". Returns None when the upstream revision does not exist.
+pub(crate) fn ahead_behind(path: &std::path::Path, upstream: &str) -> Option<(usize, usize)> {
+    let range = format!("HEAD...{upstream}");
+    let cmd = ["git", "rev-list", "--left-right", "--count", range.as_str()];
+    let exec = cmd::exec_in_dir(&cmd, &path);
+    let output = cmd::stdout_to_string(exec).ok()?;
+    let (ahead, behind) = output.split_once('\t')?;
+
+    Some((ahead.trim().parse().ok()?, behind.trim().parse().ok()?))
+}
+
+/// Return the branch, upstream, ahead/behind, change and stash counts for a repository.
+/// Returns None when the status cannot be determined, e.g. for bare repositories.
+pub(crate) fn status(path: &std::path::Path) -> Option<model::GitStatus> {
+    let cmd = ["git", "status", "--porcelain=v2", "--branch"];
+    let exec = cmd::exec_in_dir(&cmd, &path);
+    let output = cmd::stdout_to_string(exec).ok()?;
+    let mut status = parse_status(&output);
+    if status.branch.is_empty() {
+        status.branch = branch(path).unwrap_or_default();
+    }
+    let cmd = ["git", "stash", "list"];
+    let exec = cmd::exec_in_dir(&cmd, &path);
+    if let Ok(output) = cmd::stdout_to_string(exec) {
+        status.stashes = output.lines().filter(|x| !x.is_empty()).count();
+    }
+
+    Some(status)
+}
+
+/// Parse "git status --porcelain=v2 --branch" output.
+pub(crate) fn parse_status(output: &str) -> model::GitStatus {
+    let mut status = model::GitStatus::default();
+    for line in output.lines() {
+        if let Some(head) = line.strip_prefix("# branch.head ") {
+            if head != "(detached)" {
+                status.branch = head.to_string();
+            }
+        } else if let Some(upstream) = line.strip_prefix("# branch.upstream ") {
+            status.upstream = upstream.to_string();
+        } else if let Some(ahead_behind) = line.strip_prefix("# branch.ab ") {
+            for value in ahead_behind.split_whitespace() {
+                if let Some(ahead) = value.strip_prefix('+') {
+                    status.ahead = ahead.parse().unwrap_or_default();
+                } else if let Some(behind) = value.strip_prefix('-') {
+                    status.behind = behind.parse().unwrap_or_default();
+                }
+            }
+        } else if line.starts_with("? ") {
+            status.untracked += 1;
+        } else if line.starts_with("u ") {
+            // Unmerged entries have unresolved changes in the worktree.
+            status.unstaged += 1;
+        } else if line.starts_with("1 ") || line.starts_with("2 ") {
+            // "1 XY ..." and "2 XY ..." entries: X is the index state and Y is the worktree state.
+            let mut states = line[2..].chars();
+            if states.next().is_some_and(|x| x != '.') {
+                status.staged += 1;
+            }
+            if states.next().is_some_and(|y| y != '.') {
+                status.unstaged += 1;
+            }
+        }
+    }
+
+    status
+}
+
+/// Return the names of the remotes configured in a repository.
+pub(crate) fn remotes(path: &std::path::Path) -> Vec<String> {
+    let cmd = ["git", "remote"];
+    let exec = cmd::exec_in_dir(&cmd, &path);
+    match cmd::stdout_to_string(exec) {
+        Ok(output) => output
+            .lines()
+            .filter(|x| !x.is_empty())
+            .map(|x| x.to_string())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
 /// Return the root of the current repository when inside a Git repository.
 pub(crate) fn current_worktree_path(
     path: &std::path::Path,
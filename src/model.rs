@@ -1,5 +1,6 @@
-use std::cell::{Cell, UnsafeCell};
+use std::cell::{Cell, RefCell, UnsafeCell};
 use std::str::FromStr;
+use std::sync::{atomic, Mutex};
 
 use better_default::Default;
 use indextree::{Arena, NodeId};
@@ -33,6 +34,51 @@ pub type ConfigId = NodeId;
 /// Environment variables are set when running commands.
 pub(crate) type Environment = Vec<(String, String)>;
 
+/// The garden file and line where a configuration entity was defined.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct SourceLocation {
+    /// The garden file. Configuration read from a string has no path.
+    pub path: Option<std::path::PathBuf>,
+    /// The 1-based line number within the file.
+    pub line: usize,
+}
+
+impl SourceLocation {
+    pub(crate) fn new(path: Option<std::path::PathBuf>, line: usize) -> Self {
+        SourceLocation { path, line }
+    }
+}
+
+/// Display locations as "path:line" with paths relative to the current directory.
+impl std::fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{}:{}", path::relative_path(path).display(), self.line),
+            None => write!(f, "line {}", self.line),
+        }
+    }
+}
+
+/// Record a location as the most recent definition.
+/// Locations that were already recorded are moved to the end.
+pub(crate) fn add_location(locations: &mut Vec<SourceLocation>, location: SourceLocation) {
+    locations.retain(|existing| existing != &location);
+    locations.push(location);
+}
+
+/// Format a message with a "path:line: " prefix when the location is known.
+pub(crate) fn format_with_location(location: Option<&SourceLocation>, message: &str) -> String {
+    match location {
+        Some(location) => format!("{location}: {message}"),
+        None => message.to_string(),
+    }
+}
+
 /// Config files can define a sequence of variables that are
 /// iteratively calculated.  Variables can reference other
 /// variables in their Tree, Garden, and Configuration scopes.
@@ -48,12 +94,18 @@ pub(crate) type Environment = Vec<(String, String)>;
 /// (dollar-sign followed by space) before the value.  For example,
 /// using "$ echo foo" will place the value "foo" in the variable.
 #[derive(Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "VariableData", into = "VariableData")
+)]
 pub struct Variable {
     name: String,
     expr: String,
     value: UnsafeCell<Option<String>>,
     evaluating: Cell<bool>,
     required: bool,
+    locations: Vec<SourceLocation>,
 }
 
 impl_display_brief!(Variable);
@@ -68,6 +120,7 @@ impl Clone for Variable {
             value: UnsafeCell::new(self.get_value().cloned()),
             evaluating: Cell::new(false),
             required: self.required,
+            locations: self.locations.clone(),
         }
     }
 }
@@ -81,6 +134,7 @@ impl Variable {
             value: UnsafeCell::new(None),
             evaluating: Cell::new(false),
             required: false,
+            locations: Vec::new(),
         }
     }
 
@@ -92,6 +146,7 @@ impl Variable {
             value: UnsafeCell::new(Some(expr)),
             evaluating: Cell::new(false),
             required: false,
+            locations: Vec::new(),
         }
     }
 
@@ -103,6 +158,7 @@ impl Variable {
             required,
             value: UnsafeCell::new(None),
             evaluating: Cell::new(false),
+            locations: Vec::new(),
         }
     }
 
@@ -161,22 +217,68 @@ impl Variable {
     pub(crate) fn set_value(&self, value: String) {
         // Report an error and exit when a required variable has no value.
         if !self.required || !value.is_empty() {
-            self.set_evaluating(false);
-            unsafe {
-                *self.value.get() = Some(value);
-            }
+            self.set_unchecked_value(value);
         } else if self.required {
             eprintln!("error: required variable '{}' is empty", self.name);
             std::process::exit(errors::EX_DATAERR as i32);
         }
     }
 
+    /// Store the cached result without enforcing required variables.
+    pub(crate) fn set_unchecked_value(&self, value: String) {
+        self.set_evaluating(false);
+        unsafe {
+            *self.value.get() = Some(value);
+        }
+    }
+
     /// Reset the variable.
     pub(crate) fn reset(&self) {
         unsafe {
             *self.value.get() = None;
         }
     }
+
+    /// Return the locations where this variable was defined.
+    /// Locations are ordered from the first definition to the most recent definition.
+    pub fn get_locations(&self) -> &[SourceLocation] {
+        &self.locations
+    }
+
+    /// Return the location of the most recent definition, which provides the
+    /// variable's expression.
+    pub fn get_location(&self) -> Option<&SourceLocation> {
+        self.locations.last()
+    }
+
+    /// Record the location where this variable was defined.
+    pub(crate) fn add_location(&mut self, location: SourceLocation) {
+        add_location(&mut self.locations, location);
+    }
+
+    /// Record the locations of a variable that is being overridden by this variable.
+    pub(crate) fn inherit_locations(&mut self, previous: &Variable) {
+        let mut locations = previous.locations.clone();
+        for location in self.locations.drain(..) {
+            add_location(&mut locations, location);
+        }
+        self.locations = locations;
+    }
+}
+
+/// Insert a variable into a map while retaining the locations of the variable that it replaces.
+pub(crate) fn insert_variable(map: &mut VariableMap, name: String, mut variable: Variable) {
+    if let Some(previous) = map.get(&name) {
+        variable.inherit_locations(previous);
+    }
+    map.insert(name, variable);
+}
+
+/// Update a VariableMap "a" with the variables from "b" while retaining locations.
+pub(crate) fn append_variables(a: &mut VariableMap, b: &VariableMap) {
+    for (name, variable) in b {
+        insert_variable(a, name.clone(), variable.clone());
+    }
 }
 
 /// An unordered mapping of names to a vector of Variables.
@@ -191,19 +293,76 @@ fn reset_map_variables(vec_variables: &MultiVariableMap) {
     }
 }
 
+/// Variables are serialized using their expression and their evaluated value.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct VariableData {
+    name: String,
+    expr: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    required: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    locations: Vec<SourceLocation>,
+}
+
+#[cfg(feature = "serde")]
+impl From<Variable> for VariableData {
+    fn from(var: Variable) -> Self {
+        VariableData {
+            value: var.get_value().cloned(),
+            name: var.name,
+            expr: var.expr,
+            required: var.required,
+            locations: var.locations,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<VariableData> for Variable {
+    fn from(data: VariableData) -> Self {
+        Variable {
+            name: data.name,
+            expr: data.expr,
+            value: UnsafeCell::new(data.value),
+            evaluating: Cell::new(false),
+            required: data.required,
+            locations: data.locations,
+        }
+    }
+}
+
 /// An unordered mapping of name to Variable.
 pub(crate) type VariableMap = IndexMap<String, Variable>;
 
 // Named variables with multiple values
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MultiVariable {
     name: String,
     variables: Vec<Variable>,
+    location: Option<SourceLocation>,
 }
 
 impl MultiVariable {
     pub(crate) fn new(name: String, variables: Vec<Variable>) -> Self {
-        MultiVariable { name, variables }
+        MultiVariable {
+            name,
+            variables,
+            location: None,
+        }
+    }
+
+    /// Return the location where this entry was defined.
+    pub fn get_location(&self) -> Option<&SourceLocation> {
+        self.location.as_ref()
+    }
+
+    /// Record the location where this entry was defined.
+    pub(crate) fn set_location(&mut self, location: Option<SourceLocation>) {
+        self.location = location;
     }
 
     pub fn get(&self, idx: usize) -> &Variable {
@@ -233,28 +392,92 @@ impl MultiVariable {
     }
 }
 
+/// How submodules are initialized by "garden grow".
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, strum_macros::EnumString, strum_macros::Display,
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[strum(ascii_case_insensitive, serialize_all = "kebab-case")]
+pub enum SubmoduleMode {
+    /// Submodules are not initialized.
+    #[default]
+    #[strum(serialize = "false")]
+    None,
+    /// Submodules are initialized using "git submodule update --init".
+    #[strum(serialize = "true")]
+    Init,
+    /// Nested submodules are initialized using "git submodule update --init --recursive".
+    Recursive,
+}
+
+/// The commit or tag that a tree is pinned to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum TreePin {
+    Commit(String),
+    Tag(String),
+}
+
+impl TreePin {
+    /// Return the commit ID or tag name.
+    pub(crate) fn name(&self) -> &str {
+        match self {
+            TreePin::Commit(commit) => commit,
+            TreePin::Tag(tag) => tag,
+        }
+    }
+
+    /// Return the revision that is checked out for the pin.
+    pub(crate) fn revision(&self) -> String {
+        match self {
+            TreePin::Commit(commit) => commit.to_string(),
+            TreePin::Tag(tag) => format!("refs/tags/{tag}"),
+        }
+    }
+}
+
+impl std::fmt::Display for TreePin {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TreePin::Commit(commit) => write!(formatter, "{} {}", constants::COMMIT, commit),
+            TreePin::Tag(tag) => write!(formatter, "{} {}", constants::TAG, tag),
+        }
+    }
+}
+
 /// Trees represent a single worktree
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct Tree {
     pub commands: MultiVariableMap,
     pub environment: Vec<MultiVariable>,
     pub gitconfig: MultiVariableMap,
     pub remotes: VariableMap,
     pub(crate) symlink: Variable,
+    pub tags: StringSet,
     pub templates: StringSet,
     pub variables: VariableMap,
     pub branch: Variable,
     pub(crate) branches: VariableMap,
+    pub(crate) commit: Variable,
+    pub(crate) tag: Variable,
     pub worktree: Variable,
     #[default(string!("origin"))]
     pub(crate) default_remote: String,
     pub(crate) clone_depth: i64,
+    pub(crate) clone_filter: String,
     pub(crate) is_single_branch: bool,
+    pub(crate) sparse_checkout: StringSet,
+    pub(crate) submodules: Option<SubmoduleMode>,
     pub is_symlink: bool,
     pub is_bare_repository: bool,
     pub is_worktree: bool,
     pub(crate) description: String,
     pub(crate) links: Vec<Variable>,
+    pub(crate) locations: Vec<SourceLocation>,
 
     name: String,
     path: Variable,
@@ -282,6 +505,17 @@ impl Tree {
         &mut self.path
     }
 
+    /// Return the locations where this tree was defined and overridden.
+    /// Locations are ordered from the first definition to the most recent definition.
+    pub fn get_locations(&self) -> &[SourceLocation] {
+        &self.locations
+    }
+
+    /// Return the location of the most recent definition of this tree.
+    pub fn get_location(&self) -> Option<&SourceLocation> {
+        self.locations.last()
+    }
+
     pub(crate) fn path_is_valid(&self) -> bool {
         self.path.get_value().is_some()
     }
@@ -305,26 +539,36 @@ impl Tree {
         self.path.get_value().map(std::path::PathBuf::from)
     }
 
+    /// Return the modification time of the tree's directory.
+    pub(crate) fn modified(&self) -> Option<std::time::SystemTime> {
+        self.pathbuf()
+            .and_then(|pathbuf| pathbuf.metadata().ok())
+            .and_then(|metadata| metadata.modified().ok())
+    }
+
     pub fn path_as_ref(&self) -> Result<&String, errors::GardenError> {
         match self.path.get_value() {
             Some(value) => Ok(value),
-            None => Err(errors::GardenError::ConfigurationError(format!(
-                "unset tree path for {}",
-                self.name
-            ))),
+            None => Err(errors::GardenError::ConfigurationError(
+                self.format_with_location(&format!("unset tree path for {}", self.name)),
+            )),
         }
     }
 
     pub(crate) fn symlink_as_ref(&self) -> Result<&String, errors::GardenError> {
         match self.symlink.get_value() {
             Some(value) => Ok(value),
-            None => Err(errors::GardenError::ConfigurationError(format!(
-                "unset tree path for {}",
-                self.name
-            ))),
+            None => Err(errors::GardenError::ConfigurationError(
+                self.format_with_location(&format!("unset tree path for {}", self.name)),
+            )),
         }
     }
 
+    /// Prefix a message with the location where this tree was defined.
+    pub(crate) fn format_with_location(&self, message: &str) -> String {
+        format_with_location(self.get_location(), message)
+    }
+
     /// Add the builtin TREE_NAME and TREE_PATH variables.
     pub(crate) fn add_builtin_variables(&mut self) {
         self.variables.insert(
@@ -361,9 +605,11 @@ impl Tree {
     pub(crate) fn clone_from_tree(&mut self, tree: &Tree) {
         collections::append_map(&mut self.commands, &tree.commands);
         collections::append_map(&mut self.gitconfig, &tree.gitconfig);
-        collections::append_map(&mut self.variables, &tree.variables);
-        collections::append_map(&mut self.remotes, &tree.remotes);
+        append_variables(&mut self.variables, &tree.variables);
+        append_variables(&mut self.remotes, &tree.remotes);
+        collections::append_set(&mut self.tags, &tree.tags);
         collections::append_set(&mut self.templates, &tree.templates);
+        collections::append_set(&mut self.sparse_checkout, &tree.sparse_checkout);
 
         // "environment" follow last-set-wins semantics.
         self.environment.append(&mut tree.environment.clone());
@@ -374,9 +620,15 @@ impl Tree {
         if tree.is_bare_repository {
             self.is_bare_repository = tree.is_bare_repository;
         }
+        if !tree.clone_filter.is_empty() {
+            self.clone_filter = tree.clone_filter.to_string();
+        }
         if tree.is_single_branch {
             self.is_single_branch = tree.is_single_branch;
         }
+        if tree.submodules.is_some() {
+            self.submodules = tree.submodules;
+        }
         if tree.is_worktree {
             self.is_worktree = tree.is_worktree;
         }
@@ -386,6 +638,12 @@ impl Tree {
         if !tree.branch.is_empty() {
             self.branch = tree.branch.clone();
         }
+        if !tree.commit.is_empty() {
+            self.commit = tree.commit.clone();
+        }
+        if !tree.tag.is_empty() {
+            self.tag = tree.tag.clone();
+        }
         if !tree.symlink.is_empty() {
             self.symlink = tree.symlink.clone();
         }
@@ -440,6 +698,46 @@ impl Tree {
         )
     }
 
+    /// Return the resolved "commit" or "tag" pin.
+    pub(crate) fn eval_pin(&self, eval_context: &EvalContext) -> Option<TreePin> {
+        self.get_pin(
+            eval_context.app_context,
+            eval_context.config,
+            eval_context.graft_config,
+            eval_context.tree_context,
+        )
+    }
+
+    /// Return the resolved "commit" or "tag" pin. Commits take precedence over tags.
+    pub(crate) fn get_pin(
+        &self,
+        app_context: &ApplicationContext,
+        config: &Configuration,
+        graft_config: Option<&Configuration>,
+        tree_context: &TreeContext,
+    ) -> Option<TreePin> {
+        let tree_variable = |var: &Variable| {
+            eval::tree_variable(
+                app_context,
+                config,
+                graft_config,
+                &tree_context.tree,
+                tree_context.garden.as_ref(),
+                var,
+            )
+        };
+        let commit = tree_variable(&self.commit);
+        if !commit.is_empty() {
+            return Some(TreePin::Commit(commit));
+        }
+        let tag = tree_variable(&self.tag);
+        if !tag.is_empty() {
+            return Some(TreePin::Tag(tag));
+        }
+
+        None
+    }
+
     // Return the resolved "url" field for the default remote.
     pub(crate) fn eval_url(&self, eval_context: &EvalContext) -> Option<String> {
         self.get_url(
@@ -529,9 +827,15 @@ impl Tree {
 }
 
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct Group {
     name: String,
     pub members: StringSet,
+    pub(crate) locations: Vec<SourceLocation>,
 }
 
 impl Group {
@@ -547,6 +851,11 @@ impl Group {
     pub(crate) fn get_name_mut(&mut self) -> &mut String {
         &mut self.name
     }
+
+    /// Return the locations where this group was defined and redefined.
+    pub fn get_locations(&self) -> &[SourceLocation] {
+        &self.locations
+    }
 }
 
 /// Groups are stored in a GroupMap inside Configuration.
@@ -556,9 +865,17 @@ pub type GroupMap = IndexMap<GroupName, Group>;
 /// They contain a (path-less) tree object which can be used for creating
 /// materialized trees.
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct Template {
     pub tree: Tree,
     pub extend: StringSet,
+    /// Parameters and their default values. Parameters without a default value are
+    /// required parameters and must be provided by trees that use the template.
+    pub parameters: VariableMap,
     name: String,
 }
 
@@ -575,10 +892,41 @@ impl Template {
     pub(crate) fn apply(&self, tree: &mut Tree) {
         tree.clone_from_tree(&self.tree);
     }
+
+    /// Apply this template onto the specified tree using the specified parameter values.
+    /// Parameters are provided to the tree as variables. Values from "args" override
+    /// the parameter defaults and the template's variables.
+    pub(crate) fn apply_with_args(&self, tree: &mut Tree, args: &VariableMap) {
+        for (name, parameter) in &self.parameters {
+            if !is_required_parameter(parameter) {
+                insert_variable(&mut tree.variables, name.clone(), parameter.clone());
+            }
+        }
+        self.apply(tree);
+        append_variables(&mut tree.variables, args);
+    }
+
+    /// Return the name of the first required parameter that is missing from "args".
+    pub(crate) fn missing_parameter(&self, args: &VariableMap) -> Option<&String> {
+        self.parameters
+            .iter()
+            .find(|(name, parameter)| is_required_parameter(parameter) && !args.contains_key(*name))
+            .map(|(name, _)| name)
+    }
+}
+
+/// Return true for template parameters that do not have a default value.
+fn is_required_parameter(parameter: &Variable) -> bool {
+    parameter.is_required() && parameter.get_expr().is_empty()
 }
 
 // Gardens aggregate trees
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct Garden {
     pub commands: MultiVariableMap,
     pub environment: Vec<MultiVariable>,
@@ -586,6 +934,7 @@ pub struct Garden {
     pub groups: StringSet,
     pub trees: StringSet,
     pub variables: VariableMap,
+    pub(crate) locations: Vec<SourceLocation>,
     name: GardenName,
 }
 
@@ -597,6 +946,11 @@ impl Garden {
     pub(crate) fn get_name_mut(&mut self) -> &mut String {
         &mut self.name
     }
+
+    /// Return the locations where this garden was defined and redefined.
+    pub fn get_locations(&self) -> &[SourceLocation] {
+        &self.locations
+    }
 }
 
 /// Gardens are stored in a GardenMap inside Configuration.
@@ -618,6 +972,11 @@ fn get_default_shell() -> String {
 
 /// Configuration represents an instantiated garden configuration
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct Configuration {
     pub commands: MultiVariableMap,
     pub debug: IndexMap<String, u8>,
@@ -1067,21 +1426,102 @@ impl Configuration {
         self.config_path(&value)
     }
 
-    /// Evaluate and resolve a pathbuf relative to the config directory for "includes".
-    pub(crate) fn eval_config_pathbuf_from_include(
+    /// Return true when the condition for an include entry evaluates to a truthy value.
+    /// Empty values, "false" and "0" are false. Includes without a condition are always included.
+    pub(crate) fn eval_include_condition(
+        &self,
+        app_context: &ApplicationContext,
+        include: &Include,
+    ) -> bool {
+        let Some(condition) = include.condition.as_ref() else {
+            return true;
+        };
+        let value = eval::value(app_context, self, condition);
+        let value = value.trim();
+
+        !value.is_empty() && syntax::string_to_bool(value) != Some(false)
+    }
+
+    /// Evaluate and resolve the pathbufs for an include entry.
+    /// Glob patterns expand to the matching files in sorted order. Patterns are
+    /// matched relative to the include file's directory first and then relative to
+    /// the config directory. A single unresolved path is returned when nothing matches
+    /// so that callers can report the missing file.
+    pub(crate) fn eval_include_pathbufs(
         &self,
         app_context: &ApplicationContext,
         include_path: Option<&std::path::Path>,
-        path: &str,
-    ) -> Option<std::path::PathBuf> {
-        let value = eval::value(app_context, self, path);
+        include: &Include,
+    ) -> Vec<std::path::PathBuf> {
+        let value = eval::value(app_context, self, &include.path);
+        self.include_pathbufs(include_path, &value)
+    }
 
-        if let Some(include_path) = include_path {
-            self.config_pathbuf_from_include(include_path, &value)
+    /// Resolve the pathbufs for an evaluated include path.
+    /// See eval_include_pathbufs() for details.
+    pub(crate) fn include_pathbufs(
+        &self,
+        include_path: Option<&std::path::Path>,
+        value: &str,
+    ) -> Vec<std::path::PathBuf> {
+        if glob::Pattern::escape(value) == value {
+            return include_path
+                .and_then(|include_path| self.config_pathbuf_from_include(include_path, value))
+                .or_else(|| self.config_pathbuf(value))
+                .or_else(|| Some(std::path::PathBuf::from(value)))
+                .into_iter()
+                .collect();
+        }
+
+        let mut patterns = Vec::new();
+        if std::path::Path::new(value).is_absolute() {
+            patterns.push(value.to_string());
         } else {
-            self.config_pathbuf(&value)
+            let include_dirname = include_path.and_then(|path| path.parent());
+            for dirname in include_dirname.into_iter().chain(self.dirname.as_deref()) {
+                let dirname = glob::Pattern::escape(&dirname.to_string_lossy());
+                patterns.push(format!("{dirname}/{value}"));
+            }
+            if patterns.is_empty() {
+                patterns.push(value.to_string());
+            }
+        }
+        for pattern in &patterns {
+            let Ok(paths) = glob::glob(pattern) else {
+                continue;
+            };
+            let mut pathbufs: Vec<std::path::PathBuf> = paths
+                .filter_map(|path| path.ok())
+                .filter(|path| path.is_file())
+                .collect();
+            if !pathbufs.is_empty() {
+                pathbufs.sort();
+                return pathbufs;
+            }
         }
-        .or_else(|| Some(std::path::PathBuf::from(&value)))
+
+        patterns
+            .last()
+            .map(std::path::PathBuf::from)
+            .into_iter()
+            .collect()
+    }
+
+    /// Replace the parsed configuration with a configuration read from the cache.
+    /// Runtime state that is not read from garden files is retained.
+    #[cfg(feature = "cache")]
+    pub(crate) fn apply_cached(&mut self, cached: Configuration) {
+        let current = std::mem::replace(self, cached);
+        self.id = current.id;
+        self.parent_id = current.parent_id;
+        self.path = current.path;
+        self.dirname = current.dirname;
+        self.debug = current.debug;
+        self.override_variables = current.override_variables;
+        self.config_verbose = current.config_verbose;
+        self.quiet = current.quiet;
+        self.verbose = current.verbose;
+        self.update_quiet_and_verbose_variables(self.quiet, 0);
     }
 
     /// Reset resolved variables
@@ -1211,11 +1651,17 @@ fn set_bool(name: &str, expr: &str, output: &mut bool) {
 }
 
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct Graft {
     id: Option<ConfigId>,
     name: String,
     pub root: String,
     pub config: String,
+    location: Option<SourceLocation>,
 }
 
 impl Graft {
@@ -1225,9 +1671,20 @@ impl Graft {
             name,
             root,
             config,
+            location: None,
         }
     }
 
+    /// Return the location where this graft was defined.
+    pub fn get_location(&self) -> Option<&SourceLocation> {
+        self.location.as_ref()
+    }
+
+    /// Record the location where this graft was defined.
+    pub(crate) fn set_location(&mut self, location: Option<SourceLocation>) {
+        self.location = location;
+    }
+
     pub fn get_name(&self) -> &String {
         &self.name
     }
@@ -1241,6 +1698,28 @@ impl Graft {
     }
 }
 
+/// An entry in the "garden.includes" list.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Include {
+    /// The path to include. Glob patterns are expanded in sorted order.
+    pub(crate) path: String,
+    /// Missing files are an error when the include is not optional.
+    #[default(true)]
+    pub(crate) optional: bool,
+    /// The file is only included when this expression evaluates to a truthy value.
+    pub(crate) condition: Option<String>,
+}
+
+impl Include {
+    /// Create an optional include for the specified path.
+    pub(crate) fn new(path: String) -> Self {
+        Include {
+            path,
+            ..Default::default()
+        }
+    }
+}
+
 // TODO EvalContext
 #[derive(Clone, Debug)]
 pub(crate) struct EvalContext<'a> {
@@ -1304,6 +1783,7 @@ impl EvalContext<'_> {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TreeContext {
     pub tree: TreeName,
     pub config: Option<ConfigId>,
@@ -1336,6 +1816,7 @@ pub struct TreeQuery {
     pub is_garden: bool,
     pub is_group: bool,
     pub is_tree: bool,
+    pub is_tag: bool,
     pub include_gardens: bool,
     pub include_groups: bool,
     pub include_trees: bool,
@@ -1345,6 +1826,7 @@ impl TreeQuery {
     pub fn new(query: &str) -> Self {
         let mut is_default = false;
         let mut is_tree = false;
+        let mut is_tag = false;
         let mut is_garden = false;
         let mut is_group = false;
         let mut include_gardens = true;
@@ -1363,6 +1845,11 @@ impl TreeQuery {
             is_tree = true;
             include_gardens = false;
             include_groups = false;
+        } else if syntax::is_tag(query) {
+            is_tag = true;
+            include_gardens = false;
+            include_groups = false;
+            include_trees = false;
         } else {
             is_default = true;
         }
@@ -1375,6 +1862,7 @@ impl TreeQuery {
             is_garden,
             is_group,
             is_tree,
+            is_tag,
             include_gardens,
             include_groups,
             include_trees,
@@ -1383,6 +1871,19 @@ impl TreeQuery {
     }
 }
 
+/// Tree query expressions combine tree queries using set operations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TreeQueryExpr {
+    /// A garden, group, tree or path query, e.g. ":garden", "%group", "@tree" or "tree*".
+    Query(String),
+    /// Trees matched by either expression: "a, b" or "a + b".
+    Union(Box<TreeQueryExpr>, Box<TreeQueryExpr>),
+    /// Trees matched by the first expression but not the second: "a - b" or "a !b".
+    Difference(Box<TreeQueryExpr>, Box<TreeQueryExpr>),
+    /// Trees matched by both expressions: "a & b".
+    Intersection(Box<TreeQueryExpr>, Box<TreeQueryExpr>),
+}
+
 #[derive(
     Clone,
     Debug,
@@ -1476,11 +1977,164 @@ impl TreeSortMode {
     }
 }
 
+/// Structured output formats.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    strum_macros::EnumString,
+    strum_macros::Display,
+    strum_macros::VariantNames,
+)]
+#[strum(ascii_case_insensitive, serialize_all = "kebab-case")]
+pub enum OutputFormat {
+    #[default]
+    Yaml,
+    Json,
+}
+
+impl OutputFormat {
+    /// Parse an output format from a string using strum's from_str().
+    pub(crate) fn parse_from_str(string: &str) -> Result<OutputFormat, String> {
+        OutputFormat::from_str(string).map_err(|_| format!("choices are {:?}", Self::VARIANTS))
+    }
+}
+
+/// File formats that can be written by "garden export".
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    strum_macros::EnumString,
+    strum_macros::Display,
+    strum_macros::VariantNames,
+)]
+#[strum(ascii_case_insensitive, serialize_all = "kebab-case")]
+pub enum ExportFormat {
+    /// Git submodules in ".gitmodules" format.
+    Gitmodules,
+    /// A JSON document containing the evaluated trees.
+    Json,
+    /// Projects in Google "repo" XML manifest format.
+    RepoManifest,
+    /// Repositories in vcstool ".repos" format.
+    Vcstool,
+}
+
+impl ExportFormat {
+    /// Parse an export format from a string using strum's from_str().
+    pub(crate) fn parse_from_str(string: &str) -> Result<ExportFormat, String> {
+        ExportFormat::from_str(string).map_err(|_| format!("choices are {:?}", Self::VARIANTS))
+    }
+}
+
+/// File formats that can be imported by "garden import".
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    strum_macros::EnumString,
+    strum_macros::Display,
+    strum_macros::VariantNames,
+)]
+#[strum(ascii_case_insensitive, serialize_all = "kebab-case")]
+pub enum ImportFormat {
+    /// Git submodules defined in a ".gitmodules" file.
+    Gitmodules,
+    /// Projects defined in a Google "repo" XML manifest.
+    RepoManifest,
+    /// Repositories defined in a vcstool ".repos" file.
+    Vcstool,
+}
+
+impl ImportFormat {
+    /// Parse an import format from a string using strum's from_str().
+    pub(crate) fn parse_from_str(string: &str) -> Result<ImportFormat, String> {
+        ImportFormat::from_str(string).map_err(|_| format!("choices are {:?}", Self::VARIANTS))
+    }
+}
+
+/// The scopes in which variables are defined.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum_macros::Display)]
+#[strum(serialize_all = "kebab-case")]
+pub enum VariableScope {
+    /// Variables defined using "garden -D name=value".
+    Override,
+    /// Variables defined in a garden's "variables" block.
+    Garden,
+    /// Variables defined in a tree's "variables" block.
+    Tree,
+    /// Variables defined in the top-level "variables" block.
+    Global,
+    /// Variables defined in a grafted configuration.
+    Graft,
+    /// Variables defined in an "environment" block.
+    Environment,
+}
+
+/// A chain of variables whose evaluation refers back to itself.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VariableCycle {
+    pub chain: Vec<(String, VariableScope)>,
+}
+
+impl std::fmt::Display for VariableCycle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let chain: Vec<String> = self
+            .chain
+            .iter()
+            .map(|(name, scope)| format!("{name} ({scope})"))
+            .collect();
+        write!(f, "{}", chain.join(" -> "))
+    }
+}
+
+/// A variable that was evaluated and the location where it was defined.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VariableOrigin {
+    pub name: String,
+    pub scope: VariableScope,
+    /// Overridden definitions come first. The last location is the definition that won.
+    pub locations: Vec<SourceLocation>,
+}
+
+impl std::fmt::Display for VariableOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}): ", self.name, self.scope)?;
+        match (self.locations.split_last(), self.scope) {
+            (Some((location, [])), _) => write!(f, "{location}"),
+            (Some((location, overridden)), _) => {
+                let overridden: Vec<String> = overridden.iter().map(ToString::to_string).collect();
+                write!(f, "{location} (overrides {})", overridden.join(", "))
+            }
+            (None, VariableScope::Override) => write!(f, "command-line"),
+            (None, VariableScope::Environment) => write!(f, "environment"),
+            (None, _) => write!(f, "built-in"),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ApplicationContext {
     pub options: cli::MainOptions,
     arena: UnsafeCell<Arena<Configuration>>,
     root_id: ConfigId,
+    /// The chain of variables that are currently being evaluated.
+    eval_stack: RefCell<Vec<(String, VariableScope)>>,
+    /// Cycles that were encountered while evaluating variables.
+    /// The shared context is cloned from multiple threads so these fields are synchronized.
+    variable_cycles: Mutex<Vec<VariableCycle>>,
+    /// Are variables being evaluated only to detect cycles?
+    checking_cycles: atomic::AtomicBool,
+    /// The variables that were evaluated and where they were defined, when recording.
+    variable_origins: Mutex<Option<Vec<VariableOrigin>>>,
 }
 
 /// Safety: ApplicationContext is not thread-safe due to its use of internal mutability.
@@ -1514,6 +2168,10 @@ impl Clone for ApplicationContext {
             arena: UnsafeCell::new(arena),
             options: self.options.clone(),
             root_id: self.root_id,
+            eval_stack: RefCell::new(Vec::new()),
+            variable_cycles: Mutex::new(self.get_variable_cycles()),
+            checking_cycles: atomic::AtomicBool::new(self.is_checking_cycles()),
+            variable_origins: Mutex::new(self.variable_origins.lock().unwrap().clone()),
         }
     }
 }
@@ -1529,6 +2187,10 @@ impl ApplicationContext {
             arena: UnsafeCell::new(arena),
             root_id,
             options,
+            eval_stack: RefCell::new(Vec::new()),
+            variable_cycles: Mutex::new(Vec::new()),
+            checking_cycles: atomic::AtomicBool::new(false),
+            variable_origins: Mutex::new(None),
         };
         // Record the ID in the configuration.
         let config = app_context.get_root_config_mut();
@@ -1539,6 +2201,16 @@ impl ApplicationContext {
 
     /// Initialize an ApplicationContext and Configuration from cli::MainOptions.
     pub fn from_options(options: &cli::MainOptions) -> Result<Self, errors::GardenError> {
+        let app_context = Self::from_options_without_grafts(options)?;
+        config::read_grafts(&app_context)?;
+
+        Ok(app_context)
+    }
+
+    /// Initialize an ApplicationContext from cli::MainOptions without reading grafts.
+    pub(crate) fn from_options_without_grafts(
+        options: &cli::MainOptions,
+    ) -> Result<Self, errors::GardenError> {
         let app_context = Self::new(options.clone());
         let config_verbose = options.debug_level(constants::DEBUG_LEVEL_CONFIG);
 
@@ -1550,7 +2222,6 @@ impl ApplicationContext {
             None,
         )?;
         app_context.get_root_config_mut().update_options(options)?;
-        config::read_grafts(&app_context)?;
 
         Ok(app_context)
     }
@@ -1635,13 +2306,14 @@ impl ApplicationContext {
     }
 
     /// Attach a graft to the configuration specified by ConfigId.
+    /// Child grafts are not read. Returns the ConfigId of the new graft.
     pub(crate) fn add_graft_config(
         &self,
         config_id: ConfigId,
         graft_name: &str,
         path: &std::path::Path,
         root: Option<&std::path::Path>,
-    ) -> Result<(), errors::GardenError> {
+    ) -> Result<ConfigId, errors::GardenError> {
         let path = path.to_path_buf();
         let config_verbose = self.options.debug_level(constants::DEBUG_LEVEL_CONFIG);
         let mut graft_config = Configuration::new();
@@ -1658,10 +2330,81 @@ impl ApplicationContext {
         if let Some(graft_config) = self.get_config_mut(config_id).grafts.get_mut(graft_name) {
             graft_config.set_id(graft_id);
         }
-        // Read child grafts recursively.
-        config::read_grafts_recursive(self, graft_id)?;
 
-        Ok(())
+        Ok(graft_id)
+    }
+
+    /// Record that a variable is being evaluated.
+    pub(crate) fn push_variable(
+        &self,
+        name: &str,
+        scope: VariableScope,
+        locations: &[SourceLocation],
+    ) {
+        if let Some(origins) = self.variable_origins.lock().unwrap().as_mut() {
+            let origin = VariableOrigin {
+                name: name.to_string(),
+                scope,
+                locations: locations.to_vec(),
+            };
+            if !origins.contains(&origin) {
+                origins.push(origin);
+            }
+        }
+        self.eval_stack.borrow_mut().push((name.to_string(), scope));
+    }
+
+    /// Record that the most recently pushed variable has been evaluated.
+    pub(crate) fn pop_variable(&self) {
+        self.eval_stack.borrow_mut().pop();
+    }
+
+    /// Record a cycle for the variable that is currently being evaluated.
+    /// The cycle starts at the first entry in the evaluation chain that refers
+    /// to the same variable as the innermost entry.
+    pub(crate) fn add_variable_cycle(&self) {
+        let stack = self.eval_stack.borrow();
+        let Some(current) = stack.last() else {
+            return;
+        };
+        let start = stack.iter().position(|entry| entry == current).unwrap_or(0);
+        let cycle = VariableCycle {
+            chain: stack[start..].to_vec(),
+        };
+        let mut cycles = self.variable_cycles.lock().unwrap();
+        if !cycles.contains(&cycle) {
+            cycles.push(cycle);
+        }
+    }
+
+    /// Enable or disable cycle-checking mode. Required variables are not enforced
+    /// while checking for cycles so that evaluation errors are reported in order.
+    pub(crate) fn set_checking_cycles(&self, value: bool) {
+        self.checking_cycles.store(value, atomic::Ordering::Relaxed);
+    }
+
+    /// Are variables being evaluated only to detect cycles?
+    pub(crate) fn is_checking_cycles(&self) -> bool {
+        self.checking_cycles.load(atomic::Ordering::Relaxed)
+    }
+
+    /// Start recording the origins of the variables that are evaluated.
+    pub fn record_variable_origins(&self) {
+        *self.variable_origins.lock().unwrap() = Some(Vec::new());
+    }
+
+    /// Return the origins of the variables that were evaluated while recording.
+    pub fn get_variable_origins(&self) -> Vec<VariableOrigin> {
+        self.variable_origins
+            .lock()
+            .unwrap()
+            .clone()
+            .unwrap_or_default()
+    }
+
+    /// Return the variable cycles that were encountered during evaluation.
+    pub fn get_variable_cycles(&self) -> Vec<VariableCycle> {
+        self.variable_cycles.lock().unwrap().clone()
     }
 }
 
@@ -1685,6 +2428,38 @@ pub struct GitTreeDetails {
     pub tree_type: GitTreeType,
 }
 
+/// Represent "git status" details queried from Git.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GitStatus {
+    /// The current branch, or the abbreviated commit ID for detached heads.
+    pub branch: String,
+    /// The upstream branch, or an empty string when no upstream is configured.
+    pub upstream: String,
+    /// The number of commits that have not been pushed to the upstream branch.
+    pub ahead: usize,
+    /// The number of upstream commits that have not been merged.
+    pub behind: usize,
+    /// The number of files with staged changes.
+    pub staged: usize,
+    /// The number of files with unstaged changes.
+    pub unstaged: usize,
+    /// The number of untracked files.
+    pub untracked: usize,
+    /// The number of stash entries.
+    pub stashes: usize,
+}
+
+impl GitStatus {
+    /// Return true when there are no local changes and the branch matches its upstream.
+    pub fn is_clean(&self) -> bool {
+        self.ahead == 0
+            && self.behind == 0
+            && self.staged == 0
+            && self.unstaged == 0
+            && self.untracked == 0
+    }
+}
+
 /// Return true if the context has not been filtered out and refers to a valid configured tree.
 pub(crate) fn is_valid_context(
     app_context: &ApplicationContext,
@@ -10,6 +10,11 @@ pub(crate) fn current_dir_string() -> String {
     current_dir().to_string_lossy().to_string()
 }
 
+/// Return a path relative to the current directory when the path is inside of it.
+pub(crate) fn relative_path(path: &std::path::Path) -> std::path::PathBuf {
+    strip_prefix(&current_dir(), path).unwrap_or_else(|_| path.to_path_buf())
+}
+
 /// Return the home directory for the current user.
 pub(crate) fn home_dir() -> std::path::PathBuf {
     dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("/tmp"))
@@ -1,4 +1,6 @@
-use crate::{constants, errors, eval, model, path, query, syntax};
+use rayon::prelude::*;
+
+use crate::{cli, constants, errors, eval, git, model, path, query, syntax};
 
 /// Resolve a tree query into a `Vec<garden::model::TreeContext>`.
 ///
@@ -13,11 +15,250 @@ pub fn resolve_trees(
     config: &model::Configuration,
     graft_config: Option<&model::Configuration>,
     query: &str,
+) -> Vec<model::TreeContext> {
+    // Queries that cannot be parsed as an expression are resolved as a single query.
+    match parse_query(query) {
+        Some(model::TreeQueryExpr::Query(term)) => {
+            resolve_query(app_context, config, graft_config, &term)
+        }
+        Some(expr) => resolve_expr(app_context, config, graft_config, &expr),
+        None => resolve_query(app_context, config, graft_config, query),
+    }
+}
+
+/// Evaluate a tree query expression into a `Vec<garden::model::TreeContext>`.
+fn resolve_expr(
+    app_context: &model::ApplicationContext,
+    config: &model::Configuration,
+    graft_config: Option<&model::Configuration>,
+    expr: &model::TreeQueryExpr,
+) -> Vec<model::TreeContext> {
+    match expr {
+        model::TreeQueryExpr::Query(query) => {
+            resolve_query(app_context, config, graft_config, query)
+        }
+        model::TreeQueryExpr::Union(left, right) => {
+            let mut result = resolve_expr(app_context, config, graft_config, left);
+            let mut seen: indexmap::IndexSet<_> = result
+                .iter()
+                .map(|ctx| tree_context_key(config, ctx))
+                .collect();
+            for ctx in resolve_expr(app_context, config, graft_config, right) {
+                if seen.insert(tree_context_key(config, &ctx)) {
+                    result.push(ctx);
+                }
+            }
+            result
+        }
+        model::TreeQueryExpr::Difference(left, right) => {
+            let excluded: indexmap::IndexSet<_> =
+                resolve_expr(app_context, config, graft_config, right)
+                    .iter()
+                    .map(|ctx| tree_context_key(config, ctx))
+                    .collect();
+            let mut result = resolve_expr(app_context, config, graft_config, left);
+            result.retain(|ctx| !excluded.contains(&tree_context_key(config, ctx)));
+            result
+        }
+        model::TreeQueryExpr::Intersection(left, right) => {
+            let included: indexmap::IndexSet<_> =
+                resolve_expr(app_context, config, graft_config, right)
+                    .iter()
+                    .map(|ctx| tree_context_key(config, ctx))
+                    .collect();
+            let mut result = resolve_expr(app_context, config, graft_config, left);
+            result.retain(|ctx| included.contains(&tree_context_key(config, ctx)));
+            result
+        }
+    }
+}
+
+/// Trees are identified by their name and the configuration that defines them.
+/// Trees without a configuration are defined by the root configuration.
+fn tree_context_key(
+    config: &model::Configuration,
+    ctx: &model::TreeContext,
+) -> (String, Option<model::ConfigId>) {
+    (ctx.tree.clone(), ctx.config.or(config.get_id()))
+}
+
+/// Tokens used by tree query expressions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum QueryToken {
+    Query(String),
+    Union,
+    Difference,
+    Intersection,
+    Open,
+    Close,
+}
+
+/// Split a tree query expression into tokens.
+/// "," "&" "(" and ")" are operators everywhere outside of "[...]" glob brackets.
+/// "+" "-" and "!" are only operators at the start of a term so that
+/// tree names such as "tree-name" do not need to be quoted.
+fn tokenize_query(query: &str) -> Vec<QueryToken> {
+    let mut tokens = Vec::new();
+    let mut term = String::new();
+    let mut in_brackets = false;
+
+    let end_term = |term: &mut String, tokens: &mut Vec<QueryToken>| {
+        if !term.is_empty() {
+            tokens.push(QueryToken::Query(std::mem::take(term)));
+        }
+    };
+
+    for value in query.chars() {
+        if in_brackets {
+            in_brackets = value != ']';
+            term.push(value);
+            continue;
+        }
+        let operator = match value {
+            ',' => Some(QueryToken::Union),
+            '&' => Some(QueryToken::Intersection),
+            '(' => Some(QueryToken::Open),
+            ')' => Some(QueryToken::Close),
+            '+' if term.is_empty() => Some(QueryToken::Union),
+            '-' | '!' if term.is_empty() => Some(QueryToken::Difference),
+            _ => None,
+        };
+        if let Some(token) = operator {
+            end_term(&mut term, &mut tokens);
+            tokens.push(token);
+        } else if value.is_whitespace() {
+            end_term(&mut term, &mut tokens);
+        } else {
+            in_brackets = value == '[';
+            term.push(value);
+        }
+    }
+    end_term(&mut term, &mut tokens);
+
+    tokens
+}
+
+/// Parse a tree query expression.
+///
+/// Intersections ("&") bind more tightly than unions ("," and "+") and
+/// differences ("-" and "!"), which are evaluated from left to right.
+/// A leading "-" or "!" excludes trees from the set of all trees.
+/// Returns None when the query is not a valid expression.
+pub fn parse_query(query: &str) -> Option<model::TreeQueryExpr> {
+    let tokens = tokenize_query(query);
+    let mut parser = QueryParser {
+        tokens: &tokens,
+        pos: 0,
+    };
+    let expr = parser.parse_expr()?;
+    if parser.pos == tokens.len() {
+        Some(expr)
+    } else {
+        None
+    }
+}
+
+/// Recursive descent parser for tree query expressions.
+struct QueryParser<'a> {
+    tokens: &'a [QueryToken],
+    pos: usize,
+}
+
+impl<'a> QueryParser<'a> {
+    /// Return the current token and advance to the next token.
+    fn next(&mut self) -> Option<&'a QueryToken> {
+        let token = self.tokens.get(self.pos);
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    /// expr := intersection (("," | "+" | "-" | "!") intersection)*
+    fn parse_expr(&mut self) -> Option<model::TreeQueryExpr> {
+        let mut expr = self.parse_intersection()?;
+        loop {
+            match self.tokens.get(self.pos) {
+                Some(QueryToken::Union) => {
+                    self.pos += 1;
+                    let right = self.parse_intersection()?;
+                    expr = model::TreeQueryExpr::Union(Box::new(expr), Box::new(right));
+                }
+                Some(QueryToken::Difference) => {
+                    self.pos += 1;
+                    let right = self.parse_intersection()?;
+                    expr = model::TreeQueryExpr::Difference(Box::new(expr), Box::new(right));
+                }
+                _ => return Some(expr),
+            }
+        }
+    }
+
+    /// intersection := unary ("&" unary)*
+    fn parse_intersection(&mut self) -> Option<model::TreeQueryExpr> {
+        let mut expr = self.parse_unary()?;
+        while self.tokens.get(self.pos) == Some(&QueryToken::Intersection) {
+            self.pos += 1;
+            let right = self.parse_unary()?;
+            expr = model::TreeQueryExpr::Intersection(Box::new(expr), Box::new(right));
+        }
+        Some(expr)
+    }
+
+    /// unary := ("-" | "!") unary | query | "(" expr ")"
+    fn parse_unary(&mut self) -> Option<model::TreeQueryExpr> {
+        match self.next()? {
+            QueryToken::Difference => {
+                let all_trees = model::TreeQueryExpr::Query(string!("@*"));
+                let expr = self.parse_unary()?;
+                Some(model::TreeQueryExpr::Difference(
+                    Box::new(all_trees),
+                    Box::new(expr),
+                ))
+            }
+            QueryToken::Query(query) => Some(model::TreeQueryExpr::Query(query.clone())),
+            QueryToken::Open => {
+                let expr = self.parse_expr()?;
+                match self.next()? {
+                    QueryToken::Close => Some(expr),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Resolve a single garden, group, tree or path query.
+fn resolve_query(
+    app_context: &model::ApplicationContext,
+    config: &model::Configuration,
+    graft_config: Option<&model::Configuration>,
+    query: &str,
 ) -> Vec<model::TreeContext> {
     let mut result = Vec::new();
     let tree_query = model::TreeQuery::new(query);
     let pattern = &tree_query.pattern;
 
+    // Tag queries only match trees by their tags.
+    if tree_query.is_tag {
+        if syntax::is_graft(query) {
+            if let (Ok((graft_id, _)), Some(remainder)) =
+                (config.get_graft_id(query), syntax::trim_graft(query))
+            {
+                result = resolve_query(
+                    app_context,
+                    config,
+                    Some(app_context.get_config(graft_id)),
+                    &remainder,
+                );
+            }
+        } else {
+            result = tagged_trees(graft_config.unwrap_or(config), pattern);
+        }
+        return result;
+    }
+
     if tree_query.include_gardens {
         result = garden_trees(app_context, config, graft_config, pattern);
         if !result.is_empty() {
@@ -127,6 +368,114 @@ pub(crate) fn resolve_and_filter_trees(
     result
 }
 
+/// Resolve tree queries into the trees that they match, filtered by Git state.
+/// Trees that are matched by multiple queries, gardens or groups are only returned once.
+/// Returns (qualified name, tree context, tree) tuples in query order.
+pub(crate) fn resolve_unique_trees<'a>(
+    app_context: &'a model::ApplicationContext,
+    queries: &[String],
+    pattern: &str,
+    filters: &cli::TreeFilterOptions,
+) -> Vec<(String, model::TreeContext, &'a model::Tree)> {
+    let config = app_context.get_root_config();
+    let mut trees = Vec::new();
+    let mut seen = model::StringSet::new();
+    for query in queries {
+        let resolved = resolve_and_filter_trees(app_context, config, query, pattern);
+        for context in filter_trees(app_context, resolved, filters) {
+            let name = qualified_tree_name(app_context, &context);
+            if !seen.insert(name.clone()) {
+                continue;
+            }
+            let tree_config = match context.config {
+                Some(config_id) => app_context.get_config(config_id),
+                None => config,
+            };
+            let Some(tree) = tree_config.trees.get(&context.tree) else {
+                continue;
+            };
+            trees.push((name, context, tree));
+        }
+    }
+
+    trees
+}
+
+/// Filter tree contexts by their Git state. Trees are checked in parallel.
+pub(crate) fn filter_trees(
+    app_context: &model::ApplicationContext,
+    contexts: Vec<model::TreeContext>,
+    filters: &cli::TreeFilterOptions,
+) -> Vec<model::TreeContext> {
+    if !filters.is_active() {
+        return contexts;
+    }
+    contexts
+        .into_par_iter()
+        .filter(|context| tree_matches_filters(app_context, context, filters))
+        .collect()
+}
+
+/// Return true when a tree matches all of the specified filters.
+fn tree_matches_filters(
+    app_context: &model::ApplicationContext,
+    context: &model::TreeContext,
+    filters: &cli::TreeFilterOptions,
+) -> bool {
+    let config = match context.config {
+        Some(config_id) => app_context.get_config(config_id),
+        None => app_context.get_root_config(),
+    };
+    let Some(tree) = config.trees.get(&context.tree) else {
+        return false;
+    };
+    let Some(pathbuf) = tree.pathbuf() else {
+        return false;
+    };
+    let exists = pathbuf.exists();
+    if filters.missing {
+        return !exists;
+    }
+    if !exists {
+        return false;
+    }
+    if let Some(duration) = filters.modified_within {
+        let is_recent = tree
+            .modified()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|elapsed| elapsed <= duration);
+        if !is_recent {
+            return false;
+        }
+    }
+    if filters.dirty || filters.clean {
+        match git::is_dirty(&pathbuf) {
+            Some(is_dirty) if is_dirty == filters.dirty => (),
+            _ => return false,
+        }
+    }
+    if let Some(pattern) = &filters.branch_pattern {
+        if !git::branch(&pathbuf).is_some_and(|branch| pattern.matches(&branch)) {
+            return false;
+        }
+    }
+    if filters.ahead || filters.behind {
+        let Some((ahead, behind)) = git::ahead_behind(&pathbuf, "@{upstream}") else {
+            return false;
+        };
+        if (filters.ahead && ahead == 0) || (filters.behind && behind == 0) {
+            return false;
+        }
+    }
+    if let Some(remote) = &filters.has_remote {
+        if !git::remotes(&pathbuf).contains(remote) {
+            return false;
+        }
+    }
+
+    true
+}
+
 /// Return tree contexts for every garden matching the specified pattern.
 /// Parameters:
 /// - config: `&garden::model::Configuration`
@@ -444,6 +793,23 @@ pub(crate) fn tree_name_from_abspath(
     None
 }
 
+/// Return tree contexts for trees with tags that match the specified pattern.
+fn tagged_trees(config: &model::Configuration, pattern: &glob::Pattern) -> Vec<model::TreeContext> {
+    let mut result = Vec::new();
+    for tree in config.trees.values() {
+        if tree.tags.iter().any(|tag| pattern.matches(tag)) {
+            result.push(model::TreeContext::new(
+                tree.get_name(),
+                config.graft_id(),
+                None,
+                None,
+            ));
+        }
+    }
+
+    result
+}
+
 /// Returns tree contexts matching the specified pattern
 fn trees(config: &model::Configuration, pattern: &glob::Pattern) -> Vec<model::TreeContext> {
     let mut result = Vec::new();
@@ -592,3 +958,29 @@ pub(crate) fn shared_worktree_path(
 
     tree.get_name().to_string()
 }
+
+/// Return the "graft::tree" name for a tree context. Root trees are returned as-is.
+pub(crate) fn qualified_tree_name(
+    app_context: &model::ApplicationContext,
+    ctx: &model::TreeContext,
+) -> String {
+    let mut name = ctx.tree.clone();
+    let mut config_id = ctx.config;
+    while let Some(id) = config_id {
+        let Some(parent_id) = app_context.get_config(id).parent_id else {
+            break;
+        };
+        let parent = app_context.get_config(parent_id);
+        if let Some(graft_name) = parent
+            .grafts
+            .iter()
+            .find(|(_, graft)| graft.get_id() == Some(id))
+            .map(|(graft_name, _)| graft_name)
+        {
+            name = format!("{graft_name}::{name}");
+        }
+        config_id = Some(parent_id);
+    }
+
+    name
+}
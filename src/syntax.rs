@@ -28,6 +28,12 @@ pub(crate) fn is_tree(string: &str) -> bool {
     string.starts_with('@')
 }
 
+/// Return true if `string` is a `#tag` expression.
+#[inline]
+pub(crate) fn is_tag(string: &str) -> bool {
+    string.starts_with('#')
+}
+
 /// Return true if `string` is a variable "replace" operation.
 #[inline]
 pub(crate) fn is_append_op(string: &str) -> bool {
@@ -83,10 +89,46 @@ pub(crate) fn is_shebang(string: &str) -> bool {
     string.starts_with("#!")
 }
 
-/// Trim garden, group, and tree prefixes
+/// Parse a duration such as "90", "30s", "15m", "2h", "1d", "1w" or "1h30m".
+/// Values without a unit are seconds.
+pub(crate) fn parse_duration(value: &str) -> Result<std::time::Duration, String> {
+    let invalid = || format!("invalid duration: '{value}'");
+    let mut seconds: u64 = 0;
+    let mut digits = String::new();
+    for c in value.trim().chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let scale = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return Err(invalid()),
+        };
+        let amount: u64 = digits.parse().map_err(|_| invalid())?;
+        seconds = amount
+            .checked_mul(scale)
+            .and_then(|amount| seconds.checked_add(amount))
+            .ok_or_else(invalid)?;
+        digits.clear();
+    }
+    if !digits.is_empty() {
+        let amount: u64 = digits.parse().map_err(|_| invalid())?;
+        seconds = seconds.checked_add(amount).ok_or_else(invalid)?;
+    } else if seconds == 0 && value.trim().is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(std::time::Duration::from_secs(seconds))
+}
+
+/// Trim garden, group, tag and tree prefixes
 #[inline]
 pub(crate) fn trim(string: &str) -> &str {
-    let needs_trim = is_group(string) || is_tree(string) || is_garden(string);
+    let needs_trim = is_group(string) || is_tree(string) || is_garden(string) || is_tag(string);
     if !string.is_empty() && needs_trim {
         &string[1..]
     } else {
@@ -137,6 +179,8 @@ pub(crate) fn trim_graft(string: &str) -> Option<String> {
         result = string!("%") + after;
     } else if is_tree(string) {
         result = string!("@") + after;
+    } else if is_tag(string) {
+        result = string!("#") + after;
     } else {
         result = after.to_string();
     }
@@ -241,6 +285,46 @@ pub(crate) fn post_command(name: &str) -> String {
     format!("{name}>")
 }
 
+/// Return true when a URL is relative, e.g. "../name.git" or "./name".
+pub(crate) fn is_relative_url(url: &str) -> bool {
+    matches!(url, "." | "..") || url.starts_with("./") || url.starts_with("../")
+}
+
+/// Resolve a relative URL against a base URL. The base URL is treated as a directory
+/// so "../name.git" relative to "https://host/org/project.git" is "https://host/org/name.git".
+/// scp-style "host:path" URLs are resolved like Git, e.g. "../name.git" relative to
+/// "git@host:project.git" is "git@host:name.git".
+pub(crate) fn resolve_relative_url(base_url: &str, relative_url: &str) -> String {
+    let mut url = base_url.trim_end_matches('/').to_string();
+    for component in relative_url.split('/') {
+        match component {
+            "" | "." => (),
+            ".." => match url.rsplit_once('/') {
+                // Stop at the scheme and host, e.g. "https://host".
+                Some((parent, _)) => {
+                    if !parent.is_empty() && !parent.ends_with('/') {
+                        url.truncate(parent.len());
+                    }
+                }
+                // Remove the path from scp-style "host:path" URLs.
+                None => {
+                    if let Some((host, _)) = url.split_once(':') {
+                        url.truncate(host.len() + 1);
+                    }
+                }
+            },
+            _ => {
+                if !url.ends_with(':') {
+                    url.push('/');
+                }
+                url.push_str(component);
+            }
+        }
+    }
+
+    url
+}
+
 /// Unit tests
 #[cfg(test)]
 mod tests {
@@ -268,6 +352,64 @@ mod tests {
         assert!(!super::is_tree("tree"), "tree is not a tree");
     }
 
+    #[test]
+    fn is_tag() {
+        assert!(super::is_tag("#tag"), "#tag is a tag");
+        assert!(!super::is_tag("tag"), "tag is not a tag");
+    }
+
+    #[test]
+    fn resolve_relative_url() {
+        let resolve = super::resolve_relative_url;
+        assert!(super::is_relative_url("../sub.git"));
+        assert!(super::is_relative_url("./sub"));
+        assert!(!super::is_relative_url("https://host/sub.git"));
+        assert_eq!(
+            "https://host/org/sub.git",
+            resolve("https://host/org/project.git", "../sub.git")
+        );
+        assert_eq!(
+            "https://host/org/project.git/sub",
+            resolve("https://host/org/project.git/", "./sub")
+        );
+        // Relative URLs cannot leave the host.
+        assert_eq!(
+            "https://host/sub.git",
+            resolve("https://host", "../sub.git")
+        );
+        // scp-style URLs.
+        assert_eq!(
+            "git@host:sub.git",
+            resolve("git@host:project.git", "../sub.git")
+        );
+        assert_eq!(
+            "git@host:org/sub.git",
+            resolve("git@host:org/project.git", "../sub.git")
+        );
+        assert_eq!(
+            "git@host:sub.git",
+            resolve("git@host:org/project.git", "../../sub.git")
+        );
+    }
+
+    #[test]
+    fn parse_duration() {
+        let seconds = |value| super::parse_duration(value).map(|duration| duration.as_secs());
+        assert_eq!(Ok(90), seconds("90"));
+        assert_eq!(Ok(30), seconds("30s"));
+        assert_eq!(Ok(15 * 60), seconds("15m"));
+        assert_eq!(Ok(2 * 60 * 60), seconds("2h"));
+        assert_eq!(Ok(24 * 60 * 60), seconds("1d"));
+        assert_eq!(Ok(7 * 24 * 60 * 60), seconds("1w"));
+        assert_eq!(Ok(90 * 60), seconds("1h30m"));
+        assert!(super::parse_duration("").is_err());
+        assert!(super::parse_duration("h").is_err());
+        assert!(super::parse_duration("1y").is_err());
+        // Durations that overflow are invalid.
+        assert!(super::parse_duration("99999999999999999w").is_err());
+        assert!(super::parse_duration("18446744073709551615s1s").is_err());
+    }
+
     #[test]
     fn is_git_dir() {
         assert!(super::is_git_dir("tree.git"), "tree.git is a git dir");
@@ -338,6 +480,10 @@ mod tests {
         assert!(value.is_some());
         assert_eq!(":bar::baz", value.unwrap());
 
+        let value = super::trim_graft("#foo::bar::baz");
+        assert!(value.is_some());
+        assert_eq!("#bar::baz", value.unwrap());
+
         let value = super::trim_graft("foo::bar");
         assert!(value.is_some());
         assert_eq!("bar", value.unwrap());
@@ -173,12 +173,27 @@ pub fn garden_capture(args: &[&str]) -> String {
 /// Execute a command and ensure that exit status 0 is returned.
 /// Return the captured stdout value as a string.
 pub fn garden_exec(args: &[&str]) -> (u32, String, String) {
+    garden_exec_with_env(args, &[])
+}
+
+/// Execute a command in the "root" directory using the specified garden file.
+/// Return the exit status and the captured stdout and stderr values.
+pub fn garden_exec_with_config(root: &str, config: &str, args: &[&str]) -> (u32, String, String) {
+    let mut argv: Vec<&str> = vec!["--chdir", root, "--config", config];
+    argv.extend(args);
+    garden_exec(&argv)
+}
+
+/// Execute a command with additional environment variables.
+/// Return the exit status and the captured stdout and stderr values.
+pub fn garden_exec_with_env(args: &[&str], env: &[(&str, &str)]) -> (u32, String, String) {
     let mut argv: Vec<&str> = vec!["garden"];
     argv.extend(args);
     display::print_command_vec(&argv);
 
     let mut exec = cargo_bin_cmd("garden").expect("garden command");
     exec.args(args);
+    exec.envs(env.iter().copied());
     let output_result = exec.output();
     assert!(output_result.is_ok());
     let status = exec.status().unwrap().code().unwrap_or(0);
@@ -189,6 +189,166 @@ fn templates() -> Result<()> {
     Ok(())
 }
 
+/// Templates with parameters
+#[test]
+fn template_parameters() -> Result<()> {
+    let string = string!(
+        r#"
+    templates:
+        cmake:
+            parameters:
+                generator: Unix Makefiles
+                build-type:
+            variables:
+                build-dir: build-${build-type}
+            commands:
+                configure: cmake -G "${generator}" -DCMAKE_BUILD_TYPE=${build-type}
+        cmake-debug:
+            extend: cmake
+            variables:
+                flavor: debug
+    trees:
+        ninja:
+            templates:
+                - name: cmake
+                  args:
+                      generator: Ninja
+                      build-type: Release
+        make:
+            templates:
+                - name: cmake-debug
+                  args:
+                      build-type: Debug
+        override:
+            templates:
+                - name: cmake
+                  args:
+                      build-type: Release
+            variables:
+                generator: Xcode
+    "#
+    );
+    let app_context = common::garden_context_from_string(&string)?;
+    let config = app_context.get_root_config();
+
+    let template = config.templates.get("cmake").context("cmake")?;
+    assert_eq!(2, template.parameters.len());
+    // Extended templates inherit parameters.
+    let template = config.templates.get("cmake-debug").context("cmake-debug")?;
+    assert_eq!(2, template.parameters.len());
+
+    let tree_value = |tree_name: &str, expr: &str| -> Result<String> {
+        let tree = config.trees.get(tree_name).context("tree")?;
+        Ok(garden::eval::tree_value(
+            &app_context,
+            config,
+            None,
+            expr,
+            tree.get_name(),
+            None,
+        ))
+    };
+    // Arguments override the parameter defaults.
+    assert_eq!(
+        "Ninja Release",
+        tree_value("ninja", "${generator} ${build-type}")?
+    );
+    assert_eq!("build-Release", tree_value("ninja", "${build-dir}")?);
+    // Parameter defaults are used when arguments are not specified.
+    assert_eq!(
+        "Unix Makefiles Debug",
+        tree_value("make", "${generator} ${build-type}")?
+    );
+    assert_eq!("debug", tree_value("make", "${flavor}")?);
+    // Variables defined by the tree have the highest precedence.
+    assert_eq!("Xcode", tree_value("override", "${generator}")?);
+
+    let tree = config.trees.get("ninja").context("ninja")?;
+    assert_eq!(indexset! {string!("cmake")}, tree.templates);
+
+    Ok(())
+}
+
+/// Missing template parameters are reported as configuration errors.
+#[test]
+fn template_parameters_required() {
+    let string = string!(
+        r#"
+    templates:
+        cmake:
+            parameters:
+                generator: Unix Makefiles
+                build-type:
+    trees:
+        example:
+            templates: cmake
+    "#
+    );
+    let result = common::garden_context_from_string(&string);
+    let Err(garden::errors::GardenError::ConfigurationError(msg)) = result else {
+        panic!("missing template parameter was not reported");
+    };
+    assert_eq!(
+        msg,
+        "line 9: example: template \"cmake\" requires parameter \"build-type\""
+    );
+}
+
+/// Tags are read from trees and templates and are used by "#tag" queries.
+#[test]
+fn tree_tags() -> Result<()> {
+    let string = string!(
+        r#"
+    templates:
+        rust:
+            tags: rust
+        rust-cli:
+            extend: rust
+            tags: [cli]
+    trees:
+        ripgrep:
+            templates: rust-cli
+            tags: search
+        ripgrep-fork:
+            extend: ripgrep
+            tags: [fork]
+        git:
+            tags: [c, cli]
+        untagged: https://example.com/untagged.git
+    "#
+    );
+    let app_context = common::garden_context_from_string(&string)?;
+    let config = app_context.get_root_config();
+
+    let tags = |name: &str| -> Result<Vec<String>> {
+        let tree = config.trees.get(name).context(name.to_string())?;
+        Ok(tree.tags.iter().cloned().collect())
+    };
+    let template = config.templates.get("rust-cli").context("rust-cli")?;
+    assert!(template.tree.tags.iter().eq(["rust", "cli"]));
+    assert_eq!(vec!["rust", "cli", "search"], tags("ripgrep")?);
+    assert_eq!(vec!["rust", "cli", "search", "fork"], tags("ripgrep-fork")?);
+    assert_eq!(vec!["c", "cli"], tags("git")?);
+    assert!(tags("untagged")?.is_empty());
+
+    let resolve = |query: &str| -> Vec<String> {
+        garden::query::resolve_trees(&app_context, config, None, query)
+            .into_iter()
+            .map(|ctx| ctx.tree)
+            .collect()
+    };
+    assert_eq!(vec!["ripgrep", "ripgrep-fork"], resolve("#rust"));
+    assert_eq!(vec!["ripgrep", "ripgrep-fork", "git"], resolve("#cli"));
+    assert_eq!(vec!["ripgrep", "ripgrep-fork", "git"], resolve("#c*"));
+    assert_eq!(vec!["git"], resolve("#c"));
+    assert_eq!(vec!["ripgrep"], resolve("#cli & #rust - #fork"));
+    assert!(resolve("#missing").is_empty());
+    // Tag queries do not match tree names.
+    assert!(resolve("#git").is_empty());
+
+    Ok(())
+}
+
 /// Groups
 #[test]
 fn groups() -> Result<()> {
@@ -554,6 +714,34 @@ fn test_template_url() -> Result<()> {
     Ok(())
 }
 
+/// Configuration read from a string records line numbers without a path.
+#[test]
+fn source_locations() -> Result<()> {
+    let app_context = common::garden_context()?;
+    let config = app_context.get_root_config();
+
+    let tree = config.trees.get("cola").context("cola")?;
+    let location = tree.get_location().context("cola location")?;
+    assert_eq!(location.path, None);
+    assert_eq!(location.line, 40);
+    assert_eq!(location.to_string(), "line 40");
+
+    // Commands record the line of each entry.
+    let commands = tree.commands.get("test").context("test")?;
+    let lines: Vec<usize> = commands
+        .iter()
+        .filter_map(|command| command.get_location())
+        .map(|location| location.line)
+        .collect();
+    assert_eq!(lines, vec![53, 54]);
+
+    let garden = config.gardens.get("cola").context("cola garden")?;
+    assert_eq!(garden.get_locations().len(), 1);
+    assert_eq!(garden.get_locations()[0].line, 86);
+
+    Ok(())
+}
+
 #[test]
 fn read_grafts() -> Result<()> {
     let app = garden::model::ApplicationContext::from_path_string("tests/data/garden.yaml")?;
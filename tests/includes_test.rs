@@ -261,6 +261,26 @@ fn template_includes() -> Result<()> {
     Ok(())
 }
 
+/// Tags are merged when trees are defined in multiple files.
+#[test]
+fn tag_includes() -> Result<()> {
+    let string = string!(
+        r#"
+    garden:
+      includes: tests/data/includes/tags.yaml
+    trees:
+      tagged:
+        tags: cli
+    "#
+    );
+    let app_context = common::garden_context_from_string(&string)?;
+    let config = app_context.get_root_config();
+    let tree = config.trees.get("tagged").context("tagged")?;
+    assert!(tree.tags.iter().eq(["rust", "tools", "cli"]));
+
+    Ok(())
+}
+
 /// Ensure that commands are overridden when defined in multiple files.
 #[test]
 fn command_overrides() -> Result<()> {
@@ -341,3 +361,139 @@ fn command_overrides() -> Result<()> {
 
     Ok(())
 }
+
+/// Trees, variables and groups record the file and line where they were defined.
+#[test]
+fn source_locations() -> Result<()> {
+    let app_context =
+        garden::model::ApplicationContext::from_path_string("tests/data/locations/garden.yaml")?;
+    let config = app_context.get_root_config();
+
+    // Return the (filename, line) pairs for a slice of locations.
+    let file_lines = |locations: &[garden::model::SourceLocation]| -> Vec<(String, usize)> {
+        locations
+            .iter()
+            .map(|location| {
+                let filename = location
+                    .path
+                    .as_ref()
+                    .and_then(|path| path.file_name())
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                (filename, location.line)
+            })
+            .collect()
+    };
+
+    // The tree is defined by trees.yaml and overridden by garden.yaml.
+    let tree = config.trees.get("example").context("example")?;
+    assert_eq!(
+        file_lines(tree.get_locations()),
+        vec![(string!("trees.yaml"), 6), (string!("garden.yaml"), 10)]
+    );
+    // The most recent definition is reported as the tree's location.
+    assert_eq!(tree.get_location().map(|location| location.line), Some(10));
+    assert_eq!(
+        file_lines(tree.get_path().get_locations()),
+        vec![(string!("trees.yaml"), 7)]
+    );
+    // Overridden definitions come first. The last location is the definition that won.
+    let variable = tree.variables.get("flavor").context("flavor")?;
+    assert_eq!(
+        file_lines(variable.get_locations()),
+        vec![(string!("trees.yaml"), 9), (string!("garden.yaml"), 12)]
+    );
+    assert_eq!(
+        variable.get_location().map(|location| location.line),
+        Some(12)
+    );
+    let variable = config.variables.get("flavor").context("flavor")?;
+    assert_eq!(
+        file_lines(variable.get_locations()),
+        vec![(string!("trees.yaml"), 3), (string!("garden.yaml"), 7)]
+    );
+    let group = config.groups.get("examples").context("examples")?;
+    assert_eq!(
+        file_lines(group.get_locations()),
+        vec![(string!("trees.yaml"), 13), (string!("garden.yaml"), 15)]
+    );
+
+    Ok(())
+}
+
+/// Duplicate keys are reported when line numbers are recorded while parsing.
+#[test]
+fn source_locations_duplicate_keys() {
+    let result = garden::model::ApplicationContext::from_path_string(
+        "tests/data/locations/duplicate-keys.yaml",
+    );
+    let err = result.err().map(|err| err.to_string()).unwrap_or_default();
+    assert!(err.contains("duplicated key in mapping"), "{err}");
+}
+
+/// Glob patterns and conditional entries in "garden.includes".
+#[test]
+fn include_patterns() -> Result<()> {
+    let app_context = garden::model::ApplicationContext::from_path_string(
+        "tests/data/include-patterns/garden.yaml",
+    )?;
+    let config = app_context.get_root_config();
+
+    // Files matched by a glob pattern are included in sorted order.
+    assert!(config.trees.contains_key("first"));
+    assert!(config.trees.contains_key("second"));
+    let actual = garden::eval::value(&app_context, config, "${first} ${second}");
+    assert_eq!(actual, "1 2");
+    let actual = garden::eval::value(&app_context, config, "${order}");
+    assert_eq!(actual, "second");
+
+    // Conditional includes are only read when the condition is truthy.
+    let actual = garden::eval::value(&app_context, config, "${host_enabled}");
+    assert_eq!(actual, "enabled");
+    let actual = garden::eval::value(&app_context, config, "${host_command}");
+    assert_eq!(actual, "command");
+    assert!(!config.variables.contains_key("host_disabled"));
+    assert!(!config.variables.contains_key("host_empty"));
+
+    Ok(())
+}
+
+/// Missing includes are an error when "optional: false" is specified.
+#[test]
+fn required_includes() {
+    let result = garden::model::ApplicationContext::from_path_string(
+        "tests/data/include-patterns/required.yaml",
+    );
+    assert!(matches!(
+        result,
+        Err(garden::errors::GardenError::IncludeNotFound { .. })
+    ));
+}
+
+/// Include cycles are reported with the chain of include files.
+#[test]
+fn include_cycles() {
+    let result =
+        garden::model::ApplicationContext::from_path_string("tests/data/include-cycle/garden.yaml");
+    let Err(garden::errors::GardenError::InvalidConfiguration { msg }) = result else {
+        panic!("include cycle was not detected");
+    };
+    assert_eq!(
+        msg,
+        "include cycle detected: tests/data/include-cycle/garden.yaml -> \
+        tests/data/include-cycle/a.yaml -> tests/data/include-cycle/b.yaml -> \
+        tests/data/include-cycle/a.yaml"
+    );
+}
+
+/// Files that are included more than once are only read once.
+#[test]
+fn duplicate_includes() -> Result<()> {
+    let app_context = garden::model::ApplicationContext::from_path_string(
+        "tests/data/include-cycle/duplicate.yaml",
+    )?;
+    let config = app_context.get_root_config();
+    assert_eq!(config.environment.len(), 1);
+
+    Ok(())
+}
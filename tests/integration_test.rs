@@ -1,7 +1,7 @@
 pub mod common;
 use common::{
     assert_cmd, assert_cmd_capture, assert_ref, assert_ref_missing, exec_garden, garden_capture,
-    garden_exec, BareRepoFixture,
+    garden_exec, garden_exec_with_config, garden_exec_with_env, BareRepoFixture,
 };
 
 use garden::errors;
@@ -501,6 +501,652 @@ fn grow_worktree_and_parent() -> Result<()> {
     Ok(())
 }
 
+/// "garden grow --jobs" grows trees in parallel and grows worktrees after their parent.
+#[cfg(not(any(
+    target_arch = "aarch64",
+    target_arch = "powerpc64",
+    target_arch = "s390x",
+    target_arch = "x86"
+)))]
+#[test]
+#[named]
+fn grow_parallel() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    // The "dev" worktree is listed before its "default" parent.
+    let (exit_status, _output, err) = garden_exec(&[
+        "--chdir",
+        &root,
+        "--config",
+        "tests/data/worktree.yaml",
+        "grow",
+        "--jobs=2",
+        "dev",
+        "default",
+    ]);
+    assert_eq!(exit_status, errors::EX_OK);
+    assert!(err.contains("# dev"));
+    assert!(err.contains("# default"));
+
+    let worktree_default = fixture.worktree("default");
+    let worktree_dev = fixture.worktree("dev");
+    assert_ref(&worktree_default, "default");
+    let cmd = ["git", "symbolic-ref", "--short", "HEAD"];
+    let output = assert_cmd_capture(&cmd, &worktree_dev);
+    assert_eq!("dev", output);
+
+    // Grow trees from multiple repositories in parallel.
+    exec_garden(&[
+        "--chdir",
+        &root,
+        "--config",
+        "tests/data/branches.yaml",
+        "grow",
+        "-j",
+        "3",
+        "local",
+        "custom-remote",
+    ])?;
+    let worktree_local = fixture.worktree("local");
+    assert_ref(&worktree_local, "local");
+    let worktree_custom = fixture.worktree("custom-remote");
+    let cmd = ["git", "symbolic-ref", "--short", "HEAD"];
+    let output = assert_cmd_capture(&cmd, &worktree_custom);
+    assert_eq!("custom-dev", output);
+
+    Ok(())
+}
+
+/// "garden grow --dry-run" prints the commands that would be run without running them.
+#[test]
+#[named]
+fn grow_dry_run() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    let output = garden_capture(&[
+        "--chdir",
+        &root,
+        "--config",
+        "tests/data/worktree.yaml",
+        "grow",
+        "--dry-run",
+        "--format=json",
+        "dev",
+    ]);
+    let doc: serde_json::Value = serde_json::from_str(&output)?;
+    let plan = doc["plan"].as_array().expect("plan");
+    // The parent worktree is cloned before the child worktree is created.
+    assert_eq!("default", plan[0]["tree"]);
+    assert_eq!("git", plan[0]["command"][0]);
+    assert_eq!("clone", plan[0]["command"][1]);
+    let last = plan.last().expect("worktree command");
+    assert_eq!("dev", last["tree"]);
+    assert!(last["directory"]
+        .as_str()
+        .is_some_and(|directory| directory.ends_with("/default")));
+    let command: Vec<&str> = last["command"]
+        .as_array()
+        .expect("command")
+        .iter()
+        .filter_map(|arg| arg.as_str())
+        .collect();
+    assert_eq!(
+        command,
+        [
+            "git",
+            "worktree",
+            "add",
+            "--track",
+            "-b",
+            "dev",
+            "../dev",
+            "origin/dev"
+        ]
+    );
+    // Nothing is created during a dry run.
+    assert!(!fixture.root_pathbuf().join("default").exists());
+    assert!(!fixture.root_pathbuf().join("dev").exists());
+
+    // The default output prints each command beneath its tree.
+    let output = garden_capture(&[
+        "--chdir",
+        &root,
+        "--config",
+        "tests/data/worktree.yaml",
+        "grow",
+        "-N",
+        "dev",
+    ]);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!("# default", lines[0]);
+    assert!(lines[1].starts_with(": git clone "));
+    assert!(lines.contains(&"# dev"));
+    assert_eq!(
+        Some(&": git worktree add --track -b dev ../dev origin/dev"),
+        lines.last()
+    );
+    assert!(!fixture.root_pathbuf().join("default").exists());
+
+    Ok(())
+}
+
+/// "filter", "sparse-checkout" and "submodules" are applied when cloning and re-growing trees.
+#[test]
+#[named]
+fn grow_partial_sparse_submodules() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    let root_path = std::fs::canonicalize(fixture.root_pathbuf())?;
+    let root_str = root_path.to_string_lossy().to_string();
+    // Local submodules must be allowed explicitly.
+    let env = [
+        ("GIT_CONFIG_COUNT", "1"),
+        ("GIT_CONFIG_KEY_0", "protocol.file.allow"),
+        ("GIT_CONFIG_VALUE_0", "always"),
+    ];
+    let git = |args: &[&str], directory: &str| {
+        let mut command = vec![
+            "git",
+            "-c",
+            "user.name=Garden",
+            "-c",
+            "user.email=garden-tools@crates.io",
+            "-c",
+            "protocol.file.allow=always",
+        ];
+        command.extend(args);
+        assert_cmd(&command, directory);
+    };
+    // Create repos/content.git containing "docs", "src" and an "example" submodule.
+    git(&["init", "--quiet", "content"], &root_str);
+    let content = format!("{root_str}/content");
+    std::fs::create_dir_all(root_path.join("content/docs"))?;
+    std::fs::create_dir_all(root_path.join("content/src"))?;
+    std::fs::write(root_path.join("content/docs/README"), "docs")?;
+    std::fs::write(root_path.join("content/src/main.rs"), "fn main() {}")?;
+    let example_url = format!("file://{root_str}/repos/example.git");
+    git(
+        &["submodule", "add", "--quiet", &example_url, "example"],
+        &content,
+    );
+    git(&["add", "docs", "src"], &content);
+    git(&["commit", "--quiet", "--message=content"], &content);
+    let content_url = format!("{root_str}/repos/content.git");
+    git(
+        &["clone", "--quiet", "--bare", &content, &content_url],
+        &root_str,
+    );
+    git(&["config", "uploadpack.allowFilter", "true"], &content_url);
+
+    // Templates provide the "filter" and "sparse-checkout" settings.
+    let output = garden_capture(&[
+        "--chdir",
+        &root,
+        "--config",
+        "tests/data/partial.yaml",
+        "grow",
+        "--dry-run",
+        "sparse",
+        "submodules",
+    ]);
+    assert!(output.contains(" '--filter=blob:none' --sparse "));
+    assert!(output.contains(": git sparse-checkout set --cone docs"));
+    assert!(output.contains(": git submodule update --init --recursive"));
+    // "submodules: false" overrides the "submodules: true" setting from a template.
+    assert!(!output.contains(": git submodule update --init\n"));
+
+    let grow = || {
+        let (status, _out, _err) = garden_exec_with_env(
+            &[
+                "--chdir",
+                &root,
+                "--config",
+                "tests/data/partial.yaml",
+                "grow",
+                "sparse",
+                "submodules",
+            ],
+            &env,
+        );
+        assert_eq!(status, errors::EX_OK);
+    };
+    grow();
+
+    let sparse = fixture.worktree("sparse");
+    assert!(root_path.join("sparse/docs/README").exists());
+    assert!(!root_path.join("sparse/src").exists());
+    let output = assert_cmd_capture(&["git", "sparse-checkout", "list"], &sparse);
+    assert_eq!("docs", output);
+    let output = assert_cmd_capture(&["git", "config", "remote.origin.promisor"], &sparse);
+    assert_eq!("true", output);
+    let cmd = ["git", "config", "remote.origin.partialclonefilter"];
+    let output = assert_cmd_capture(&cmd, &sparse);
+    assert_eq!("blob:none", output);
+    assert!(root_path.join("submodules/example/.git").exists());
+    assert!(!root_path.join("sparse/example/.git").exists());
+
+    // Existing trees are updated when they are grown again.
+    assert_cmd(&["git", "sparse-checkout", "disable"], &sparse);
+    assert_cmd(
+        &["git", "config", "--unset", "remote.origin.promisor"],
+        &sparse,
+    );
+    let submodules = fixture.worktree("submodules");
+    assert_cmd(
+        &["git", "submodule", "deinit", "--quiet", "--all"],
+        &submodules,
+    );
+    assert!(root_path.join("sparse/src").exists());
+    assert!(!root_path.join("submodules/example/.git").exists());
+    grow();
+
+    assert!(!root_path.join("sparse/src").exists());
+    let output = assert_cmd_capture(&["git", "config", "remote.origin.promisor"], &sparse);
+    assert_eq!("true", output);
+    assert!(root_path.join("submodules/example/.git").exists());
+
+    Ok(())
+}
+
+/// "commit" and "tag" pins are checked out as detached heads and drift is reported.
+#[test]
+#[named]
+fn grow_pinned_commit_and_tag() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    let repo = format!("{root}/repos/example.git");
+    assert_cmd(&["git", "tag", "v1", "default~1"], &repo);
+    let pinned = assert_cmd_capture(&["git", "rev-parse", "default~1"], &repo);
+    let define = format!("pinned_commit={pinned}");
+    let config = "tests/data/pinned.yaml";
+
+    let (status, _out, _err) = garden_exec_with_config(
+        &root,
+        config,
+        &["--define", &define, "grow", "commit", "tag"],
+    );
+    assert_eq!(status, errors::EX_OK);
+    for name in ["commit", "tag"] {
+        let worktree = fixture.worktree(name);
+        let head = assert_cmd_capture(&["git", "rev-parse", "HEAD"], &worktree);
+        assert_eq!(head, pinned);
+        let cmd = ["git", "rev-parse", "--abbrev-ref", "HEAD"];
+        let output = assert_cmd_capture(&cmd, &worktree);
+        assert_eq!(output, "HEAD");
+    }
+
+    // "garden ls" displays pins next to the branch.
+    let (status, _out, err) =
+        garden_exec_with_config(&root, config, &["--define", &define, "ls", "commit", "tag"]);
+    assert_eq!(status, errors::EX_OK);
+    let short = assert_cmd_capture(&["git", "rev-parse", "--short", &pinned], &repo);
+    assert!(err.contains(&format!("# commit [{short} @ {pinned}]\n")));
+    assert!(err.ends_with(&format!("# tag [{short} @ v1]")));
+    let (_status, out, _err) = garden_exec_with_config(
+        &root,
+        config,
+        &[
+            "--define",
+            &define,
+            "ls",
+            "--format",
+            "%(name) %(commit) %(tag)",
+            "commit",
+        ],
+    );
+    assert_eq!(out, format!("commit {pinned}"));
+
+    // Existing trees that have drifted from their pin are reported.
+    let commit = fixture.worktree("commit");
+    assert_cmd(&["git", "checkout", "--quiet", "default"], &commit);
+    let (status, _out, err) =
+        garden_exec_with_config(&root, config, &["--define", &define, "grow", "commit"]);
+    assert_eq!(status, errors::EX_OK);
+    assert!(err.contains(&format!("has drifted from commit {pinned}")));
+    // "garden ls -v" marks trees that have drifted from their pin.
+    let (_status, _out, err) =
+        garden_exec_with_config(&root, config, &["--define", &define, "ls", "-v", "commit"]);
+    assert!(err.contains(&format!("# commit [default @ {pinned}] (drifted) ")));
+
+    Ok(())
+}
+
+/// "garden lock" records the commit for each tree and "garden grow --locked" restores them.
+#[test]
+#[named]
+fn lock() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    // "garden.lock" is written next to the garden file.
+    let config_path = fixture.root_pathbuf().join("garden.yaml");
+    std::fs::copy("tests/data/worktree.yaml", &config_path)?;
+    let config = config_path.to_string_lossy().to_string();
+    let (status, _out, _err) = garden_exec_with_config(&root, &config, &["grow", "default", "dev"]);
+    assert_eq!(status, errors::EX_OK);
+
+    let (status, _out, _err) = garden_exec_with_config(&root, &config, &["lock"]);
+    assert_eq!(status, errors::EX_OK);
+    let lock = std::fs::read_to_string(fixture.root_pathbuf().join("garden.lock"))?;
+    let default_worktree = fixture.worktree("default");
+    let dev_worktree = fixture.worktree("dev");
+    let default_commit = assert_cmd_capture(&["git", "rev-parse", "HEAD"], &default_worktree);
+    let dev_commit = assert_cmd_capture(&["git", "rev-parse", "HEAD"], &dev_worktree);
+    assert!(lock.contains(&format!("commit: {default_commit}")));
+    assert!(lock.contains("branch: dev"));
+    let (status, out, _err) = garden_exec_with_config(&root, &config, &["lock", "--check"]);
+    assert_eq!(status, errors::EX_OK);
+    assert!(out.is_empty());
+
+    // Moving a worktree away from its locked commit fails the check.
+    assert_cmd(
+        &["git", "reset", "--quiet", "--hard", "HEAD~1"],
+        &dev_worktree,
+    );
+    let (status, out, _err) = garden_exec_with_config(&root, &config, &["lock", "--check"]);
+    assert_eq!(status, errors::EX_ERROR);
+    assert!(out.contains("changed dev (HEAD "));
+    assert!(!out.contains("changed default"));
+
+    // "garden grow --locked" checks out the locked commits in existing trees.
+    let (status, _out, _err) =
+        garden_exec_with_config(&root, &config, &["grow", "--locked", "default", "dev"]);
+    assert_eq!(status, errors::EX_OK);
+    let output = assert_cmd_capture(&["git", "rev-parse", "HEAD"], &dev_worktree);
+    assert_eq!(output, dev_commit);
+    let (status, _out, _err) = garden_exec_with_config(&root, &config, &["lock", "--check"]);
+    assert_eq!(status, errors::EX_OK);
+
+    // Trees that have not been grown are cloned at their locked commit.
+    std::fs::remove_dir_all(fixture.root_pathbuf().join("dev"))?;
+    std::fs::remove_dir_all(fixture.root_pathbuf().join("default"))?;
+    let (status, _out, _err) =
+        garden_exec_with_config(&root, &config, &["grow", "--locked", "dev"]);
+    assert_eq!(status, errors::EX_OK);
+    let output = assert_cmd_capture(&["git", "rev-parse", "HEAD"], &default_worktree);
+    assert_eq!(output, default_commit);
+    let output = assert_cmd_capture(&["git", "rev-parse", "HEAD"], &dev_worktree);
+    assert_eq!(output, dev_commit);
+
+    Ok(())
+}
+
+/// "garden import" adds trees from .gitmodules, repo manifest and vcstool files.
+#[test]
+#[named]
+fn import() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    let config_path = fixture.root_pathbuf().join("garden.yaml");
+    std::fs::copy("tests/data/import/garden.yaml", &config_path)?;
+    let config = config_path.to_string_lossy().to_string();
+    // Imported paths are relative to the "--chdir" directory.
+    let data_dir = std::fs::canonicalize("tests/data/import")?;
+    let import = |format: &str, filename: &str| {
+        let path = data_dir.join(filename).to_string_lossy().to_string();
+        let (status, _out, err) = garden_exec(&[
+            "--chdir", &root, "--config", &config, "import", "--from", format, &path,
+        ]);
+        assert_eq!(status, errors::EX_OK);
+        err
+    };
+
+    // Projects are imported with their remotes, revisions and groups.
+    let err = import("repo-manifest", "default.xml");
+    assert!(err.contains("warning: missing/remote: unknown remote \"unknown\""));
+    let content = std::fs::read_to_string(&config_path)?;
+    let expect = r#"trees:
+  build:
+    # Existing settings are retained.
+    description: Build tools
+    url: "https://example.com/platform/build"
+    branch: main
+  platform/docs:
+    url: "https://example.com/platform/docs"
+    tag: v1.0
+  tools/lint:
+    default-remote: mirror
+    url: "https://mirror.example.com/tools/lint"
+    branch: stable
+  tools/pinned:
+    url: "https://example.com/tools/pinned"
+    commit: 0123456789abcdef0123456789abcdef01234567
+
+groups:
+  core:
+    - build
+  tools:
+    - build
+    - tools/pinned
+  docs:
+    - platform/docs
+"#;
+    assert!(content.starts_with("# Trees are imported into this garden file.\n"));
+    assert!(content.ends_with(expect));
+
+    // Submodules that track the superproject's branch are imported without a branch.
+    let err = import("gitmodules", "gitmodules");
+    assert!(err.contains("warning: no-url: submodule has no url"));
+    let content = std::fs::read_to_string(&config_path)?;
+    let expect = r#"
+  libs/example:
+    url: "https://example.com/example.git"
+    branch: dev
+  tracking:
+    url: "https://example.com/tracking.git"
+"#;
+    assert!(content.contains(expect));
+
+    // Repositories that are not Git repositories are skipped.
+    let err = import("vcstool", "example.repos");
+    assert!(err.contains("warning: src/legacy: unsupported repository type \"svn\""));
+    let content = std::fs::read_to_string(&config_path)?;
+    let expect = r#"
+  src/example:
+    url: "https://example.com/example.git"
+    branch: main
+  src/release:
+    url: "https://example.com/release.git"
+    commit: 0123456789abcdef0123456789abcdef01234567
+"#;
+    assert!(content.contains(expect));
+    assert!(!content.contains("src/legacy"));
+
+    // The imported configuration is valid.
+    let (status, _out, _err) = garden_exec(&["--chdir", &root, "--config", &config, "check"]);
+    assert_eq!(status, errors::EX_OK);
+
+    Ok(())
+}
+
+/// "garden import --base-url" resolves relative manifest and submodule URLs.
+#[test]
+#[named]
+fn import_relative_urls() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    let config_path = fixture.root_pathbuf().join("garden.yaml");
+    std::fs::copy("tests/data/import/garden.yaml", &config_path)?;
+    let config = config_path.to_string_lossy().to_string();
+    let data_dir = std::fs::canonicalize("tests/data/import")?;
+    let manifest = data_dir.join("relative.xml").to_string_lossy().to_string();
+    let gitmodules = data_dir
+        .join("gitmodules-relative")
+        .to_string_lossy()
+        .to_string();
+
+    // Relative URLs are skipped when no base URL is provided.
+    let (status, _out, err) = garden_exec_with_config(
+        &root,
+        &config,
+        &["import", "--from", "repo-manifest", &manifest],
+    );
+    assert_eq!(status, errors::EX_OK);
+    assert!(err.contains("warning: platform/build: relative fetch url \"..\" requires --base-url"));
+    let (status, _out, err) = garden_exec_with_config(
+        &root,
+        &config,
+        &["import", "--from", "gitmodules", &gitmodules],
+    );
+    assert_eq!(status, errors::EX_OK);
+    assert!(err.contains("warning: sibling: relative url \"../sibling.git\" requires --base-url"));
+    let content = std::fs::read_to_string(&config_path)?;
+    assert!(!content.contains("platform/build"));
+    assert!(!content.contains("sibling"));
+
+    // Manifest fetch URLs are relative to the manifest repository's directory.
+    let (status, _out, _err) = garden_exec_with_config(
+        &root,
+        &config,
+        &[
+            "import",
+            "--from",
+            "repo-manifest",
+            "--base-url",
+            "https://example.com/platform/manifest",
+            &manifest,
+        ],
+    );
+    assert_eq!(status, errors::EX_OK);
+    // Submodule URLs are relative to the superproject's URL.
+    let (status, _out, _err) = garden_exec_with_config(
+        &root,
+        &config,
+        &[
+            "import",
+            "--from",
+            "gitmodules",
+            "--base-url",
+            "https://example.com/org/project.git",
+            &gitmodules,
+        ],
+    );
+    assert_eq!(status, errors::EX_OK);
+    let content = std::fs::read_to_string(&config_path)?;
+    assert!(content.contains("url: \"https://example.com/platform/build\"\n"));
+    assert!(content.contains("url: \"https://example.com/org/sibling.git\"\n"));
+
+    Ok(())
+}
+
+/// `garden export` writes trees as repo manifests, vcstool files and submodules.
+#[test]
+#[named]
+fn export() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    let root_path = std::fs::canonicalize(fixture.root_pathbuf())?;
+    let repos = format!("{}/repos", root_path.to_string_lossy());
+    let config = "tests/data/export.yaml";
+    let (status, _out, _err) = garden_exec_with_config(&root, config, &["grow", "example"]);
+    assert_eq!(status, errors::EX_OK);
+
+    // Remotes are created for each distinct fetch URL. Tags are exported as tag refs.
+    let (status, out, err) =
+        garden_exec_with_config(&root, config, &["export", "--format", "repo-manifest"]);
+    assert_eq!(status, errors::EX_OK);
+    let expect = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<manifest>
+  <remote name="origin" fetch="file://{repos}" />
+  <remote name="origin-1" fetch="https://example.com/libs" />
+  <remote name="origin-2" fetch="https://example.org" />
+
+  <project name="example.git" path="example" remote="origin" revision="default" />
+  <project name="pinned.git" path="libs/pinned" remote="origin-1" revision="refs/tags/v1.0" />
+  <project name="other.git" path="other" remote="origin-2" />
+</manifest>"#
+    );
+    assert_eq!(out, expect);
+    // Trees that cannot be exported are reported.
+    assert!(err.contains("warning: local-only: tree has no url"));
+    assert!(err.contains("warning: link: symlink trees cannot be exported"));
+    // Non-default remotes cannot be represented and are reported once per tree.
+    assert_eq!(
+        err.matches("warning: example: remotes cannot be exported to repo manifests: mirror")
+            .count(),
+        1
+    );
+
+    // vcstool repositories are keyed by their path.
+    let (status, out, err) = garden_exec_with_config(
+        &root,
+        config,
+        &["export", "--format", "vcstool", "example", "libs/pinned"],
+    );
+    assert_eq!(status, errors::EX_OK);
+    let expect = format!(
+        r#"---
+repositories:
+  example:
+    type: git
+    url: "file://{repos}/example.git"
+    version: default
+  libs/pinned:
+    type: git
+    url: "https://example.com/libs/pinned.git"
+    version: v1.0"#
+    );
+    assert_eq!(out, expect);
+    assert!(err.contains("warning: example: remotes cannot be exported to vcstool: mirror"));
+
+    // .gitmodules files cannot record pinned tags.
+    let (status, out, err) = garden_exec_with_config(
+        &root,
+        config,
+        &["export", "--format", "gitmodules", "example", "libs/pinned"],
+    );
+    assert_eq!(status, errors::EX_OK);
+    let expect = format!(
+        r#"[submodule "example"]
+	path = example
+	url = file://{repos}/example.git
+	branch = default
+[submodule "libs/pinned"]
+	path = libs/pinned
+	url = https://example.com/libs/pinned.git"#
+    );
+    assert_eq!(out, expect);
+    assert!(err.contains("warning: libs/pinned: .gitmodules files cannot pin commits or tags"));
+    assert!(err.contains("warning: example: remotes cannot be exported to .gitmodules: mirror"));
+
+    // JSON output includes every remote.
+    let (status, out, err) =
+        garden_exec_with_config(&root, config, &["export", "--format", "json", "example"]);
+    assert_eq!(status, errors::EX_OK);
+    assert!(!err.contains("remotes cannot be exported"));
+    let json: serde_json::Value = serde_json::from_str(&out)?;
+    let tree = &json["trees"][0];
+    assert_eq!(tree["name"], "example");
+    assert_eq!(tree["path"], "example");
+    assert_eq!(tree["default-remote"], "origin");
+    assert_eq!(
+        tree["remotes"]["mirror"],
+        format!("file://{repos}/mirror.git")
+    );
+    assert_eq!(tree["branch"], "default");
+
+    // "--pin" records the commit that is checked out.
+    let head = assert_cmd_capture(&["git", "rev-parse", "HEAD"], &fixture.worktree("example"));
+    let (status, out, err) = garden_exec_with_config(
+        &root,
+        config,
+        &[
+            "export",
+            "--format",
+            "vcstool",
+            "--pin",
+            "example",
+            "libs/pinned",
+        ],
+    );
+    assert_eq!(status, errors::EX_OK);
+    assert!(out.contains(&format!("version: {head}")));
+    assert!(out.contains("version: v1.0"));
+    assert!(err.contains("warning: libs/pinned: tree does not exist and was not pinned"));
+
+    Ok(())
+}
+
 /// `garden grow` uses the configured default remote when just "url" is configured.
 #[test]
 #[named]
@@ -1259,6 +1905,482 @@ fn eval_environment_variables_with_circular_dependencies() {
     assert_eq!(output, "root-tree/variables/root-tree/");
 }
 
+/// "garden eval" warns about variable cycles.
+#[test]
+fn eval_variable_cycle_warning() {
+    let (status, out, err) = garden_exec(&[
+        "--config",
+        "tests/data/circular.yaml",
+        "eval",
+        "${root-variable}",
+        "root-tree",
+    ]);
+    assert_eq!(status, errors::EX_OK);
+    assert_eq!(out, "root-tree-graft-");
+    assert_eq!(
+        err,
+        "warning: variable cycle detected: root-variable (tree) -> circular-variable (global) \
+        -> circular-variable (graft) -> root-variable (tree)"
+    );
+}
+
+/// Variable cycles are reported as errors before commands are run.
+#[test]
+fn cmd_variable_cycle_error() {
+    let (status, out, err) = garden_exec(&[
+        "--config",
+        "tests/data/circular-commands.yaml",
+        "cmd",
+        "example",
+        "value",
+        "circular",
+    ]);
+    assert_eq!(status, errors::EX_CONFIG);
+    assert_eq!(out, "");
+    assert_eq!(
+        err,
+        "error: variable cycle detected: global-variable (global) \
+        -> tree-variable (tree) -> global-variable (global)"
+    );
+
+    let (status, out, _err) = garden_exec(&[
+        "--config",
+        "tests/data/circular-commands.yaml",
+        "--quiet",
+        "value",
+        "example",
+    ]);
+    assert_eq!(status, errors::EX_OK);
+    assert_eq!(out, "value");
+}
+
+/// "garden eval --show-origin" reports where each variable was defined.
+#[test]
+fn eval_show_origin() {
+    let (status, out, _err) = garden_exec(&[
+        "--config",
+        "tests/data/locations/garden.yaml",
+        "eval",
+        "--show-origin",
+        "${flavor} ${name}",
+        "example",
+    ]);
+    assert_eq!(status, errors::EX_OK);
+    assert_eq!(
+        out,
+        "debug example\n\
+        flavor (tree): tests/data/locations/garden.yaml:12 \
+        (overrides tests/data/locations/trees.yaml:9)\n\
+        name (tree): tests/data/locations/trees.yaml:10"
+    );
+
+    let (status, out, _err) = garden_exec(&[
+        "--config",
+        "tests/data/locations/garden.yaml",
+        "--define",
+        "name=value",
+        "eval",
+        "--show-origin",
+        "${name} ${TREE_NAME}",
+        "example",
+    ]);
+    assert_eq!(status, errors::EX_OK);
+    assert_eq!(
+        out,
+        "value example\n\
+        name (override): command-line\n\
+        TREE_NAME (tree): built-in"
+    );
+}
+
+/// "garden ls -v" displays the locations where trees, groups and gardens were defined.
+#[test]
+fn ls_verbose_locations() {
+    let (status, out, _err) = garden_exec(&[
+        "--config",
+        "tests/data/locations/garden.yaml",
+        "ls",
+        "--verbose",
+        "--all",
+        "--no-remotes",
+    ]);
+    assert_eq!(status, errors::EX_OK);
+    assert!(out.contains(
+        "sources:\n  \
+        - tests/data/locations/trees.yaml:6\n  \
+        - tests/data/locations/garden.yaml:10"
+    ));
+    assert!(out.contains(
+        "groups:\n  \
+        - examples (tests/data/locations/trees.yaml:13, tests/data/locations/garden.yaml:15)"
+    ));
+}
+
+/// "garden ls" displays tags and "#tag" queries select trees by their tags.
+#[test]
+fn ls_tags() {
+    let (status, out, _err) = garden_exec(&[
+        "--config",
+        "tests/data/includes/tags.yaml",
+        "ls",
+        "--all",
+        "#rust",
+    ]);
+    assert_eq!(status, errors::EX_OK);
+    assert_eq!(out, "#- tagged\ntags: rust tools");
+}
+
+/// Trees can be filtered by their Git state.
+#[test]
+#[named]
+fn state_filters() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    exec_garden(&[
+        "--chdir",
+        &root,
+        "--config",
+        "tests/data/branches.yaml",
+        "grow",
+        "default",
+        "dev",
+        "custom-remote",
+    ])?;
+    let filtered_trees = |filters: &[&str]| -> Vec<String> {
+        let mut args = vec![
+            "--chdir",
+            &root,
+            "--config",
+            "tests/data/branches.yaml",
+            "exec",
+            "--quiet",
+        ];
+        args.extend(filters);
+        args.extend(["@*", "sh", "-c", "basename \"$PWD\""]);
+        let output = garden_capture(&args);
+        output.lines().map(|line| line.to_string()).collect()
+    };
+    let default_worktree = fixture.worktree("default");
+    let dev_worktree = fixture.worktree("dev");
+
+    // "dev" has untracked files and is behind its upstream branch.
+    std::fs::write(
+        fixture.root_pathbuf().join("dev/untracked.txt"),
+        "untracked",
+    )?;
+    assert_cmd(
+        &["git", "reset", "--quiet", "--hard", "HEAD~1"],
+        &dev_worktree,
+    );
+    // "default" has a commit that has not been pushed.
+    assert_cmd(
+        &[
+            "git",
+            "-c",
+            "user.name=Garden",
+            "-c",
+            "user.email=garden-tools@crates.io",
+            "commit",
+            "--quiet",
+            "--allow-empty",
+            "-m",
+            "unpushed",
+        ],
+        &default_worktree,
+    );
+
+    assert_eq!(vec!["dev"], filtered_trees(&["--dirty"]));
+    assert_eq!(
+        vec!["default", "custom-remote"],
+        filtered_trees(&["--clean"])
+    );
+    assert_eq!(vec!["default"], filtered_trees(&["--ahead"]));
+    assert_eq!(vec!["dev"], filtered_trees(&["--behind"]));
+    assert_eq!(
+        vec!["custom-remote"],
+        filtered_trees(&["--branch=custom-*"])
+    );
+    assert_eq!(
+        vec!["custom-remote"],
+        filtered_trees(&["--has-remote=extra"])
+    );
+    assert_eq!(
+        vec!["default", "dev", "custom-remote"],
+        filtered_trees(&["--present", "--modified-within=1h"])
+    );
+    // Filters are combined.
+    assert_eq!(
+        vec!["default"],
+        filtered_trees(&["--clean", "--has-remote=origin", "--branch=default"])
+    );
+
+    // "local" has not been grown.
+    let output = garden_capture(&[
+        "--chdir",
+        &root,
+        "--config",
+        "tests/data/branches.yaml",
+        "ls",
+        "--missing",
+        "--no-remotes",
+        "--no-groups",
+        "--no-gardens",
+    ]);
+    assert_eq!(output, "#- local");
+
+    // Invalid patterns and durations are usage errors.
+    for arg in ["--branch=[", "--modified-within=99999999999999999w"] {
+        let (status, _out, err) = garden_exec(&["ls", arg]);
+        assert_eq!(status, 2);
+        assert!(err.starts_with("error: invalid value"), "{err}");
+    }
+
+    Ok(())
+}
+
+/// "garden ls --format" emits structured output and templated lines.
+#[test]
+#[named]
+fn ls_format() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    exec_garden(&[
+        "--chdir",
+        &root,
+        "--config",
+        "tests/data/branches.yaml",
+        "grow",
+        "default",
+    ])?;
+    let ls = |args: &[&str]| -> String {
+        let mut argv = vec![
+            "--chdir",
+            &root,
+            "--config",
+            "tests/data/branches.yaml",
+            "ls",
+        ];
+        argv.extend(args);
+        garden_capture(&argv)
+    };
+
+    let output = ls(&["--format=json", "default", "local"]);
+    let doc: serde_json::Value = serde_json::from_str(&output)?;
+    let trees = doc["trees"].as_array().expect("trees");
+    assert_eq!(2, trees.len());
+    assert_eq!("default", trees[0]["name"]);
+    assert_eq!("tree", trees[0]["type"]);
+    assert_eq!("default", trees[0]["branch"]);
+    assert_eq!(serde_json::json!([]), trees[0]["tags"]);
+    assert!(trees[0]["remotes"]["origin"]
+        .as_str()
+        .is_some_and(|url| url.ends_with("/repos/example.git")));
+    assert_eq!("local", trees[1]["name"]);
+    assert_eq!("missing", trees[1]["type"]);
+    assert_eq!("", trees[1]["branch"]);
+    assert!(doc["groups"].is_object());
+    assert!(doc["gardens"].is_object());
+
+    let output = ls(&["--format=yaml", "--no-groups", "--no-gardens", "default"]);
+    assert!(output.contains("  - name: default\n"));
+    assert!(output.contains("    type: tree\n"));
+    assert!(!output.contains("groups:"));
+
+    let output = ls(&["--format=%(name):%(type):%(branch) %%", "default", "local"]);
+    assert_eq!(output, "default:tree:default %\nlocal:missing: %");
+
+    // Unknown fields are rejected.
+    let (status, _out, err) = garden_exec(&["ls", "--format=%(unknown)"]);
+    assert_ne!(status, errors::EX_OK);
+    assert!(err.contains("unknown field \"unknown\""));
+
+    Ok(())
+}
+
+/// "garden status" reports the state of each tree and fails when trees are not clean.
+#[test]
+#[named]
+fn status() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    exec_garden(&[
+        "--chdir",
+        &root,
+        "--config",
+        "tests/data/branches.yaml",
+        "grow",
+        "default",
+        "dev",
+    ])?;
+    let status = |args: &[&str]| -> (u32, String) {
+        let mut argv = vec![
+            "--chdir",
+            &root,
+            "--config",
+            "tests/data/branches.yaml",
+            "status",
+        ];
+        argv.extend(args);
+        let (status, out, _err) = garden_exec(&argv);
+        (status, out)
+    };
+
+    // Missing trees are reported and fail the status check.
+    let (exit_status, output) = status(&["--format=json", "default", "dev", "local"]);
+    assert_eq!(exit_status, errors::EX_ERROR);
+    let doc: serde_json::Value = serde_json::from_str(&output)?;
+    let trees = doc["trees"].as_array().expect("trees");
+    assert_eq!(3, trees.len());
+    assert_eq!("default", trees[0]["name"]);
+    assert_eq!("clean", trees[0]["state"]);
+    assert_eq!("default", trees[0]["branch"]);
+    assert_eq!("origin/default", trees[0]["upstream"]);
+    assert_eq!("local", trees[2]["name"]);
+    assert_eq!("missing", trees[2]["state"]);
+
+    // Clean trees pass the status check.
+    let (exit_status, _output) = status(&["default", "dev"]);
+    assert_eq!(exit_status, errors::EX_OK);
+
+    // "default" has staged and untracked files.
+    let default_worktree = fixture.worktree("default");
+    std::fs::write(fixture.root_pathbuf().join("default/staged.txt"), "staged")?;
+    std::fs::write(fixture.root_pathbuf().join("default/untracked.txt"), "new")?;
+    assert_cmd(&["git", "add", "staged.txt"], &default_worktree);
+    // "dev" has a stash entry and is behind its upstream branch.
+    let dev_worktree = fixture.worktree("dev");
+    std::fs::write(fixture.root_pathbuf().join("dev/stashed.txt"), "stashed")?;
+    assert_cmd(&["git", "add", "stashed.txt"], &dev_worktree);
+    assert_cmd(
+        &[
+            "git",
+            "-c",
+            "user.name=Garden",
+            "-c",
+            "user.email=garden-tools@crates.io",
+            "stash",
+            "--quiet",
+        ],
+        &dev_worktree,
+    );
+    assert_cmd(
+        &["git", "reset", "--quiet", "--hard", "HEAD~1"],
+        &dev_worktree,
+    );
+
+    let (exit_status, output) = status(&["--format=json", "default", "dev"]);
+    assert_eq!(exit_status, errors::EX_ERROR);
+    let doc: serde_json::Value = serde_json::from_str(&output)?;
+    let trees = doc["trees"].as_array().expect("trees");
+    assert_eq!("dirty", trees[0]["state"]);
+    assert_eq!(1, trees[0]["staged"]);
+    assert_eq!(0, trees[0]["unstaged"]);
+    assert_eq!(1, trees[0]["untracked"]);
+    assert_eq!("dirty", trees[1]["state"]);
+    assert_eq!(0, trees[1]["ahead"]);
+    assert_eq!(1, trees[1]["behind"]);
+    assert_eq!(1, trees[1]["stashes"]);
+
+    // The default output is a table.
+    let (exit_status, output) = status(&["dev"]);
+    assert_eq!(exit_status, errors::EX_ERROR);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(
+        lines[0],
+        "tree  branch  upstream    ahead  behind  staged  unstaged  untracked  stash"
+    );
+    assert_eq!(
+        lines[1],
+        "dev   dev     origin/dev  0      1       0       0         0          1"
+    );
+
+    Ok(())
+}
+
+/// "garden sync" fetches and fast-forwards clean trees and skips the rest.
+#[test]
+#[named]
+fn sync() -> Result<()> {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = fixture.root();
+    let garden_args = ["--chdir", &root, "--config", "tests/data/branches.yaml"];
+    let mut grow_args = garden_args.to_vec();
+    grow_args.extend(["grow", "default", "dev", "custom-remote"]);
+    exec_garden(&grow_args)?;
+
+    let git_commit = [
+        "git",
+        "-c",
+        "user.name=Garden",
+        "-c",
+        "user.email=garden-tools@crates.io",
+        "commit",
+        "--quiet",
+        "--allow-empty",
+        "--message=local",
+    ];
+    // "default" has diverged from its upstream branch.
+    let default_worktree = fixture.worktree("default");
+    assert_cmd(
+        &["git", "reset", "--quiet", "--hard", "HEAD~1"],
+        &default_worktree,
+    );
+    assert_cmd(&git_commit, &default_worktree);
+    // "dev" is behind its upstream branch.
+    let dev_worktree = fixture.worktree("dev");
+    assert_cmd(
+        &["git", "reset", "--quiet", "--hard", "HEAD~1"],
+        &dev_worktree,
+    );
+
+    let mut sync_args = garden_args.to_vec();
+    sync_args.extend(["sync", "default", "dev", "local", "custom-remote"]);
+    let (exit_status, output, _err) = garden_exec(&sync_args);
+    assert_eq!(exit_status, errors::EX_OK);
+    let expect = [
+        "skipped default (default has diverged from origin/default: 1 ahead, 1 behind)",
+        "updated dev (dev: 1 commit from origin/dev)",
+        "skipped local (missing)",
+        "up-to-date custom-remote",
+        "1 updated, 1 up-to-date, 2 skipped, 0 failed",
+    ];
+    assert_eq!(output.lines().collect::<Vec<_>>(), expect);
+    let head = assert_cmd_capture(&["git", "rev-parse", "HEAD"], &dev_worktree);
+    let upstream = assert_cmd_capture(&["git", "rev-parse", "origin/dev"], &dev_worktree);
+    assert_eq!(head, upstream);
+
+    // Trees with uncommitted changes are not fast-forwarded.
+    assert_cmd(
+        &["git", "reset", "--quiet", "--hard", "HEAD~1"],
+        &dev_worktree,
+    );
+    std::fs::write(fixture.root_pathbuf().join("dev/untracked.txt"), "new")?;
+    let mut sync_args = garden_args.to_vec();
+    sync_args.extend(["sync", "dev"]);
+    let (exit_status, output, _err) = garden_exec(&sync_args);
+    assert_eq!(exit_status, errors::EX_OK);
+    let expect = [
+        "skipped dev (uncommitted changes)",
+        "0 updated, 0 up-to-date, 1 skipped, 0 failed",
+    ];
+    assert_eq!(output.lines().collect::<Vec<_>>(), expect);
+
+    Ok(())
+}
+
+/// Configuration errors include the location of the offending entry.
+#[test]
+fn configuration_error_location() {
+    let (status, _out, err) =
+        garden_exec(&["--config", "tests/data/locations/missing-graft.yaml", "ls"]);
+    assert_eq!(status, errors::EX_CONFIG);
+    assert_eq!(
+        err,
+        "error: configuration error: tests/data/locations/missing-graft.yaml:3: \
+        missing: invalid graft"
+    );
+}
+
 /// Test evaluating required variables.
 #[test]
 fn eval_required_variables() {
@@ -2491,3 +3613,284 @@ fn cmd_parallel_commands() {
     ]);
     assert_eq!(expect, actual);
 }
+
+/// "garden check" reports problems in garden files and exits with EX_CONFIG.
+#[test]
+fn check_reports_errors_and_warnings() {
+    let (status, out, err) = garden_exec(&["--chdir", "tests/data/check", "check"]);
+    assert_eq!(status, errors::EX_CONFIG);
+    assert_eq!(err, "21 error(s), 5 warning(s)");
+
+    let expect = [
+        "error[E001]: garden.yaml: garden.shel: unknown key \"shel\"",
+        "error[E001]: garden.yaml: variables.required-value.requird: unknown key \"requird\"",
+        "error[E006]: garden.yaml: commands.build<: \"build<\" refers to an undefined command: \"setup\"",
+        "warning[W003]: garden.yaml: commands.install>: \"install>\" has no base command: \"install\"",
+        "error[E001]: garden.yaml: templates.extended.path: unknown key \"path\"",
+        "error[E004]: garden.yaml: templates.extended.extend: undefined template: \"missing-template\"",
+        "error[E001]: garden.yaml: trees.tree.brnach: unknown key \"brnach\"",
+        "error[E004]: garden.yaml: trees.tree.templates: undefined template: \"undefined-template\"",
+        "error[E009]: garden.yaml: trees.tree.submodules: invalid value: expected true, false or \"recursive\"",
+        "error[E002]: garden.yaml: trees.extended.extend: undefined tree: \"missing-tree\"",
+        "error[E001]: garden.yaml: trees.parameters.templates.arg: unknown key \"arg\"",
+        "error[E008]: garden.yaml: trees.parameters.templates.args: template \"parameters\" has no parameter \"flavr\"",
+        "error[E002]: garden.yaml: trees.worktree.worktree: undefined worktree parent: \"missing-parent\"",
+        "error[E002]: garden.yaml: groups.invalid: undefined tree: \"missing-member\"",
+        "warning[W001]: garden.yaml: groups.pattern: pattern does not match any trees: \"nothing-*\"",
+        "error[E003]: garden.yaml: gardens.invalid.groups: undefined group: \"missing-group\"",
+        "error[E002]: garden.yaml: gardens.invalid.trees: undefined tree: \"missing-garden-tree\"",
+        "error[E001]: graft.yaml: trees.graft-tree.dept: unknown key \"dept\"",
+        "error[E005]: garden.yaml: grafts.missing: graft config file not found: \"missing-graft.yaml\"",
+        "error[E001]: includes.yaml: trees.included.descripton: unknown key \"descripton\"",
+        "warning[W002]: garden.yaml: garden.includes: include file not found: \"missing-include.yaml\"",
+    ];
+    let lines: Vec<&str> = out.lines().collect();
+    for line in expect {
+        assert!(lines.contains(&line), "missing: {line}");
+    }
+    // Valid references are not reported.
+    assert!(!out.contains("graft::graft-tree"));
+    assert!(!out.contains("included/child"));
+}
+
+/// "garden check" reports warnings and succeeds when no errors are found.
+#[test]
+fn check_warnings_only() {
+    let (status, out, err) = garden_exec(&[
+        "--chdir",
+        "tests/data/check",
+        "--config",
+        "warnings.yaml",
+        "check",
+    ]);
+    assert_eq!(status, errors::EX_OK);
+    assert_eq!(
+        out,
+        "warning[W003]: warnings.yaml: commands.lint>: \"lint>\" has no base command: \"lint\""
+    );
+    assert_eq!(err, "0 error(s), 1 warning(s)");
+}
+
+/// "garden check" succeeds when no problems are found.
+#[test]
+fn check_valid_config() {
+    let (status, out, err) = garden_exec(&[
+        "--chdir",
+        "tests/data/check",
+        "--config",
+        "valid.yaml",
+        "check",
+    ]);
+    assert_eq!(status, errors::EX_OK);
+    assert_eq!(out, "");
+    assert_eq!(err, "");
+}
+
+/// "garden config dump" prints the configuration with includes and templates flattened.
+#[test]
+fn config_dump() {
+    let (status, out, _err) =
+        garden_exec(&["--config", "tests/data/dump/garden.yaml", "config", "dump"]);
+    assert_eq!(status, errors::EX_OK);
+    assert!(out.starts_with("---\ngarden:\n  root: \"${GARDEN_CONFIG_DIR}\"\n"));
+    assert!(!out.contains("templates:"));
+    assert!(!out.contains("includes:"));
+    assert!(out.ends_with(
+        r#"variables:
+  host: "https://example.com"
+trees:
+  lib:
+    path: lib
+    url: "${host}/lib.git"
+    remotes:
+      upstream: "${host}/upstream/lib.git"
+  app:
+    path: app
+    url: "${host}/app.git"
+    variables:
+      flavor: debug
+    commands:
+      build: "make ${flavor}"
+groups:
+  all:
+    - app
+    - lib
+gardens:
+  dev:
+    groups:
+      - all
+    variables:
+      flavor: dev"#
+    ));
+}
+
+/// "garden config dump --evaluate" evaluates values for the trees matched by a query.
+#[test]
+fn config_dump_evaluate() {
+    let (status, out, _err) = garden_exec(&[
+        "--config",
+        "tests/data/dump/garden.yaml",
+        "--define",
+        "host=https://example.org",
+        "config",
+        "dump",
+        "--format=json",
+        "--evaluate",
+        "dev",
+    ]);
+    assert_eq!(status, errors::EX_OK);
+    let doc: serde_json::Value = serde_json::from_str(&out).unwrap();
+    let trees = doc["trees"].as_object().unwrap();
+    assert_eq!(
+        trees.keys().collect::<Vec<_>>(),
+        vec!["app", "lib"],
+        "trees are reported in query order"
+    );
+    let app = &doc["trees"]["app"];
+    assert_eq!(app["url"], "https://example.org/app.git");
+    assert_eq!(app["variables"]["flavor"], "dev");
+    assert_eq!(app["commands"]["build"], serde_json::json!(["make dev"]));
+    let lib = &doc["trees"]["lib"];
+    assert_eq!(
+        lib["remotes"]["upstream"],
+        "https://example.org/upstream/lib.git"
+    );
+    assert!(lib["path"]
+        .as_str()
+        .unwrap()
+        .ends_with("tests/data/dump/lib"));
+    assert_eq!(doc["variables"]["host"], "https://example.org");
+}
+
+/// "GARDEN_CACHE=1" caches parsed configurations until the files they were read from change.
+#[cfg(feature = "cache")]
+#[test]
+#[named]
+fn config_cache() {
+    let fixture = BareRepoFixture::new(function_name!());
+    // XDG_CACHE_HOME must be an absolute path.
+    let root = std::env::current_dir().unwrap().join(fixture.root());
+    let cache_dir = root.join("cache");
+    let config_dir = root.join("config");
+    let config_path = config_dir.join("garden.yaml");
+    std::fs::create_dir_all(config_dir.join("conf.d")).unwrap();
+    std::fs::write(&config_path, "garden:\n  includes: conf.d/*.yaml\n").unwrap();
+    std::fs::write(
+        config_dir.join("conf.d/a.yaml"),
+        "variables:\n  value: one\n",
+    )
+    .unwrap();
+
+    let cache_dir_str = cache_dir.to_string_lossy().to_string();
+    let config_path_str = config_path.to_string_lossy().to_string();
+    let env = [
+        ("GARDEN_CACHE", "1"),
+        ("XDG_CACHE_HOME", cache_dir_str.as_str()),
+    ];
+    let eval_value = || {
+        let (status, out, err) = garden_exec_with_env(
+            &[
+                "--config",
+                &config_path_str,
+                "-d",
+                "config",
+                "eval",
+                "${value}",
+            ],
+            &env,
+        );
+        assert_eq!(status, errors::EX_OK);
+        (out, err)
+    };
+
+    // The first command parses the configuration and writes the cache.
+    let (out, err) = eval_value();
+    assert_eq!(out, "one");
+    assert!(err.contains("cache: wrote"));
+    assert_eq!(
+        std::fs::read_dir(cache_dir.join("garden")).unwrap().count(),
+        1
+    );
+
+    // The cache is used when nothing has changed.
+    let (out, err) = eval_value();
+    assert_eq!(out, "one");
+    assert!(err.contains("cache: read"));
+
+    // Modifying an included file invalidates the cache.
+    std::fs::write(
+        config_dir.join("conf.d/a.yaml"),
+        "variables:\n  value: two\n",
+    )
+    .unwrap();
+    let (out, err) = eval_value();
+    assert_eq!(out, "two");
+    assert!(err.contains("cache: stale"));
+
+    // Adding a file that matches an include pattern invalidates the cache.
+    std::fs::write(
+        config_dir.join("conf.d/b.yaml"),
+        "variables:\n  value: three\n",
+    )
+    .unwrap();
+    let (out, err) = eval_value();
+    assert_eq!(out, "three");
+    assert!(err.contains("cache: stale"));
+
+    // The cache is not used unless it is enabled.
+    let (status, out, err) = garden_exec(&[
+        "--config",
+        &config_path_str,
+        "-d",
+        "config",
+        "eval",
+        "${value}",
+    ]);
+    assert_eq!(status, errors::EX_OK);
+    assert_eq!(out, "three");
+    assert!(!err.contains("cache:"));
+}
+
+/// Include paths that use variables are not cached.
+#[cfg(feature = "cache")]
+#[test]
+#[named]
+fn config_cache_variable_includes() {
+    let fixture = BareRepoFixture::new(function_name!());
+    let root = std::env::current_dir().unwrap().join(fixture.root());
+    let cache_dir = root.join("cache");
+    let config_dir = root.join("config");
+    let config_path = config_dir.join("garden.yaml");
+    std::fs::create_dir_all(config_dir.join("hosts")).unwrap();
+    std::fs::write(
+        &config_path,
+        "garden:\n  includes: hosts/${GARDEN_TEST_HOST}.yaml\n",
+    )
+    .unwrap();
+    for host in ["a", "b"] {
+        std::fs::write(
+            config_dir.join(format!("hosts/{host}.yaml")),
+            format!("variables:\n  value: {host}\n"),
+        )
+        .unwrap();
+    }
+
+    let cache_dir_str = cache_dir.to_string_lossy().to_string();
+    let config_path_str = config_path.to_string_lossy().to_string();
+    let eval_value = |host: &str| {
+        let env = [
+            ("GARDEN_CACHE", "1"),
+            ("GARDEN_TEST_HOST", host),
+            ("XDG_CACHE_HOME", cache_dir_str.as_str()),
+        ];
+        let (status, out, _err) =
+            garden_exec_with_env(&["--config", &config_path_str, "eval", "${value}"], &env);
+        assert_eq!(status, errors::EX_OK);
+        out
+    };
+
+    // Changing an environment variable between runs changes the included file.
+    assert_eq!(eval_value("a"), "a");
+    assert_eq!(eval_value("b"), "b");
+    assert!(!cache_dir.join("garden").exists());
+}
@@ -412,3 +412,333 @@ fn plant_keep_variables_with_templates() -> Result<()> {
 
     Ok(())
 }
+
+/// `garden plant` preserves comments and formatting when adding trees.
+#[test]
+#[named]
+fn plant_preserves_comments() -> Result<()> {
+    let fixture = common::BareRepoFixture::new(function_name!());
+    let fixture_root = fixture.root();
+    let mut config_pathbuf = fixture.root_pathbuf();
+    config_pathbuf.push("garden.yaml");
+    std::fs::copy("tests/data/plant-comments.yaml", &config_pathbuf)?;
+    let original = std::fs::read_to_string(&config_pathbuf)?;
+
+    // Create tests/tmp/plant_preserves_comments/alpha with two remotes.
+    let cmd = ["git", "init", "--quiet", "alpha"];
+    common::assert_cmd(&cmd, &fixture_root);
+    let worktree_alpha = fixture.worktree("alpha");
+    let cmd = ["git", "remote", "add", "origin", "alpha-url"];
+    common::assert_cmd(&cmd, &worktree_alpha);
+    let cmd = ["git", "remote", "add", "upstream", "upstream-url"];
+    common::assert_cmd(&cmd, &worktree_alpha);
+
+    // garden plant alpha
+    common::exec_garden(&["--chdir", &fixture_root, "plant", "alpha"])?;
+    let actual = std::fs::read_to_string(&config_pathbuf)?;
+    let expect = original.replace(
+        "\n# Groups are defined at the end.",
+        r#"
+  alpha:
+    remotes:
+      upstream: upstream-url
+    url: alpha-url
+
+# Groups are defined at the end."#,
+    );
+    assert_eq!(expect, actual);
+
+    // Re-planting an unchanged tree leaves the file as-is.
+    common::exec_garden(&["--chdir", &fixture_root, "plant", "alpha"])?;
+    let replanted = std::fs::read_to_string(&config_pathbuf)?;
+    assert_eq!(actual, replanted);
+
+    // Updating the url edits the value in-place.
+    let cmd = ["git", "remote", "set-url", "origin", "new-alpha-url"];
+    common::assert_cmd(&cmd, &worktree_alpha);
+    common::exec_garden(&["--chdir", &fixture_root, "plant", "alpha"])?;
+    let updated = std::fs::read_to_string(&config_pathbuf)?;
+    let expect = actual.replace("url: alpha-url", "url: new-alpha-url");
+    assert_eq!(expect, updated);
+
+    Ok(())
+}
+
+/// `garden plant --sort` moves trees along with their comments.
+#[test]
+#[named]
+fn plant_sort_preserves_comments() -> Result<()> {
+    let fixture = common::BareRepoFixture::new(function_name!());
+    let fixture_root = fixture.root();
+    let mut config_pathbuf = fixture.root_pathbuf();
+    config_pathbuf.push("garden.yaml");
+    std::fs::copy("tests/data/plant-comments.yaml", &config_pathbuf)?;
+
+    let cmd = ["git", "init", "--quiet", "alpha"];
+    common::assert_cmd(&cmd, &fixture_root);
+    let cmd = ["git", "remote", "add", "origin", "alpha-url"];
+    common::assert_cmd(&cmd, &fixture.worktree("alpha"));
+
+    // garden plant --sort alpha
+    common::exec_garden(&["--chdir", &fixture_root, "plant", "--sort", "alpha"])?;
+    let actual = std::fs::read_to_string(&config_pathbuf)?;
+    let expect = r#"# Garden file with comments and custom formatting.
+garden:
+  root: ""  # relative to this file
+
+variables:
+  host: https://example.com
+
+trees:
+  alpha:
+    url: alpha-url
+
+  # The main repository.
+  main:
+    url: "${host}/main.git"  # pinned
+    description: The main repository
+
+  zebra: {url: "${host}/zebra.git"}
+
+# Groups are defined at the end.
+groups:
+  all: [main, zebra]
+"#;
+    assert_eq!(expect, actual);
+
+    Ok(())
+}
+
+/// `garden plant --recursive` finds and plants repositories, bare repositories and
+/// worktrees below a directory.
+#[cfg(not(any(
+    target_arch = "aarch64",
+    target_arch = "powerpc64",
+    target_arch = "s390x",
+    target_arch = "x86"
+)))]
+#[test]
+#[named]
+fn plant_recursive() -> Result<()> {
+    let fixture = common::BareRepoFixture::new(function_name!());
+    let fixture_root = fixture.root();
+    common::exec_garden(&["--chdir", &fixture_root, "init", "--empty"])?;
+
+    // Create src/parent with a child worktree in src/child and a nested repository.
+    let cmd = ["git", "clone", "--quiet", "repos/example.git", "src/parent"];
+    common::assert_cmd(&cmd, &fixture_root);
+    let cmd = [
+        "git",
+        "worktree",
+        "add",
+        "--quiet",
+        "-B",
+        "dev",
+        "../child",
+        "origin/dev",
+    ];
+    common::assert_cmd(&cmd, &fixture.path("src/parent"));
+    let cmd = ["git", "init", "--quiet", "src/deep/nested/repo"];
+    common::assert_cmd(&cmd, &fixture_root);
+
+    // Depth options require "--recursive".
+    let (status, _out, err) =
+        common::garden_exec(&["--chdir", &fixture_root, "plant", "--max-depth=1", "src"]);
+    assert_eq!(status, 2);
+    assert!(err.contains("--recursive"));
+
+    // Repositories beyond the maximum depth are not planted.
+    common::exec_garden(&[
+        "--chdir",
+        &fixture_root,
+        "plant",
+        "--recursive",
+        "--max-depth=1",
+        "--group=discovered",
+        "src",
+    ])?;
+    let config_pathbuf = fixture.pathbuf("garden.yaml");
+    let actual = std::fs::read_to_string(&config_pathbuf)?;
+    let repos = std::fs::canonicalize(fixture.path("repos"))?;
+    let expect = format!(
+        r#"trees:
+  src/parent:
+    url: {}/example.git
+  src/child:
+    worktree: src/parent
+    branch: dev
+groups:
+  discovered:
+    - src/parent
+    - src/child
+"#,
+        repos.to_string_lossy()
+    );
+    assert!(actual.ends_with(&expect), "{actual}");
+
+    // Trees that have already been planted are skipped.
+    common::exec_garden(&["--chdir", &fixture_root, "plant", "--recursive", "."])?;
+    let app_context = garden::model::ApplicationContext::from_path_and_root(
+        &config_pathbuf,
+        Some(&fixture.root_pathbuf()),
+    )?;
+    let cfg = app_context.get_root_config();
+    let names: Vec<_> = cfg
+        .trees
+        .values()
+        .map(|tree| tree.get_name().as_str())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "src/parent",
+            "src/child",
+            "repos/example.git",
+            "src/deep/nested/repo"
+        ]
+    );
+    assert!(cfg.trees[2].is_bare_repository);
+
+    Ok(())
+}
+
+/// `garden plant --update` refreshes planted trees from their repositories.
+#[test]
+#[named]
+fn plant_update() -> Result<()> {
+    let fixture = common::BareRepoFixture::new(function_name!());
+    let fixture_root = fixture.root();
+    let mut config_pathbuf = fixture.root_pathbuf();
+    config_pathbuf.push("garden.yaml");
+    std::fs::copy("tests/data/plant-update.yaml", &config_pathbuf)?;
+
+    for name in ["example", "current", "variable"] {
+        let cmd = ["git", "init", "--quiet", name];
+        common::assert_cmd(&cmd, &fixture_root);
+        let cmd = ["git", "symbolic-ref", "HEAD", "refs/heads/main"];
+        common::assert_cmd(&cmd, &fixture.worktree(name));
+        let url = format!("https://example.com/{name}.git");
+        let cmd = ["git", "remote", "add", "origin", &url];
+        common::assert_cmd(&cmd, &fixture.worktree(name));
+    }
+    let cmd = ["git", "config", "checkout.defaultRemoteName", "upstream"];
+    common::assert_cmd(&cmd, &fixture.worktree("example"));
+    let cmd = [
+        "git",
+        "remote",
+        "add",
+        "upstream",
+        "https://new.example.com/example.git",
+    ];
+    common::assert_cmd(&cmd, &fixture.worktree("example"));
+
+    // Changes are summarized for each tree.
+    let (status, out, err) = common::garden_exec(&["--chdir", &fixture_root, "plant", "--update"]);
+    assert_eq!(status, garden::errors::EX_OK);
+    let expect = r#"updated example (url: https://old.example.com/example.git -> https://new.example.com/example.git, default-remote: upstream, branch: main, remotes.origin: https://example.com/example.git)
+updated current (branch: main)
+up-to-date variable
+skipped missing (missing)
+2 updated, 1 up-to-date, 1 skipped, 0 failed"#;
+    assert_eq!(expect, out);
+    assert_eq!(
+        "warning: variable: branch \"${variable_branch}\" is \"dev\" but \"main\" is checked out",
+        err
+    );
+
+    let actual = std::fs::read_to_string(&config_pathbuf)?;
+    let expect = r#"trees:
+  example:
+    url: "https://new.example.com/example.git"
+    # Settings that are not managed by "garden plant" are retained.
+    description: Example repository
+    commands:
+      build: make
+    remotes:
+      origin: "https://example.com/example.git"
+    default-remote: upstream
+    branch: main
+  current:
+    url: "${host}/current.git"
+    branch: main
+  # Branches that use variables are not replaced.
+  variable:
+    url: ${host}/variable.git
+    branch: ${variable_branch}
+  missing: ${host}/missing.git
+"#;
+    assert!(actual.ends_with(expect), "{actual}");
+
+    // Trees that match their repositories are up-to-date.
+    let (status, out, _err) =
+        common::garden_exec(&["--chdir", &fixture_root, "plant", "--update", "ex*"]);
+    assert_eq!(status, garden::errors::EX_OK);
+    assert_eq!(
+        "up-to-date example\n0 updated, 1 up-to-date, 0 skipped, 0 failed",
+        out
+    );
+
+    Ok(())
+}
+
+/// `garden plant --update` skips trees that are defined by included files.
+#[test]
+#[named]
+fn plant_update_skips_included_trees() -> Result<()> {
+    let fixture = common::BareRepoFixture::new(function_name!());
+    let fixture_root = fixture.root();
+    let config_pathbuf = fixture.root_pathbuf().join("garden.yaml");
+    std::fs::copy("tests/data/plant-update-includes.yaml", &config_pathbuf)?;
+    std::fs::copy(
+        "tests/data/plant-update-included.yaml",
+        fixture.root_pathbuf().join("plant-update-included.yaml"),
+    )?;
+    for name in ["example", "included"] {
+        let cmd = ["git", "init", "--quiet", name];
+        common::assert_cmd(&cmd, &fixture_root);
+        let cmd = ["git", "symbolic-ref", "HEAD", "refs/heads/main"];
+        common::assert_cmd(&cmd, &fixture.worktree(name));
+        let url = format!("https://example.com/{name}.git");
+        let cmd = ["git", "remote", "add", "origin", &url];
+        common::assert_cmd(&cmd, &fixture.worktree(name));
+    }
+    let original = std::fs::read_to_string(&config_pathbuf)?;
+
+    let (status, out, _err) = common::garden_exec(&["--chdir", &fixture_root, "plant", "--update"]);
+    assert_eq!(status, garden::errors::EX_OK);
+    let expect = r#"skipped included (defined in plant-update-included.yaml)
+updated example (branch: main)
+1 updated, 0 up-to-date, 1 skipped, 0 failed"#;
+    assert_eq!(expect, out);
+
+    // The included tree is not added to the root garden file.
+    let actual = std::fs::read_to_string(&config_pathbuf)?;
+    assert!(!actual.contains("included:"), "{actual}");
+    assert_ne!(original, actual);
+
+    Ok(())
+}
+
+/// `garden plant` warns when comments cannot be preserved.
+#[test]
+#[named]
+fn plant_warns_when_comments_are_dropped() -> Result<()> {
+    let fixture = common::BareRepoFixture::new(function_name!());
+    let fixture_root = fixture.root();
+    let config_pathbuf = fixture.root_pathbuf().join("garden.yaml");
+    // Flow-style documents cannot be edited in-place.
+    std::fs::write(&config_pathbuf, "# Flow-style garden file.\n{trees: {}}\n")?;
+
+    let (status, _out, err) =
+        common::garden_exec(&["--chdir", &fixture_root, "plant", "repos/example.git"]);
+    assert_eq!(status, garden::errors::EX_OK);
+    assert!(
+        err.ends_with("garden.yaml: unable to preserve comments and formatting"),
+        "{err}"
+    );
+    let actual = std::fs::read_to_string(&config_pathbuf)?;
+    assert!(actual.contains("repos/example.git"));
+    assert!(!actual.contains("# Flow-style garden file."));
+
+    Ok(())
+}
@@ -72,6 +72,97 @@ fn resolve_trees_group_with_wildcards() -> Result<()> {
     Ok(())
 }
 
+/// Return the tree names from a tree query.
+fn resolve_tree_names(query: &str) -> Result<Vec<String>> {
+    let app_context = common::garden_context()?;
+    let config = app_context.get_root_config();
+    let result = garden::query::resolve_trees(&app_context, config, None, query);
+
+    Ok(result.into_iter().map(|ctx| ctx.tree).collect())
+}
+
+#[test]
+fn resolve_trees_union() -> Result<()> {
+    assert_eq!(
+        vec!["annex/data", "annex/local", "cola"],
+        resolve_tree_names("%annex, @cola")?
+    );
+    // Trees are only reported once.
+    assert_eq!(vec!["cola", "git"], resolve_tree_names("%reverse + @git")?);
+
+    Ok(())
+}
+
+#[test]
+fn resolve_trees_difference() -> Result<()> {
+    let app_context = common::garden_context()?;
+    let config = app_context.get_root_config();
+    let result = garden::query::resolve_trees(&app_context, config, None, ":cola - @git");
+    assert_eq!(2, result.len());
+    assert_eq!(Some(string!("cola")), result[0].garden);
+    assert_eq!("cola", result[0].tree);
+    assert_eq!(Some(string!("cola")), result[1].garden);
+    assert_eq!("python/qtpy", result[1].tree);
+
+    assert_eq!(
+        vec!["cola", "python/qtpy"],
+        resolve_tree_names(":cola !@git")?
+    );
+    // A leading "-" excludes trees from the set of all trees.
+    assert_eq!(
+        vec!["git", "cola", "python/qtpy", "tmp", "oneline"],
+        resolve_tree_names("-%annex")?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn resolve_trees_intersection() -> Result<()> {
+    assert_eq!(vec!["git", "cola"], resolve_tree_names("%cola & %reverse")?);
+    // "&" binds more tightly than "," and parentheses override precedence.
+    assert_eq!(
+        vec!["annex/data", "annex/local"],
+        resolve_tree_names("%annex, @cola & @git")?
+    );
+    assert_eq!(
+        vec!["annex/data"],
+        resolve_tree_names("(%annex, @cola) & (%annex-1 + @git)")?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn parse_query() {
+    use garden::model::TreeQueryExpr;
+
+    let query = |value: &str| Box::new(TreeQueryExpr::Query(value.to_string()));
+    assert_eq!(
+        Some(TreeQueryExpr::Query(string!("tree-name"))),
+        garden::query::parse_query("tree-name")
+    );
+    assert_eq!(
+        Some(TreeQueryExpr::Difference(
+            Box::new(TreeQueryExpr::Union(query("a"), query("b"))),
+            query("[!c]*")
+        )),
+        garden::query::parse_query("a,b - [!c]*")
+    );
+    assert_eq!(
+        Some(TreeQueryExpr::Union(
+            query("a"),
+            Box::new(TreeQueryExpr::Intersection(query("b"), query("c")))
+        )),
+        garden::query::parse_query("a + b&c")
+    );
+    // Invalid expressions.
+    assert_eq!(None, garden::query::parse_query("(a, b"));
+    assert_eq!(None, garden::query::parse_query("a b"));
+    assert_eq!(None, garden::query::parse_query("a &"));
+    assert_eq!(None, garden::query::parse_query(""));
+}
+
 #[test]
 fn trees_from_pattern() -> Result<()> {
     let app_context = common::garden_context()?;
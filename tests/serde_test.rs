@@ -0,0 +1,162 @@
+pub mod common;
+
+use anyhow::{Context, Result};
+
+use garden::string;
+
+/// Configuration with trees, templates, groups, gardens and grafts.
+fn serde_config() -> String {
+    string!(
+        r#"
+    garden:
+        root: /tmp/serde
+    variables:
+        host: https://example.com
+        name:
+            value: serde
+            required: true
+    templates:
+        base:
+            variables:
+                flavor: release
+            commands:
+                build: make ${flavor}
+    trees:
+        example:
+            templates: base
+            url: ${host}/${name}.git
+            remotes:
+                upstream: ${host}/upstream/${name}.git
+            environment:
+                PATH: ${TREE_PATH}/bin
+            gitconfig:
+                user.name: A U Thor
+    groups:
+        all: example
+    gardens:
+        dev:
+            groups: all
+            variables:
+                flavor: debug
+    "#
+    )
+}
+
+/// Configurations can be serialized and deserialized.
+#[test]
+fn configuration_round_trip() -> Result<()> {
+    let app_context = common::garden_context_from_string(&serde_config())?;
+    let config = app_context.get_root_config();
+
+    let json = serde_json::to_string(config)?;
+    let copy: garden::model::Configuration = serde_json::from_str(&json)?;
+    assert_eq!(json, serde_json::to_string(&copy)?);
+
+    // Templates are serialized alongside the trees that use them.
+    let template = copy.templates.get("base").context("base template")?;
+    assert_eq!(template.get_name(), "base");
+    assert_eq!(
+        template.tree.variables.get("flavor").unwrap().get_expr(),
+        "release"
+    );
+
+    let tree = copy.get_tree("example").context("example tree")?;
+    assert_eq!(tree.get_name(), "example");
+    assert_eq!(
+        tree.remotes.get("origin").unwrap().get_expr(),
+        "${host}/${name}.git"
+    );
+    assert_eq!(tree.variables.get("flavor").unwrap().get_expr(), "release");
+    assert_eq!(tree.environment[0].get_name(), "PATH");
+    assert_eq!(
+        tree.gitconfig.get("user.name").unwrap()[0].get_expr(),
+        "A U Thor"
+    );
+    assert_eq!(tree.get_locations()[0].line, 16);
+
+    let group = copy.groups.get("all").context("all group")?;
+    assert_eq!(group.get_name(), "all");
+    assert!(group.members.contains("example"));
+
+    let garden = copy.get_garden("dev").context("dev garden")?;
+    assert_eq!(garden.get_name(), "dev");
+    assert_eq!(garden.variables.get("flavor").unwrap().get_expr(), "debug");
+
+    Ok(())
+}
+
+/// Variables are serialized with their expressions and evaluated values.
+#[test]
+fn variable_values() -> Result<()> {
+    let app_context = common::garden_context_from_string(&serde_config())?;
+    let config = app_context.get_root_config();
+
+    // Unevaluated variables only contain their expression.
+    let host = config.variables.get("host").context("host")?;
+    let value = serde_json::to_value(host)?;
+    assert_eq!(
+        value,
+        serde_json::json!({"name": "host", "expr": "https://example.com", "locations": [
+            {"path": null, "line": 5}
+        ]})
+    );
+
+    // Required variables record the "required" flag.
+    let name = config.variables.get("name").context("name")?;
+    let value = serde_json::to_value(name)?;
+    assert_eq!(value["required"], true);
+
+    // Evaluated variables contain their value.
+    let url = garden::eval::value(&app_context, config, "${host}/${name}.git");
+    assert_eq!(url, "https://example.com/serde.git");
+    let value = serde_json::to_value(host)?;
+    assert_eq!(value["expr"], "https://example.com");
+    assert_eq!(value["value"], "https://example.com");
+
+    // Values survive a round-trip.
+    let copy: garden::model::Variable = serde_json::from_value(value)?;
+    assert_eq!(copy.get_expr(), "https://example.com");
+    assert_eq!(
+        copy.get_value().map(String::as_str),
+        Some("https://example.com")
+    );
+
+    // Evaluated tree paths are serialized alongside their expressions.
+    let tree = config.get_tree("example").context("example tree")?;
+    let value = serde_json::to_value(tree)?;
+    assert_eq!(value["path"]["expr"], "example");
+    assert_eq!(value["path"]["value"], "/tmp/serde/example");
+
+    Ok(())
+}
+
+/// Grafts can be serialized and deserialized.
+#[test]
+fn graft_round_trip() -> Result<()> {
+    let graft = garden::model::Graft::new(string!("graft"), string!("libs"), string!("graft.yaml"));
+    let json = serde_json::to_string(&graft)?;
+    let copy: garden::model::Graft = serde_json::from_str(&json)?;
+    assert_eq!(copy.get_name(), "graft");
+    assert_eq!(copy.root, "libs");
+    assert_eq!(copy.config, "graft.yaml");
+    assert_eq!(json, serde_json::to_string(&copy)?);
+
+    Ok(())
+}
+
+/// Tree contexts can be serialized and deserialized.
+#[test]
+fn tree_context_round_trip() -> Result<()> {
+    let app_context = common::garden_context_from_string(&serde_config())?;
+    let config = app_context.get_root_config();
+    let context = garden::query::tree_context(&app_context, config, "example", Some("dev"))?;
+
+    let json = serde_json::to_string(&context)?;
+    let copy: garden::model::TreeContext = serde_json::from_str(&json)?;
+    assert_eq!(copy.tree, "example");
+    assert_eq!(copy.garden.as_deref(), Some("dev"));
+    assert_eq!(copy.config, None);
+    assert_eq!(json, serde_json::to_string(&copy)?);
+
+    Ok(())
+}